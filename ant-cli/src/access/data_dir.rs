@@ -26,3 +26,8 @@ pub fn get_client_data_dir_path() -> Result<PathBuf> {
         })?;
     Ok(home_dirs)
 }
+
+/// Path of the local ledger of the payments made by the client.
+pub fn get_payment_ledger_path() -> Result<PathBuf> {
+    Ok(get_client_data_dir_path()?.join("payments"))
+}
@@ -6,15 +6,18 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
+use std::hash::Hash;
+use std::time::{Duration, UNIX_EPOCH};
 
 use autonomi::{
     client::{
         files::{archive_private::PrivateArchiveDataMap, archive_public::ArchiveAddress},
         register::RegisterAddress,
-        vault::UserData,
+        vault::{AppData, UserData},
     },
     data::DataAddress,
+    AddressParseError, Bytes, GraphEntryAddress, PointerAddress, ScratchpadAddress,
 };
 use color_eyre::eyre::Result;
 
@@ -32,11 +35,19 @@ pub fn get_local_user_data() -> Result<UserData> {
     let file_archives = get_local_public_file_archives()?;
     let private_file_archives = get_local_private_file_archives()?;
     let registers = get_local_registers()?;
+    let pointers = get_local_named_addresses("pointers", PointerAddress::from_hex)?;
+    let scratchpads = get_local_named_addresses("scratchpads", ScratchpadAddress::from_hex)?;
+    let graph_entries = get_local_named_addresses("graph_entries", GraphEntryAddress::from_hex)?;
+    let app_data = get_local_app_data()?;
 
     let user_data = UserData {
         file_archives,
         private_file_archives,
         register_addresses: registers,
+        pointers,
+        scratchpads,
+        graph_entries,
+        app_data,
     };
     Ok(user_data)
 }
@@ -95,6 +106,84 @@ pub fn get_local_registers() -> Result<HashMap<RegisterAddress, String>> {
     Ok(registers)
 }
 
+/// Get the addresses stored in a user data directory, each file being named after an address and containing its name
+fn get_local_named_addresses<T: Eq + Hash>(
+    dir: &str,
+    from_hex: impl Fn(&str) -> Result<T, AddressParseError>,
+) -> Result<HashMap<T, String>> {
+    let data_dir = get_client_data_dir_path()?;
+    let addresses_path = data_dir.join("user_data").join(dir);
+    std::fs::create_dir_all(&addresses_path)?;
+
+    let mut addresses = HashMap::new();
+    for entry in walkdir::WalkDir::new(addresses_path)
+        .min_depth(1)
+        .max_depth(1)
+    {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy();
+        let address = from_hex(&file_name)?;
+        let name = std::fs::read_to_string(entry.path())?;
+        addresses.insert(address, name);
+    }
+    Ok(addresses)
+}
+
+fn write_local_named_address(dir: &str, address: String, name: &str) -> Result<()> {
+    let data_dir = get_client_data_dir_path()?;
+    let addresses_path = data_dir.join("user_data").join(dir);
+    std::fs::create_dir_all(&addresses_path)?;
+    std::fs::write(addresses_path.join(address), name)?;
+    Ok(())
+}
+
+/// Get the data of apps, each file being named after the hex encoded app name,
+/// with its modification time as the time the data was last updated
+pub fn get_local_app_data() -> Result<BTreeMap<String, AppData>> {
+    let data_dir = get_client_data_dir_path()?;
+    let app_data_path = data_dir.join("user_data").join("app_data");
+    std::fs::create_dir_all(&app_data_path)?;
+
+    let mut app_data = BTreeMap::new();
+    for entry in walkdir::WalkDir::new(app_data_path)
+        .min_depth(1)
+        .max_depth(1)
+    {
+        let entry = entry?;
+        let file_name = entry.file_name().to_string_lossy();
+        let app = String::from_utf8(hex::decode(file_name.as_ref())?)?;
+        let data = std::fs::read(entry.path())?;
+        let updated = entry
+            .metadata()?
+            .modified()?
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        app_data.insert(
+            app,
+            AppData {
+                data: Bytes::from(data),
+                updated,
+            },
+        );
+    }
+    Ok(app_data)
+}
+
+pub fn write_local_app_data(app: &str, app_data: &AppData) -> Result<()> {
+    let data_dir = get_client_data_dir_path()?;
+    let app_data_path = data_dir.join("user_data").join("app_data");
+    std::fs::create_dir_all(&app_data_path)?;
+    let file_path = app_data_path.join(hex::encode(app));
+    std::fs::write(&file_path, &app_data.data)?;
+    // keep when the data was last updated, to know which is the most recent when syncing the vault
+    std::fs::File::options()
+        .write(true)
+        .open(file_path)?
+        .set_modified(UNIX_EPOCH + Duration::from_millis(app_data.updated))?;
+    Ok(())
+}
+
 pub fn get_name_of_local_register_with_address(address: &RegisterAddress) -> Result<String> {
     let data_dir = get_client_data_dir_path()?;
     let user_data_path = data_dir.join("user_data");
@@ -137,6 +226,22 @@ pub fn write_local_user_data(user_data: &UserData) -> Result<()> {
         write_local_register(register, name)?;
     }
 
+    for (pointer, name) in user_data.pointers.iter() {
+        write_local_named_address("pointers", pointer.to_hex(), name)?;
+    }
+
+    for (scratchpad, name) in user_data.scratchpads.iter() {
+        write_local_named_address("scratchpads", scratchpad.to_hex(), name)?;
+    }
+
+    for (graph_entry, name) in user_data.graph_entries.iter() {
+        write_local_named_address("graph_entries", graph_entry.to_hex(), name)?;
+    }
+
+    for (app, data) in user_data.app_data.iter() {
+        write_local_app_data(app, data)?;
+    }
+
     Ok(())
 }
 
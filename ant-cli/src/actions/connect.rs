@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use autonomi::client::config::ClientOperatingStrategy;
+use autonomi::client::config::{ClientOperatingStrategy, PaymentPolicy};
 use autonomi::{get_evm_network, Client, ClientConfig, InitialPeersConfig};
 use color_eyre::eyre::eyre;
 use indicatif::ProgressBar;
@@ -17,12 +17,13 @@ use crate::exit_code::{connect_error_exit_code, evm_util_error_exit_code, ExitCo
 pub async fn connect_to_network(
     init_peers_config: InitialPeersConfig,
 ) -> Result<Client, ExitCodeError> {
-    connect_to_network_with_config(init_peers_config, Default::default()).await
+    connect_to_network_with_config(init_peers_config, Default::default(), Default::default()).await
 }
 
 pub async fn connect_to_network_with_config(
     init_peers_config: InitialPeersConfig,
     operation_config: ClientOperatingStrategy,
+    payment_policy: PaymentPolicy,
 ) -> Result<Client, ExitCodeError> {
     let progress_bar = ProgressBar::new_spinner();
     progress_bar.enable_steady_tick(Duration::from_millis(120));
@@ -41,11 +42,18 @@ pub async fn connect_to_network_with_config(
         (err.into(), exit_code)
     })?;
 
-    let config = ClientConfig {
-        init_peers_config,
-        evm_network,
-        strategy: operation_config,
-    };
+    let payment_ledger = crate::access::data_dir::get_payment_ledger_path()
+        .inspect_err(|err| warn!("Not recording the payments, no path for the ledger: {err:?}"))
+        .ok();
+
+    let mut config = ClientConfig::default()
+        .with_init_peers_config(init_peers_config)
+        .with_evm_network(evm_network)
+        .with_strategy(operation_config)
+        .with_payment_policy(payment_policy);
+    if let Some(path) = payment_ledger {
+        config = config.with_payment_ledger(path);
+    }
 
     let res = Client::init_with_config(config).await;
 
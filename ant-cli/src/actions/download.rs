@@ -7,7 +7,9 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::get_progress_bar;
-use crate::exit_code::{self, ExitCodeError, INVALID_INPUT_EXIT_CODE, IO_ERROR};
+use crate::exit_code::{
+    self, ExitCodeError, HASH_MISMATCH_EXIT_CODE, INVALID_INPUT_EXIT_CODE, IO_ERROR,
+};
 use autonomi::{
     chunk::DataMapChunk,
     client::{
@@ -16,11 +18,11 @@ use autonomi::{
         GetError,
     },
     data::DataAddress,
-    files::{PrivateArchive, PublicArchive},
+    files::{ContentHash, ContentInfo, PrivateArchive, PublicArchive},
     Client,
 };
 use color_eyre::{eyre::eyre, Section};
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 
 pub async fn download(addr: &str, dest_path: &str, client: &Client) -> Result<(), ExitCodeError> {
     let try_public_address = DataAddress::from_hex(addr).ok();
@@ -64,7 +66,7 @@ async fn download_private(
     download_priv_archive_to_disk(addr, archive, dest_path, client).await
 }
 
-async fn download_priv_archive_to_disk(
+pub async fn download_priv_archive_to_disk(
     addr: &str,
     archive: PrivateArchive,
     dest_path: &str,
@@ -86,6 +88,10 @@ async fn download_priv_archive_to_disk(
                 continue;
             }
         };
+        if let Some(err) = check_content_hash(path, &bytes, archive.content_info(path)) {
+            all_errs.push(err);
+            continue;
+        }
 
         let path = PathBuf::from(dest_path).join(path);
         let here = PathBuf::from(".");
@@ -100,9 +106,8 @@ async fn download_priv_archive_to_disk(
         progress_bar.finish_and_clear();
     }
 
-    match last_error {
-        Some(e) => {
-            let exit_code = exit_code::get_error_exit_code(&e);
+    match download_errors_exit_code(last_error, &all_errs) {
+        Some(exit_code) => {
             let err_no = all_errs.len();
             eprintln!("{err_no} errors while downloading private data with local address: {addr}");
             eprintln!("{all_errs:#?}");
@@ -142,7 +147,7 @@ async fn download_public(
     download_pub_archive_to_disk(addr, archive, dest_path, client).await
 }
 
-async fn download_pub_archive_to_disk(
+pub async fn download_pub_archive_to_disk(
     addr: &str,
     archive: PublicArchive,
     dest_path: &str,
@@ -164,6 +169,10 @@ async fn download_pub_archive_to_disk(
                 continue;
             }
         };
+        if let Some(err) = check_content_hash(path, &bytes, archive.content_info(path)) {
+            all_errs.push(err);
+            continue;
+        }
 
         let path = PathBuf::from(dest_path).join(path);
         let here = PathBuf::from(".");
@@ -178,9 +187,8 @@ async fn download_pub_archive_to_disk(
         progress_bar.finish_and_clear();
     }
 
-    match last_error {
-        Some(e) => {
-            let exit_code = exit_code::get_error_exit_code(&e);
+    match download_errors_exit_code(last_error, &all_errs) {
+        Some(exit_code) => {
             let err_no = all_errs.len();
             eprintln!("{err_no} errors while downloading data at: {addr}");
             eprintln!("{all_errs:#?}");
@@ -264,3 +272,26 @@ async fn download_from_datamap(
         }
     }
 }
+
+/// Check downloaded content against the hash recorded in the archive, if any.
+fn check_content_hash(
+    path: &Path,
+    content: &[u8],
+    content_info: Option<&ContentInfo>,
+) -> Option<String> {
+    let expected = content_info?.hash.as_ref()?;
+    let actual = ContentHash::sha256(content);
+    (actual != *expected).then(|| {
+        format!("Content of {path:?} does not match its recorded hash: expected {expected}, got {actual}")
+    })
+}
+
+/// The exit code for the errors of an archive download, if there were any.
+/// Errors without a network error are hash mismatches.
+fn download_errors_exit_code(last_error: Option<GetError>, all_errs: &[String]) -> Option<i32> {
+    match last_error {
+        Some(e) => Some(exit_code::get_error_exit_code(&e)),
+        None if !all_errs.is_empty() => Some(HASH_MISMATCH_EXIT_CODE),
+        None => None,
+    }
+}
@@ -11,5 +11,5 @@ mod download;
 mod progress_bar;
 
 pub use connect::{connect_to_network, connect_to_network_with_config};
-pub use download::download;
+pub use download::{download, download_priv_archive_to_disk, download_pub_archive_to_disk};
 pub use progress_bar::get_progress_bar;
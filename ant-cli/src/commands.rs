@@ -8,14 +8,16 @@
 
 mod analyze;
 mod file;
+mod folder;
 mod register;
 mod vault;
 mod wallet;
 
 use crate::opt::Opt;
-use autonomi::ResponseQuorum;
+use autonomi::{AttoTokens, ResponseQuorum};
 use clap::{error::ErrorKind, CommandFactory as _, Subcommand};
 use color_eyre::Result;
+use std::path::PathBuf;
 
 #[derive(Subcommand, Debug)]
 pub enum SubCmd {
@@ -76,6 +78,83 @@ pub enum FileCmd {
         /// Optional: Specify the maximum fee per gas in u128.
         #[arg(long)]
         max_fee_per_gas: Option<u128>,
+        /// Resume a previously interrupted upload of the same file.
+        ///
+        /// Files and chunks that were already uploaded are skipped, and payments that were made
+        /// are reused as long as their quotes have not expired.
+        #[arg(long)]
+        resume: bool,
+        /// Optional: Pay for the upload with a receipt exported by `file pay` instead of the wallet.
+        ///
+        /// The archive written by `file pay` next to the receipt, with the `.archive` extension, must be there too.
+        #[arg(long)]
+        receipt: Option<String>,
+        /// Optional: The maximum amount of tokens to spend on the upload, e.g. "0.5".
+        ///
+        /// The upload stops before paying more than this amount.
+        #[arg(long)]
+        max_cost: Option<AttoTokens>,
+        /// Show the quoted cost of each payment of the upload and ask for confirmation before paying it.
+        ///
+        /// The upload pays for its chunks in several payments, the following ones can be confirmed at once.
+        /// Not available with `--receipt`, as the upload is already paid for.
+        #[arg(long, conflicts_with = "receipt")]
+        confirm: bool,
+        /// Record the SHA-256 hash of every file in the archive, so that downloads are checked against it.
+        ///
+        /// With `--receipt`, the hashes are the ones recorded by `file pay --hash`.
+        #[arg(long)]
+        hash: bool,
+    },
+
+    /// Pay for the upload of a file without uploading it, and export the receipt of the payment.
+    ///
+    /// The receipt can then be used to upload the file from another machine with `file upload --receipt`,
+    /// as long as the file does not change and the upload happens within an hour, before the quotes expire.
+    /// The archive paid for is written next to the receipt, with the `.archive` extension, and is needed for the upload.
+    Pay {
+        /// The file to pay for.
+        file: String,
+        /// Pay for a public upload of the file. This must match the visibility of the upload.
+        #[arg(short, long)]
+        public: bool,
+        /// The path to write the receipt to.
+        #[arg(short, long)]
+        output: String,
+        /// Optional: A receipt of a previous payment, e.g. the partial receipt of a failed payment.
+        /// The chunks it covers are not paid for again, and the receipt written includes them.
+        #[arg(long)]
+        receipt: Option<String>,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+        /// Record the SHA-256 hash of every file in the archive paid for, see `file upload --hash`.
+        #[arg(long)]
+        hash: bool,
+    },
+
+    /// Upload the changes made to a directory since it was uploaded to the given archive.
+    ///
+    /// Files whose size and modification time did not change are not uploaded again,
+    /// the new archive refers to their previous content. The visibility of the archive is kept.
+    Sync {
+        /// The directory to sync.
+        dir: String,
+        /// The address of the archive the directory was previously uploaded to.
+        archive: String,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+    },
+
+    /// Show the files added, removed, modified or renamed from one archive to another.
+    ///
+    /// Both archives must have the same visibility, public or private.
+    Diff {
+        /// The address of the base archive.
+        a: String,
+        /// The address of the archive to compare with the base archive.
+        b: String,
     },
 
     /// Download a file from the given address.
@@ -93,6 +172,103 @@ pub enum FileCmd {
 
     /// List previous uploads
     List,
+
+    /// Operations on folders: archives that can be changed while keeping the same address.
+    Folder {
+        #[command(subcommand)]
+        command: FolderCmd,
+    },
+}
+
+#[derive(Subcommand, Debug)]
+pub enum FolderCmd {
+    /// Create a new empty folder. Folders are private by default.
+    Create {
+        /// The name of the folder, used with your register key to derive its address.
+        name: String,
+        /// Create the folder as public. Everyone can see public data on the Network.
+        #[arg(short, long)]
+        public: bool,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+    },
+
+    /// Upload a file and publish a new version of the folder with it.
+    Add {
+        /// The name of the folder.
+        name: String,
+        /// The file to add.
+        file: String,
+        /// Optional: The path of the file in the folder, defaults to the file name.
+        #[arg(long)]
+        path: Option<String>,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+    },
+
+    /// Publish a new version of the folder without the given file.
+    Remove {
+        /// The name of the folder.
+        name: String,
+        /// The path of the file in the folder.
+        path: String,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+    },
+
+    /// Publish a new version of the folder with a file renamed.
+    Rename {
+        /// The name of the folder.
+        name: String,
+        /// The current path of the file in the folder.
+        from: String,
+        /// The new path of the file in the folder.
+        to: String,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+    },
+
+    /// List the files in the latest version of a folder.
+    List {
+        /// Use the name of the folder instead of the address
+        /// Note that only the owner of the folder can use this shorthand, and that private folders can only be read this way.
+        #[arg(short, long)]
+        name: bool,
+        /// The address of the folder
+        /// With the name option on the address will be used as a name
+        address: String,
+    },
+
+    /// Show the versions of a folder, from the first to the latest.
+    History {
+        /// Use the name of the folder instead of the address
+        /// Note that only the owner of the folder can use this shorthand, and that private folders can only be read this way.
+        #[arg(short, long)]
+        name: bool,
+        /// The address of the folder
+        /// With the name option on the address will be used as a name
+        address: String,
+    },
+
+    /// Download a version of a folder, the latest by default.
+    Download {
+        /// Use the name of the folder instead of the address
+        /// Note that only the owner of the folder can use this shorthand, and that private folders can only be read this way.
+        #[arg(short, long)]
+        name: bool,
+        /// The address of the folder
+        /// With the name option on the address will be used as a name
+        address: String,
+        /// The destination directory.
+        dest_path: String,
+        /// Optional: The version to download, as numbered by `file folder history`.
+        #[arg(long)]
+        version: Option<usize>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -205,10 +381,25 @@ pub enum VaultCmd {
     /// Pushes your local user data to the network.
     Sync {
         /// Force push your local user data to the network.
-        /// This will overwrite any existing data in your vault.
+        /// This will overwrite any existing data in your vault, the previous data is kept as a snapshot.
         #[arg(short, long)]
         force: bool,
     },
+
+    /// List the snapshots of your vault.
+    /// A snapshot of the vault is kept every time it is synced or restored.
+    History,
+
+    /// Restore your vault from one of its snapshots, as numbered by `vault history`.
+    /// The current content of the vault is snapshotted first, so the restore can be undone.
+    /// Use `vault load` afterwards to also restore your local user data.
+    Restore {
+        /// The number of the snapshot to restore.
+        snapshot: usize,
+        /// Optional: Specify the maximum fee per gas in u128.
+        #[arg(long)]
+        max_fee_per_gas: Option<u128>,
+    },
 }
 
 #[derive(Subcommand, Debug)]
@@ -221,12 +412,28 @@ pub enum WalletCmd {
         /// Optional password to encrypt the wallet with.
         #[clap(long, short)]
         password: Option<String>,
+        /// Derive the wallet from a new BIP-39 mnemonic phrase, which is printed to back up the wallet.
+        #[clap(long, action)]
+        mnemonic: bool,
     },
 
     /// Import an existing wallet.
+    ///
+    /// The wallet is either given as a hex-encoded private key, recovered from a BIP-39 mnemonic
+    /// phrase, or decrypted from an Ethereum V3 JSON keystore file.
     Import {
         /// Hex-encoded private key.
-        private_key: String,
+        #[clap(required_unless_present_any = ["mnemonic", "keystore"], conflicts_with_all = ["mnemonic", "keystore"])]
+        private_key: Option<String>,
+        /// Recover the wallet from a BIP-39 mnemonic phrase, which is prompted for.
+        #[clap(long, action, conflicts_with = "keystore")]
+        mnemonic: bool,
+        /// The BIP-44 account index to derive from the mnemonic phrase.
+        #[clap(long, default_value_t = 0, requires = "mnemonic")]
+        account_index: u32,
+        /// Path to an Ethereum V3 JSON keystore file, its password is prompted for.
+        #[clap(long)]
+        keystore: Option<PathBuf>,
         /// Optional flag to not add a password.
         #[clap(long, action)]
         no_password: bool,
@@ -236,10 +443,47 @@ pub enum WalletCmd {
     },
 
     /// Print the private key of a wallet.
-    Export,
+    Export {
+        /// Write the wallet to an Ethereum V3 JSON keystore file in this directory instead.
+        #[clap(long)]
+        keystore: Option<PathBuf>,
+    },
 
     /// Check the balance of the wallet.
     Balance,
+
+    /// Check how many tokens the data payments contract is approved to spend from the wallet.
+    Allowance,
+
+    /// Revoke the approval of the data payments contract to spend tokens from the wallet.
+    ///
+    /// The next payment approves the amount it needs again.
+    Revoke,
+
+    /// Show the payments made for storing data, from the local payment history.
+    History {
+        /// Only payments made at or after this UNIX timestamp, in seconds.
+        #[arg(long)]
+        since: Option<u64>,
+        /// Only payments made before this UNIX timestamp, in seconds.
+        #[arg(long)]
+        until: Option<u64>,
+        /// Only payments made by this wallet address.
+        #[arg(long)]
+        wallet: Option<String>,
+        /// Only payments for this type of data.
+        #[arg(long, value_enum)]
+        data_type: Option<wallet::HistoryDataType>,
+        /// Only payments for the data at this hex-encoded address.
+        #[arg(long)]
+        address: Option<String>,
+        /// Output format of the payments.
+        #[arg(long, value_enum, default_value = "table")]
+        format: wallet::HistoryFormat,
+        /// Write the payments to this file instead of the standard output.
+        #[arg(short, long)]
+        output: Option<PathBuf>,
+    },
 }
 
 pub async fn handle_subcommand(opt: Opt) -> Result<()> {
@@ -253,9 +497,64 @@ pub async fn handle_subcommand(opt: Opt) -> Result<()> {
                 public,
                 quorum,
                 max_fee_per_gas,
+                resume,
+                receipt,
+                max_cost,
+                confirm,
+                hash,
+            } => {
+                if let Err((err, exit_code)) = file::upload(
+                    &file,
+                    public,
+                    opt.peers,
+                    quorum,
+                    max_fee_per_gas,
+                    resume,
+                    receipt,
+                    max_cost,
+                    confirm,
+                    hash,
+                )
+                .await
+                {
+                    eprintln!("{err:?}");
+                    std::process::exit(exit_code);
+                } else {
+                    Ok(())
+                }
+            }
+            FileCmd::Pay {
+                file,
+                public,
+                output,
+                receipt,
+                max_fee_per_gas,
+                hash,
+            } => {
+                if let Err((err, exit_code)) = file::pay(
+                    &file,
+                    public,
+                    &output,
+                    receipt,
+                    opt.peers,
+                    max_fee_per_gas,
+                    hash,
+                )
+                .await
+                {
+                    eprintln!("{err:?}");
+                    std::process::exit(exit_code);
+                } else {
+                    Ok(())
+                }
+            }
+            FileCmd::Sync {
+                dir,
+                archive,
+                max_fee_per_gas,
             } => {
                 if let Err((err, exit_code)) =
-                    file::upload(&file, public, opt.peers, quorum, max_fee_per_gas).await
+                    file::sync(&dir, &archive, opt.peers, max_fee_per_gas).await
                 {
                     eprintln!("{err:?}");
                     std::process::exit(exit_code);
@@ -263,6 +562,14 @@ pub async fn handle_subcommand(opt: Opt) -> Result<()> {
                     Ok(())
                 }
             }
+            FileCmd::Diff { a, b } => {
+                if let Err((err, exit_code)) = file::diff(&a, &b, opt.peers).await {
+                    eprintln!("{err:?}");
+                    std::process::exit(exit_code);
+                } else {
+                    Ok(())
+                }
+            }
             FileCmd::Download {
                 addr,
                 dest_file,
@@ -278,6 +585,50 @@ pub async fn handle_subcommand(opt: Opt) -> Result<()> {
                 }
             }
             FileCmd::List => file::list(),
+            FileCmd::Folder { command } => {
+                let res = match command {
+                    FolderCmd::Create {
+                        name,
+                        public,
+                        max_fee_per_gas,
+                    } => folder::create(&name, public, opt.peers, max_fee_per_gas).await,
+                    FolderCmd::Add {
+                        name,
+                        file,
+                        path,
+                        max_fee_per_gas,
+                    } => folder::add(&name, &file, path, opt.peers, max_fee_per_gas).await,
+                    FolderCmd::Remove {
+                        name,
+                        path,
+                        max_fee_per_gas,
+                    } => folder::remove(&name, &path, opt.peers, max_fee_per_gas).await,
+                    FolderCmd::Rename {
+                        name,
+                        from,
+                        to,
+                        max_fee_per_gas,
+                    } => folder::rename(&name, &from, &to, opt.peers, max_fee_per_gas).await,
+                    FolderCmd::List { name, address } => {
+                        folder::list(&address, name, opt.peers).await
+                    }
+                    FolderCmd::History { name, address } => {
+                        folder::history(&address, name, opt.peers).await
+                    }
+                    FolderCmd::Download {
+                        name,
+                        address,
+                        dest_path,
+                        version,
+                    } => folder::download(&address, name, &dest_path, version, opt.peers).await,
+                };
+                if let Err((err, exit_code)) = res {
+                    eprintln!("{err:?}");
+                    std::process::exit(exit_code);
+                } else {
+                    Ok(())
+                }
+            }
         },
         Some(SubCmd::Register { command }) => match command {
             RegisterCmd::GenerateKey { overwrite } => register::generate_key(overwrite),
@@ -308,19 +659,47 @@ pub async fn handle_subcommand(opt: Opt) -> Result<()> {
             VaultCmd::Create { max_fee_per_gas } => vault::create(opt.peers, max_fee_per_gas).await,
             VaultCmd::Load => vault::load(opt.peers).await,
             VaultCmd::Sync { force } => vault::sync(force, opt.peers).await,
+            VaultCmd::History => vault::history(opt.peers).await,
+            VaultCmd::Restore {
+                snapshot,
+                max_fee_per_gas,
+            } => vault::restore(snapshot, opt.peers, max_fee_per_gas).await,
         },
         Some(SubCmd::Wallet { command }) => match command {
             WalletCmd::Create {
                 no_password,
                 password,
-            } => wallet::create(no_password, password),
+                mnemonic,
+            } => wallet::create(no_password, password, mnemonic),
             WalletCmd::Import {
                 private_key,
+                mnemonic,
+                account_index,
+                keystore,
                 no_password,
                 password,
-            } => wallet::import(private_key, no_password, password),
-            WalletCmd::Export => wallet::export(),
+            } => {
+                let source = match (private_key, keystore) {
+                    (Some(private_key), _) => wallet::ImportSource::PrivateKey(private_key),
+                    (None, Some(keystore)) => wallet::ImportSource::Keystore(keystore),
+                    (None, None) if mnemonic => wallet::ImportSource::Mnemonic(account_index),
+                    (None, None) => unreachable!("clap requires one of the import sources"),
+                };
+                wallet::import(source, no_password, password)
+            }
+            WalletCmd::Export { keystore } => wallet::export(keystore),
             WalletCmd::Balance => wallet::balance(opt.peers.local).await,
+            WalletCmd::Allowance => wallet::allowance(opt.peers.local).await,
+            WalletCmd::Revoke => wallet::revoke(opt.peers.local).await,
+            WalletCmd::History {
+                since,
+                until,
+                wallet,
+                data_type,
+                address,
+                format,
+                output,
+            } => wallet::history(since, until, wallet, data_type, address, format, output),
         },
         Some(SubCmd::Analyze { addr, verbose }) => {
             analyze::analyze(&addr, verbose, opt.peers).await
@@ -6,15 +6,30 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::exit_code::{upload_exit_code, ExitCodeError, IO_ERROR};
+use crate::exit_code::{
+    get_error_exit_code, put_error_exit_code, upload_exit_code, ExitCodeError,
+    INCOMPLETE_UPLOAD_EXIT_CODE, INVALID_INPUT_EXIT_CODE, IO_ERROR,
+};
 use crate::utils::collect_upload_summary;
+use crate::wallet::input::get_wallet_selection_input;
 use crate::wallet::load_wallet;
-use autonomi::client::payment::PaymentOption;
-use autonomi::ResponseQuorum;
-use autonomi::{ClientOperatingStrategy, InitialPeersConfig, TransactionConfig};
-use color_eyre::eyre::{eyre, Context, Result};
+use autonomi::client::files::archive_private::PrivateArchiveDataMap;
+use autonomi::client::payment::{receipt_from_bytes, receipt_to_bytes, PaymentOption, Receipt};
+use autonomi::client::{GetError, PutError};
+use autonomi::data::DataAddress;
+use autonomi::files::{
+    FileUploadOutcome, PrivateArchive, PublicArchive, UploadError, UploadJournal, UploadReport,
+};
+use autonomi::{
+    AttoTokens, Bytes, Client, ClientOperatingStrategy, InitialPeersConfig, PaymentApproval,
+    PaymentPolicy,
+};
+use autonomi::{ResponseQuorum, XorName};
+use color_eyre::eyre::{eyre, Context, Report, Result};
 use color_eyre::Section;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicBool, Ordering};
+use std::sync::Arc;
 
 pub async fn cost(file: &str, init_peers_config: InitialPeersConfig) -> Result<()> {
     let client = crate::actions::connect_to_network(init_peers_config)
@@ -34,27 +49,59 @@ pub async fn cost(file: &str, init_peers_config: InitialPeersConfig) -> Result<(
     Ok(())
 }
 
+#[allow(clippy::too_many_arguments)]
 pub async fn upload(
     file: &str,
     public: bool,
     init_peers_config: InitialPeersConfig,
     optional_verification_quorum: Option<ResponseQuorum>,
     max_fee_per_gas: Option<u128>,
+    resume: bool,
+    receipt: Option<String>,
+    max_cost: Option<AttoTokens>,
+    confirm: bool,
+    hash: bool,
 ) -> Result<(), ExitCodeError> {
     let mut config = ClientOperatingStrategy::new();
     if let Some(verification_quorum) = optional_verification_quorum {
         config.chunks.verification_quorum = verification_quorum;
     }
+    // the client only lives for this upload, so its session budget is the budget of the upload
+    let payment_policy = PaymentPolicy {
+        max_per_session: max_cost,
+        approval: confirm.then(confirm_payments),
+        ..Default::default()
+    };
     let mut client =
-        crate::actions::connect_to_network_with_config(init_peers_config, config).await?;
-
-    let mut wallet = load_wallet(client.evm_network()).map_err(|err| (err, IO_ERROR))?;
-
-    if let Some(max_fee_per_gas) = max_fee_per_gas {
-        wallet.set_transaction_config(TransactionConfig::new(max_fee_per_gas))
+        crate::actions::connect_to_network_with_config(init_peers_config, config, payment_policy)
+            .await?;
+    if hash {
+        client.enable_content_hashes();
     }
 
-    let payment = PaymentOption::Wallet(wallet);
+    // the receipt of `file pay` and the archive that was paid for along with it
+    let paid = match receipt {
+        Some(receipt_path) => {
+            let receipt = read_receipt(&receipt_path)?;
+            let paid_archive = read_paid_archive(&receipt_path)?;
+            println!("Using the receipt at {receipt_path} to pay for the upload");
+            info!(
+                "Using the receipt at {receipt_path} covering {} addresses",
+                receipt.len()
+            );
+            Some((receipt, paid_archive))
+        }
+        None => None,
+    };
+    let payment = if let Some((receipt, _)) = &paid {
+        PaymentOption::Receipt(receipt.clone())
+    } else {
+        let mut wallet = load_wallet(client.evm_network()).map_err(|err| (err, IO_ERROR))?;
+        if let Some(max_fee_per_gas) = max_fee_per_gas {
+            wallet.set_max_fee_per_gas(max_fee_per_gas)
+        }
+        PaymentOption::Wallet(wallet)
+    };
     let event_receiver = client.enable_client_events();
     let (upload_summary_thread, upload_completed_tx) = collect_upload_summary(event_receiver);
 
@@ -70,40 +117,66 @@ pub async fn upload(
         .map(|n| n.to_string_lossy().to_string())
         .unwrap_or(file.to_string());
 
+    let journal = open_upload_journal(&dir_path, public, resume)
+        .await
+        .map_err(|err| (err, IO_ERROR))?;
+
     // upload dir
     let local_addr;
+    let report;
     let archive = if public {
-        let result = client.dir_upload_public(dir_path, payment.clone()).await;
+        let result = match paid {
+            Some((receipt, paid_archive)) => {
+                let paid_archive =
+                    PublicArchive::from_bytes(paid_archive).map_err(paid_archive_error)?;
+                client
+                    .dir_upload_public_paid(dir_path, &paid_archive, receipt, Some(&journal))
+                    .await
+            }
+            None => {
+                client
+                    .dir_upload_public_resumable(dir_path, payment.clone(), &journal)
+                    .await
+            }
+        };
         match result {
-            Ok((_cost, xor_name)) => {
+            Ok((_cost, xor_name, upload_report)) => {
+                report = upload_report;
                 local_addr = xor_name.to_hex();
                 local_addr.clone()
             }
-            Err(err) => {
-                let exit_code = upload_exit_code(&err);
-                return Err((
-                    eyre!(err).wrap_err("Failed to upload file".to_string()),
-                    exit_code,
-                ));
-            }
+            Err(err) => return Err(upload_error(err)),
         }
     } else {
-        let result = client.dir_upload(dir_path, payment).await;
+        let result = match paid {
+            Some((receipt, paid_archive)) => {
+                let paid_archive =
+                    PrivateArchive::from_bytes(paid_archive).map_err(paid_archive_error)?;
+                client
+                    .dir_upload_paid(dir_path, &paid_archive, receipt, Some(&journal))
+                    .await
+            }
+            None => {
+                client
+                    .dir_upload_resumable(dir_path, payment, &journal)
+                    .await
+            }
+        };
         match result {
-            Ok((_cost, private_data_access)) => {
+            Ok((_cost, private_data_access, upload_report)) => {
+                report = upload_report;
                 local_addr = private_data_access.address();
                 private_data_access.to_hex()
             }
-            Err(err) => {
-                let exit_code = upload_exit_code(&err);
-                return Err((
-                    eyre!(err).wrap_err("Failed to upload file".to_string()),
-                    exit_code,
-                ));
-            }
+            Err(err) => return Err(upload_error(err)),
         }
     };
 
+    // the upload is complete, there is nothing left to resume
+    if let Err(err) = journal.remove().await {
+        warn!("Failed to remove the upload journal: {err:?}");
+    }
+
     // wait for upload to complete
     if let Err(e) = upload_completed_tx.send(()) {
         error!("Failed to send upload completed event: {e:?}");
@@ -142,9 +215,426 @@ pub async fn upload(
 
     info!("Saved file to local user data");
 
+    if !report.is_complete() {
+        print_upload_report(&report);
+        return Err((
+            eyre!("Upload incomplete: {report}"),
+            INCOMPLETE_UPLOAD_EXIT_CODE,
+        ));
+    }
+
+    Ok(())
+}
+
+/// Show the quoted cost of each payment of the upload and ask the user to confirm it,
+/// or to confirm all the following payments at once.
+fn confirm_payments() -> PaymentApproval {
+    let confirmed_all = Arc::new(AtomicBool::new(false));
+    // payments may be quoted concurrently, ask about one at a time
+    let prompt_lock = Arc::new(tokio::sync::Mutex::new(()));
+    PaymentApproval::new(move |quotes| {
+        let confirmed_all = Arc::clone(&confirmed_all);
+        let prompt_lock = Arc::clone(&prompt_lock);
+        let quoted = AttoTokens::from_atto(quotes.price());
+        let chunks = quotes.len();
+        Box::pin(async move {
+            let _prompt = prompt_lock.lock().await;
+            if confirmed_all.load(Ordering::SeqCst) {
+                return true;
+            }
+            let prompt = format!(
+                "Pay {quoted} for {chunks} chunks? [y]es, [n]o, [a]ll the payments of this upload"
+            );
+            let answer = tokio::task::spawn_blocking(move || get_wallet_selection_input(&prompt))
+                .await
+                .unwrap_or_default();
+            match answer.to_lowercase().as_str() {
+                "y" | "yes" => true,
+                "a" | "all" => {
+                    confirmed_all.store(true, Ordering::SeqCst);
+                    true
+                }
+                _ => {
+                    info!("Payment of {quoted} for {chunks} chunks was not confirmed");
+                    false
+                }
+            }
+        })
+    })
+}
+
+fn upload_error(err: UploadError) -> ExitCodeError {
+    let exit_code = upload_exit_code(&err);
+    if let UploadError::Incomplete(report) = &err {
+        print_upload_report(report);
+    }
+    (
+        eyre!(err)
+            .wrap_err("Failed to upload file".to_string())
+            .with_suggestion(|| {
+                "the progress of the upload was saved, run the same command with --resume to continue it"
+            }),
+        exit_code,
+    )
+}
+
+/// Print the files that were not uploaded, with the reason why.
+fn print_upload_report(report: &UploadReport) {
+    println!("{report}");
+    for (path, reason) in report.skipped() {
+        println!("Skipped {}: {reason}", path.display());
+    }
+    for (path, outcome) in report.failed() {
+        println!("Failed {}: {outcome}", path.display());
+        if let FileUploadOutcome::UploadFailed { chunks, .. } = outcome {
+            for chunk in chunks {
+                println!("  chunk {}", chunk.to_hex());
+            }
+        }
+    }
+    info!("Upload report: {report:?}");
+}
+
+pub async fn pay(
+    file: &str,
+    public: bool,
+    output: &str,
+    receipt: Option<String>,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+    hash: bool,
+) -> Result<(), ExitCodeError> {
+    let mut client = crate::actions::connect_to_network(init_peers_config).await?;
+    if hash {
+        client.enable_content_hashes();
+    }
+
+    let mut wallet = load_wallet(client.evm_network()).map_err(|err| (err, IO_ERROR))?;
+    if let Some(max_fee_per_gas) = max_fee_per_gas {
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
+    }
+
+    let previous_receipt = match receipt {
+        Some(receipt_path) => {
+            let receipt = read_receipt(&receipt_path)?;
+            println!(
+                "Using the receipt at {receipt_path}, only paying for the chunks it does not cover"
+            );
+            receipt
+        }
+        None => Receipt::new(),
+    };
+
+    println!("Paying for the upload of {file}...");
+    info!(
+        "Paying for the {} upload of: {file}",
+        if public { "public" } else { "private" }
+    );
+
+    let dir_path = PathBuf::from(file);
+    let result = if public {
+        client
+            .dir_pay_public(dir_path, &wallet, previous_receipt)
+            .await
+            .and_then(|(cost, receipt, archive)| Ok((cost, receipt, archive.to_bytes()?)))
+    } else {
+        client
+            .dir_pay(dir_path, &wallet, previous_receipt)
+            .await
+            .and_then(|(cost, receipt, archive)| Ok((cost, receipt, archive.to_bytes()?)))
+    };
+    let (cost, receipt, paid_archive) = match result {
+        Ok(paid) => paid,
+        Err(err) => {
+            // don't lose the payments that went through
+            if let UploadError::PutError(PutError::PayError(pay_error)) = &err {
+                if let Some(partial) = pay_error.partial_receipt() {
+                    let bytes = receipt_to_bytes(partial).map_err(|err| (eyre!(err), IO_ERROR))?;
+                    std::fs::write(output, bytes)
+                        .wrap_err(format!("Failed to write the partial receipt to {output}"))
+                        .map_err(|err| (err, IO_ERROR))?;
+                    println!(
+                        "Paid for {} chunks before the payment failed, partial receipt written to: {output}",
+                        partial.len()
+                    );
+                    println!(
+                        "Finish paying with `file pay {file}{} --receipt {output} --output {output}`",
+                        if public { " --public" } else { "" }
+                    );
+                    info!(
+                        "Partial receipt for {} chunks of {file} written to {output}",
+                        partial.len()
+                    );
+                }
+            }
+            let exit_code = upload_exit_code(&err);
+            return Err((
+                eyre!(err).wrap_err("Failed to pay for the upload".to_string()),
+                exit_code,
+            ));
+        }
+    };
+
+    let bytes = receipt_to_bytes(&receipt).map_err(|err| (eyre!(err), IO_ERROR))?;
+    std::fs::write(output, bytes)
+        .wrap_err(format!("Failed to write the receipt to {output}"))
+        .map_err(|err| (err, IO_ERROR))?;
+    // the upload uploads this archive, as the one it builds would differ on another machine
+    let archive_path = paid_archive_path(output);
+    std::fs::write(&archive_path, paid_archive)
+        .wrap_err(format!(
+            "Failed to write the paid archive to {archive_path}"
+        ))
+        .map_err(|err| (err, IO_ERROR))?;
+
+    println!("Paid for {} chunks, total cost: {cost}", receipt.len());
+    println!("Receipt written to: {output}, along with the archive paid for: {archive_path}");
+    println!(
+        "Upload the file with `file upload {file}{} --receipt {output}` within an hour, before the quotes expire",
+        if public { " --public" } else { "" }
+    );
+    info!("Paid {cost} for the upload of {file}, receipt written to {output}");
+    Ok(())
+}
+
+/// Where `file pay` writes the archive it paid for, next to the receipt.
+fn paid_archive_path(receipt_path: &str) -> String {
+    format!("{receipt_path}.archive")
+}
+
+/// Read the archive written by `file pay` next to the receipt at `receipt_path`.
+fn read_paid_archive(receipt_path: &str) -> Result<Bytes, ExitCodeError> {
+    let archive_path = paid_archive_path(receipt_path);
+    std::fs::read(&archive_path)
+        .map(Bytes::from)
+        .wrap_err(format!("Failed to read the paid archive at {archive_path}"))
+        .with_suggestion(|| "`file pay` writes the archive it paid for next to the receipt, copy it along with the receipt")
+        .map_err(|err| (err, IO_ERROR))
+}
+
+fn paid_archive_error(err: impl std::error::Error + Send + Sync + 'static) -> ExitCodeError {
+    (
+        Report::new(err).wrap_err("Failed to parse the paid archive"),
+        INVALID_INPUT_EXIT_CODE,
+    )
+}
+
+/// Read a receipt written by `file pay`.
+fn read_receipt(receipt_path: &str) -> Result<Receipt, ExitCodeError> {
+    let bytes = std::fs::read(receipt_path)
+        .wrap_err(format!("Failed to read the receipt at {receipt_path}"))
+        .map_err(|err| (err, IO_ERROR))?;
+    receipt_from_bytes(&bytes)
+        .wrap_err(format!("Failed to parse the receipt at {receipt_path}"))
+        .map_err(|err| (err, INVALID_INPUT_EXIT_CODE))
+}
+
+pub async fn sync(
+    dir: &str,
+    archive_addr: &str,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+) -> Result<(), ExitCodeError> {
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+
+    let mut wallet = load_wallet(client.evm_network()).map_err(|err| (err, IO_ERROR))?;
+    if let Some(max_fee_per_gas) = max_fee_per_gas {
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
+    }
+    let payment = PaymentOption::Wallet(wallet);
+
+    let dir_path = PathBuf::from(dir);
+    let name = dir_path
+        .file_name()
+        .map(|n| n.to_string_lossy().to_string())
+        .unwrap_or(dir.to_string());
+
+    let put_error = |err: PutError| {
+        let exit_code = put_error_exit_code(&err);
+        (
+            eyre!(err).wrap_err("Failed to upload the new archive"),
+            exit_code,
+        )
+    };
+
+    println!("Syncing {dir} against the archive at {archive_addr}...");
+    info!("Syncing {dir} against the archive at {archive_addr}");
+    let (report, local_addr) = match fetch_archive(&client, archive_addr).await? {
+        Archive::Public(previous) => {
+            let (_cost, archive, report) = client
+                .dir_sync_public(dir_path, &previous, payment.clone())
+                .await
+                .map_err(sync_error)?;
+            let (_cost, addr) = client
+                .archive_put_public(&archive, payment)
+                .await
+                .map_err(put_error)?;
+            let local_addr = addr.to_hex();
+            crate::user_data::write_local_public_file_archive(local_addr.clone(), &name)
+                .wrap_err("Failed to save file to local user data")
+                .map_err(|err| (err, IO_ERROR))?;
+            (report, local_addr)
+        }
+        Archive::Private(previous) => {
+            let (_cost, archive, report) = client
+                .dir_sync(dir_path, &previous, payment.clone())
+                .await
+                .map_err(sync_error)?;
+            let (_cost, access) = client
+                .archive_put(&archive, payment)
+                .await
+                .map_err(put_error)?;
+            let local_addr = access.address();
+            crate::user_data::write_local_private_file_archive(
+                access.to_hex(),
+                local_addr.clone(),
+                &name,
+            )
+            .wrap_err("Failed to save file to local user data")
+            .map_err(|err| (err, IO_ERROR))?;
+            (report, local_addr)
+        }
+    };
+
+    println!("{report}");
+    for (path, reason) in report.skipped() {
+        println!("Skipped {}: {reason}", path.display());
+    }
+    println!("Successfully synced: {dir}");
+    println!("At address: {local_addr}");
+    info!("Synced {dir} at address {local_addr}: {report}");
+    Ok(())
+}
+
+fn sync_error(err: UploadError) -> ExitCodeError {
+    let exit_code = upload_exit_code(&err);
+    if let UploadError::Incomplete(report) = &err {
+        print_upload_report(report);
+    }
+    (eyre!(err).wrap_err("Failed to sync directory"), exit_code)
+}
+
+pub async fn diff(
+    base_addr: &str,
+    new_addr: &str,
+    init_peers_config: InitialPeersConfig,
+) -> Result<(), ExitCodeError> {
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+
+    let base = fetch_archive(&client, base_addr).await?;
+    let new = fetch_archive(&client, new_addr).await?;
+    let diff = match (base, new) {
+        (Archive::Public(base), Archive::Public(new)) => base.diff(&new),
+        (Archive::Private(base), Archive::Private(new)) => base.diff(&new),
+        _ => {
+            return Err((
+                eyre!("Cannot compare a public archive with a private archive"),
+                INVALID_INPUT_EXIT_CODE,
+            ))
+        }
+    };
+
+    for path in &diff.added {
+        println!("+ {}", path.display());
+    }
+    for path in &diff.removed {
+        println!("- {}", path.display());
+    }
+    for path in &diff.modified {
+        println!("M {}", path.display());
+    }
+    for (old_path, new_path) in &diff.renamed {
+        println!("R {} -> {}", old_path.display(), new_path.display());
+    }
+    if diff.is_empty() {
+        println!("The archives have the same files");
+    } else {
+        println!("{diff}");
+    }
+    info!("Diff from {base_addr} to {new_addr}: {diff}");
     Ok(())
 }
 
+/// A public or private archive fetched from the network.
+enum Archive {
+    Public(PublicArchive),
+    Private(PrivateArchive),
+}
+
+/// Fetch the archive at a public address, a local private address or a hex encoded private archive data map.
+async fn fetch_archive(client: &Client, addr: &str) -> Result<Archive, ExitCodeError> {
+    let get_error = |err: GetError| {
+        let exit_code = get_error_exit_code(&err);
+        (
+            eyre!(err).wrap_err(format!("Failed to fetch the archive at {addr}")),
+            exit_code,
+        )
+    };
+
+    if let Ok(public_addr) = DataAddress::from_hex(addr) {
+        let archive = client
+            .archive_get_public(&public_addr)
+            .await
+            .map_err(get_error)?;
+        return Ok(Archive::Public(archive));
+    }
+
+    let access = crate::user_data::get_local_private_archive_access(addr)
+        .ok()
+        .or_else(|| PrivateArchiveDataMap::from_hex(addr).ok())
+        .ok_or_else(|| {
+            (
+                eyre!("Failed to parse archive address {addr}").with_suggestion(|| {
+                    "Try the `file list` command to get addresses you have access to"
+                }),
+                INVALID_INPUT_EXIT_CODE,
+            )
+        })?;
+    let archive = client.archive_get(&access).await.map_err(get_error)?;
+    Ok(Archive::Private(archive))
+}
+
+/// Open the journal recording the progress of the upload of the given file.
+///
+/// There is one journal per file (and visibility), so an interrupted upload can be resumed with `--resume`.
+/// Without `--resume`, any journal left over by a previous upload is discarded and the upload starts from scratch.
+async fn open_upload_journal(file: &Path, public: bool, resume: bool) -> Result<UploadJournal> {
+    let abs_path =
+        std::path::absolute(file).wrap_err("Failed to get the absolute path of the file")?;
+    let key = format!(
+        "{}:{}",
+        if public { "public" } else { "private" },
+        abs_path.display()
+    );
+    let journal_path = crate::data_dir::get_client_data_dir_path()?
+        .join("upload_journals")
+        .join(hex::encode(XorName::from_content(key.as_bytes())));
+
+    let journal = UploadJournal::open(journal_path)
+        .await
+        .wrap_err("Failed to open the upload journal")?;
+    if journal.is_empty().await {
+        return Ok(journal);
+    }
+    if resume {
+        println!("Resuming the previous upload of {}", file.display());
+        return Ok(journal);
+    }
+
+    println!(
+        "Discarding the progress of a previous upload of {}, use --resume to continue it instead",
+        file.display()
+    );
+    let journal_path = journal.path().to_path_buf();
+    journal
+        .remove()
+        .await
+        .wrap_err("Failed to remove the previous upload journal")?;
+    UploadJournal::open(journal_path)
+        .await
+        .wrap_err("Failed to open the upload journal")
+}
+
 pub async fn download(
     addr: &str,
     dest_path: &str,
@@ -155,7 +645,12 @@ pub async fn download(
     if let Some(quorum) = quorum {
         config.chunks.get_quorum = quorum;
     }
-    let client = crate::actions::connect_to_network_with_config(init_peers_config, config).await?;
+    let client = crate::actions::connect_to_network_with_config(
+        init_peers_config,
+        config,
+        Default::default(),
+    )
+    .await?;
     crate::actions::download(addr, dest_path, &client).await
 }
 
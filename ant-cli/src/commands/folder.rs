@@ -0,0 +1,419 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use crate::exit_code::{
+    folder_error_exit_code, get_error_exit_code, upload_exit_code, ExitCodeError,
+    INVALID_INPUT_EXIT_CODE, IO_ERROR,
+};
+use crate::wallet::load_wallet;
+use autonomi::client::payment::PaymentOption;
+use autonomi::client::register::{RegisterAddress, RegisterError, SecretKey as RegisterSecretKey};
+use autonomi::files::{
+    FolderAddress, FolderArchive, FolderError, Metadata, PrivateArchive, PublicArchive, UploadError,
+};
+use autonomi::{Client, InitialPeersConfig};
+use color_eyre::eyre::{eyre, Context};
+use color_eyre::Section;
+use std::path::{Path, PathBuf};
+use std::time::UNIX_EPOCH;
+
+pub async fn create(
+    name: &str,
+    public: bool,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+) -> Result<(), ExitCodeError> {
+    let folder_key = folder_key_from_name(name)?;
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+    let payment = wallet_payment(&client, max_fee_per_gas)?;
+
+    println!("Creating folder with name: {name}");
+    info!("Creating folder with name: {name}");
+    let (cost, addr) = if public {
+        client
+            .folder_create_public(&folder_key, &PublicArchive::new(), payment)
+            .await
+    } else {
+        client
+            .folder_create(&folder_key, &PrivateArchive::new(), payment)
+            .await
+    }
+    .map_err(|err| folder_error(err, "Failed to create folder"))?;
+
+    println!("✅ Folder created at address: {addr}");
+    println!("With name: {name}");
+    println!("Total cost: {cost} AttoTokens");
+    info!("Folder created at address: {addr} with name: {name}");
+    Ok(())
+}
+
+pub async fn add(
+    name: &str,
+    file: &str,
+    path: Option<String>,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+) -> Result<(), ExitCodeError> {
+    let file_path = PathBuf::from(file);
+    let fs_metadata = std::fs::metadata(&file_path)
+        .wrap_err(format!("Failed to read file: {file}"))
+        .map_err(|err| (err, IO_ERROR))?;
+    if !fs_metadata.is_file() {
+        return Err((
+            eyre!("{file} is not a file").with_suggestion(|| {
+                "to upload a directory, upload it as an archive with `file upload`"
+            }),
+            INVALID_INPUT_EXIT_CODE,
+        ));
+    }
+    let archive_path = match path {
+        Some(path) => PathBuf::from(path),
+        None => file_path
+            .file_name()
+            .map(PathBuf::from)
+            .unwrap_or_else(|| file_path.clone()),
+    };
+    let mut metadata = Metadata::new_with_size(fs_metadata.len());
+    if let Some(modified) = fs_metadata
+        .modified()
+        .ok()
+        .and_then(|time| time.duration_since(UNIX_EPOCH).ok())
+    {
+        metadata.modified = modified.as_secs();
+    }
+
+    let folder_key = folder_key_from_name(name)?;
+    let addr = RegisterAddress::new(folder_key.public_key());
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+    let payment = wallet_payment(&client, max_fee_per_gas)?;
+
+    println!("Adding {file} to folder {name} as {archive_path:?}...");
+    info!("Adding {file} to folder {name} at {addr} as {archive_path:?}");
+    let data_key = Client::register_data_key(&folder_key);
+    let upload_error = |err: UploadError| {
+        let exit_code = upload_exit_code(&err);
+        (eyre!(err).wrap_err("Failed to upload file"), exit_code)
+    };
+    let cost = match fetch_folder(&client, &addr, Some(&data_key)).await? {
+        FolderArchive::Public(_, mut archive) => {
+            let (_, data_addr) = client
+                .file_content_upload_public(file_path, payment.clone())
+                .await
+                .map_err(upload_error)?;
+            archive.add_file(archive_path, data_addr, metadata);
+            client
+                .folder_publish_public(&folder_key, &archive, payment)
+                .await
+        }
+        FolderArchive::Private(mut archive) => {
+            let (_, data_map) = client
+                .file_content_upload(file_path, payment.clone())
+                .await
+                .map_err(upload_error)?;
+            archive.add_file(archive_path, data_map, metadata);
+            client.folder_publish(&folder_key, &archive, payment).await
+        }
+    }
+    .map_err(|err| folder_error(err, "Failed to publish the new version of the folder"))?;
+
+    println!("✅ Published a new version of folder {name} with {file}");
+    println!("Total cost of the new version: {cost} AttoTokens");
+    info!("Added {file} to folder {name} at {addr}");
+    Ok(())
+}
+
+pub async fn remove(
+    name: &str,
+    path: &str,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+) -> Result<(), ExitCodeError> {
+    edit(name, init_peers_config, max_fee_per_gas, |folder| {
+        let removed = match folder {
+            FolderArchive::Public(_, archive) => archive.remove_file(Path::new(path)).is_some(),
+            FolderArchive::Private(archive) => archive.remove_file(Path::new(path)).is_some(),
+        };
+        if removed {
+            Ok(())
+        } else {
+            Err(not_in_folder(path, name))
+        }
+    })
+    .await?;
+    println!("✅ Removed {path} from folder {name}");
+    Ok(())
+}
+
+pub async fn rename(
+    name: &str,
+    from: &str,
+    to: &str,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+) -> Result<(), ExitCodeError> {
+    edit(name, init_peers_config, max_fee_per_gas, |folder| {
+        let (from, to) = (Path::new(from), Path::new(to));
+        match folder {
+            FolderArchive::Public(_, archive) => archive.rename_file(from, to),
+            FolderArchive::Private(archive) => archive.rename_file(from, to),
+        }
+        .map_err(|_| not_in_folder(&from.to_string_lossy(), name))
+    })
+    .await?;
+    println!("✅ Renamed {from} to {to} in folder {name}");
+    Ok(())
+}
+
+pub async fn list(
+    address: &str,
+    name: bool,
+    init_peers_config: InitialPeersConfig,
+) -> Result<(), ExitCodeError> {
+    let (addr, data_key) = folder_address(address, name)?;
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+
+    let files = match fetch_folder(&client, &addr, data_key.as_ref()).await? {
+        FolderArchive::Public(archive_addr, archive) => {
+            println!("✅ Public folder at {addr}, latest version at: {archive_addr}");
+            archive.files()
+        }
+        FolderArchive::Private(archive) => {
+            println!("✅ Private folder at {addr}");
+            archive.files()
+        }
+    };
+    println!("With {} file(s):", files.len());
+    for (path, metadata) in files {
+        println!("{}: {} bytes", path.display(), metadata.size);
+    }
+    info!("Listed folder at {addr}");
+    Ok(())
+}
+
+pub async fn history(
+    address: &str,
+    name: bool,
+    init_peers_config: InitialPeersConfig,
+) -> Result<(), ExitCodeError> {
+    let (addr, data_key) = folder_address(address, name)?;
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+
+    let history_error = |err| folder_error(err, "Failed to get the folder history");
+    if client
+        .folder_is_public(&addr)
+        .await
+        .map_err(history_error)?
+    {
+        let versions = client
+            .folder_history_public(&addr)
+            .await
+            .map_err(history_error)?;
+        println!(
+            "✅ Public folder at {addr} has {} version(s):",
+            versions.len()
+        );
+        for (i, archive_addr) in versions.iter().enumerate() {
+            println!("[{i}] {archive_addr}");
+        }
+    } else {
+        let data_key = data_key.ok_or_else(private_folder_error)?;
+        let versions = client
+            .folder_history(&addr, &data_key)
+            .await
+            .map_err(history_error)?;
+        println!(
+            "✅ Private folder at {addr} has {} version(s):",
+            versions.len()
+        );
+        for (i, archive) in versions.iter().enumerate() {
+            println!("[{i}] {} file(s)", archive.iter().count());
+        }
+    }
+    info!("Listed the history of folder at {addr}");
+    Ok(())
+}
+
+pub async fn download(
+    address: &str,
+    name: bool,
+    dest_path: &str,
+    version: Option<usize>,
+    init_peers_config: InitialPeersConfig,
+) -> Result<(), ExitCodeError> {
+    let (addr, data_key) = folder_address(address, name)?;
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+
+    let folder = match version {
+        None => fetch_folder(&client, &addr, data_key.as_ref()).await?,
+        Some(version) => fetch_folder_version(&client, &addr, data_key.as_ref(), version).await?,
+    };
+    let addr = addr.to_hex();
+    match folder {
+        FolderArchive::Public(_, archive) => {
+            crate::actions::download_pub_archive_to_disk(&addr, archive, dest_path, &client).await
+        }
+        FolderArchive::Private(archive) => {
+            crate::actions::download_priv_archive_to_disk(&addr, archive, dest_path, &client).await
+        }
+    }
+}
+
+/// Fetch the latest version of the folder with the given name, apply the change and publish it
+async fn edit(
+    name: &str,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+    change: impl FnOnce(&mut FolderArchive) -> Result<(), ExitCodeError>,
+) -> Result<(), ExitCodeError> {
+    let folder_key = folder_key_from_name(name)?;
+    let addr = RegisterAddress::new(folder_key.public_key());
+    let client = crate::actions::connect_to_network(init_peers_config).await?;
+    let data_key = Client::register_data_key(&folder_key);
+
+    let mut folder = fetch_folder(&client, &addr, Some(&data_key)).await?;
+    change(&mut folder)?;
+
+    let payment = wallet_payment(&client, max_fee_per_gas)?;
+    let cost = match &folder {
+        FolderArchive::Public(_, archive) => {
+            client
+                .folder_publish_public(&folder_key, archive, payment)
+                .await
+        }
+        FolderArchive::Private(archive) => {
+            client.folder_publish(&folder_key, archive, payment).await
+        }
+    }
+    .map_err(|err| folder_error(err, "Failed to publish the new version of the folder"))?;
+
+    println!("Total cost of the new version: {cost} AttoTokens");
+    info!("Published a new version of folder {name} at {addr}");
+    Ok(())
+}
+
+async fn fetch_folder(
+    client: &Client,
+    addr: &FolderAddress,
+    data_key: Option<&RegisterSecretKey>,
+) -> Result<FolderArchive, ExitCodeError> {
+    match client.folder_get_latest(addr, data_key).await {
+        Ok(folder) => Ok(folder),
+        Err(FolderError::Register(RegisterError::MissingDataKey)) => Err(private_folder_error()),
+        Err(err) => Err(folder_error(err, format!("Failed to get folder at {addr}"))),
+    }
+}
+
+async fn fetch_folder_version(
+    client: &Client,
+    addr: &FolderAddress,
+    data_key: Option<&RegisterSecretKey>,
+    version: usize,
+) -> Result<FolderArchive, ExitCodeError> {
+    let history_error = |err| folder_error(err, format!("Failed to get the history of {addr}"));
+    let missing_version = |count: usize| {
+        (
+            eyre!("Folder at {addr} has no version {version}")
+                .with_suggestion(|| format!("versions go from 0 to {}", count.saturating_sub(1))),
+            INVALID_INPUT_EXIT_CODE,
+        )
+    };
+    if client.folder_is_public(addr).await.map_err(history_error)? {
+        let versions = client
+            .folder_history_public(addr)
+            .await
+            .map_err(history_error)?;
+        let archive_addr = *versions
+            .get(version)
+            .ok_or_else(|| missing_version(versions.len()))?;
+        let archive = client
+            .archive_get_public(&archive_addr)
+            .await
+            .map_err(|err| {
+                let exit_code = get_error_exit_code(&err);
+                (
+                    eyre!(err).wrap_err(format!("Failed to fetch version {version}")),
+                    exit_code,
+                )
+            })?;
+        Ok(FolderArchive::Public(archive_addr, archive))
+    } else {
+        let data_key = data_key.ok_or_else(private_folder_error)?;
+        let mut versions = client
+            .folder_history(addr, data_key)
+            .await
+            .map_err(history_error)?;
+        if version >= versions.len() {
+            return Err(missing_version(versions.len()));
+        }
+        Ok(FolderArchive::Private(versions.swap_remove(version)))
+    }
+}
+
+/// Parse a folder address, or derive it from its name along with its data key
+fn folder_address(
+    address: &str,
+    name: bool,
+) -> Result<(FolderAddress, Option<RegisterSecretKey>), ExitCodeError> {
+    if name {
+        let folder_key = folder_key_from_name(address)?;
+        let data_key = Client::register_data_key(&folder_key);
+        Ok((
+            RegisterAddress::new(folder_key.public_key()),
+            Some(data_key),
+        ))
+    } else {
+        let addr = RegisterAddress::from_hex(address)
+            .wrap_err(format!("Failed to parse folder address: {address}"))
+            .with_suggestion(|| {
+                "if you want to use the name as the address, run the command with the --name flag"
+            })
+            .map_err(|err| (err, INVALID_INPUT_EXIT_CODE))?;
+        Ok((addr, None))
+    }
+}
+
+fn folder_key_from_name(name: &str) -> Result<RegisterSecretKey, ExitCodeError> {
+    let main_registers_key = crate::keys::get_register_signing_key()
+        .wrap_err("The register key is required to perform this action")
+        .with_suggestion(|| "create one with `register generate-key`")
+        .map_err(|err| (err, INVALID_INPUT_EXIT_CODE))?;
+    Ok(Client::folder_key_from_name(&main_registers_key, name))
+}
+
+fn wallet_payment(
+    client: &Client,
+    max_fee_per_gas: Option<u128>,
+) -> Result<PaymentOption, ExitCodeError> {
+    let mut wallet = load_wallet(client.evm_network()).map_err(|err| (err, IO_ERROR))?;
+    if let Some(max_fee_per_gas) = max_fee_per_gas {
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
+    }
+    Ok(PaymentOption::Wallet(wallet))
+}
+
+fn folder_error(err: FolderError, context: impl Into<String>) -> ExitCodeError {
+    let exit_code = folder_error_exit_code(&err);
+    (eyre!(err).wrap_err(context.into()), exit_code)
+}
+
+fn private_folder_error() -> ExitCodeError {
+    (
+        eyre!("This folder is private, only its owner can read it").with_suggestion(|| {
+            "if you own this folder, use its name with the --name flag instead of its address"
+        }),
+        INVALID_INPUT_EXIT_CODE,
+    )
+}
+
+fn not_in_folder(path: &str, name: &str) -> ExitCodeError {
+    (
+        eyre!("{path} is not in folder {name}")
+            .with_suggestion(|| format!("list the files with `file folder list --name {name}`")),
+        INVALID_INPUT_EXIT_CODE,
+    )
+}
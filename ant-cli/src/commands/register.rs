@@ -11,7 +11,7 @@
 use crate::wallet::load_wallet;
 use autonomi::client::register::RegisterAddress;
 use autonomi::client::register::SecretKey as RegisterSecretKey;
-use autonomi::{Client, InitialPeersConfig, TransactionConfig};
+use autonomi::{Client, InitialPeersConfig};
 use color_eyre::eyre::eyre;
 use color_eyre::eyre::Context;
 use color_eyre::eyre::Result;
@@ -70,7 +70,7 @@ pub async fn create(
     let mut wallet = load_wallet(client.evm_network())?;
 
     if let Some(max_fee_per_gas) = max_fee_per_gas {
-        wallet.set_transaction_config(TransactionConfig::new(max_fee_per_gas))
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
     }
 
     let register_key = Client::register_key_from_name(&main_registers_key, name);
@@ -127,7 +127,7 @@ pub async fn edit(
     let mut wallet = load_wallet(client.evm_network())?;
 
     if let Some(max_fee_per_gas) = max_fee_per_gas {
-        wallet.set_transaction_config(TransactionConfig::new(max_fee_per_gas))
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
     }
 
     let value_bytes = if hex {
@@ -7,7 +7,9 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use crate::wallet::load_wallet;
-use autonomi::{InitialPeersConfig, TransactionConfig};
+use autonomi::client::vault::user_data::USER_DATA_VAULT_CONTENT_IDENTIFIER;
+use autonomi::client::vault::UserData;
+use autonomi::InitialPeersConfig;
 use color_eyre::eyre::Context;
 use color_eyre::eyre::Result;
 use color_eyre::Section;
@@ -41,19 +43,16 @@ pub async fn create(
     let mut wallet = load_wallet(client.evm_network())?;
 
     if let Some(max_fee_per_gas) = max_fee_per_gas {
-        wallet.set_transaction_config(TransactionConfig::new(max_fee_per_gas))
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
     }
 
     let vault_sk = crate::keys::get_vault_secret_key()?;
 
     println!("Retrieving local user data...");
     let local_user_data = crate::user_data::get_local_user_data()?;
-    let file_archives_len = local_user_data.file_archives.len();
-    let private_file_archives_len = local_user_data.private_file_archives.len();
-    let registers_len = local_user_data.register_addresses.len();
     println!("Pushing to network vault...");
     let total_cost = client
-        .put_user_data_to_vault(&vault_sk, wallet.into(), local_user_data)
+        .put_user_data_to_vault(&vault_sk, wallet.into(), local_user_data.clone())
         .await?;
 
     if total_cost.is_zero() {
@@ -64,9 +63,7 @@ pub async fn create(
 
     println!("Total cost: {total_cost} AttoTokens");
     println!("Vault contains:");
-    println!("{file_archives_len} public file archive(s)");
-    println!("{private_file_archives_len} private file archive(s)");
-    println!("{registers_len} register(s)");
+    print_user_data_summary(&local_user_data);
     Ok(())
 }
 
@@ -78,6 +75,7 @@ pub async fn sync(force: bool, init_peers_config: InitialPeersConfig) -> Result<
     let vault_sk = crate::keys::get_vault_secret_key()?;
     let wallet = load_wallet(client.evm_network())?;
 
+    let mut user_data = crate::user_data::get_local_user_data()?;
     if force {
         println!("The force flag was provided, overwriting user data in the vault with local user data...");
     } else {
@@ -88,24 +86,22 @@ pub async fn sync(force: bool, init_peers_config: InitialPeersConfig) -> Result<
             .wrap_err("Failed to fetch vault from network")
             .with_suggestion(|| "Make sure you have already created a vault on the network")?;
         println!("Syncing vault with local user data...");
-        crate::user_data::write_local_user_data(&net_user_data)?;
+        // the names from the vault win over the local ones, the most recent app data wins
+        user_data.merge_from_vault(net_user_data);
+        crate::user_data::write_local_user_data(&user_data)?;
     }
 
     println!("Pushing local user data to network vault...");
-    let local_user_data = crate::user_data::get_local_user_data()?;
-    let file_archives_len = local_user_data.file_archives.len();
-    let private_file_archives_len = local_user_data.private_file_archives.len();
-    let registers_len = local_user_data.register_addresses.len();
-    client
-        .put_user_data_to_vault(&vault_sk, wallet.into(), local_user_data)
+    let total_cost = client
+        .put_user_data_to_vault(&vault_sk, wallet.into(), user_data.clone())
         .await
         .with_suggestion(|| "Make sure you have already created a vault on the network")?;
 
     println!("✅ Successfully synced vault");
+    println!("Any previous content of the vault was kept as a snapshot, see `vault history`");
+    println!("Total cost: {total_cost} AttoTokens");
     println!("Vault contains:");
-    println!("{file_archives_len} public file archive(s)");
-    println!("{private_file_archives_len} private file archive(s)");
-    println!("{registers_len} register(s)");
+    print_user_data_summary(&user_data);
     Ok(())
 }
 
@@ -122,11 +118,85 @@ pub async fn load(init_peers_config: InitialPeersConfig) -> Result<()> {
     crate::user_data::write_local_user_data(&user_data)?;
 
     println!("✅ Successfully loaded vault with:");
+    print_user_data_summary(&user_data);
+    Ok(())
+}
+
+pub async fn history(init_peers_config: InitialPeersConfig) -> Result<()> {
+    let client = crate::actions::connect_to_network(init_peers_config)
+        .await
+        .map_err(|(err, _)| err)?;
+
+    let vault_sk = crate::keys::get_vault_secret_key()?;
+
+    println!("Retrieving vault snapshots from network...");
+    let snapshots = client
+        .vault_snapshots(&vault_sk)
+        .await
+        .wrap_err("Failed to fetch the vault snapshots")?;
+    if snapshots.is_empty() {
+        println!(
+            "Your vault has no snapshots, they are taken every time the vault is synced or restored."
+        );
+        return Ok(());
+    }
+
+    println!("✅ Your vault has {} snapshot(s):", snapshots.len());
+    for (i, snapshot) in snapshots.iter().enumerate() {
+        println!(
+            "[{i}] replaced at {} (unix time), {} bytes",
+            snapshot.timestamp,
+            snapshot.data.len()
+        );
+        if snapshot.content_type == *USER_DATA_VAULT_CONTENT_IDENTIFIER {
+            if let Ok(user_data) = UserData::from_bytes(snapshot.data.clone()) {
+                print_user_data_summary(&user_data);
+            }
+        }
+    }
+    Ok(())
+}
+
+pub async fn restore(
+    snapshot: usize,
+    init_peers_config: InitialPeersConfig,
+    max_fee_per_gas: Option<u128>,
+) -> Result<()> {
+    let client = crate::actions::connect_to_network(init_peers_config)
+        .await
+        .map_err(|(err, _)| err)?;
+
+    let mut wallet = load_wallet(client.evm_network())?;
+
+    if let Some(max_fee_per_gas) = max_fee_per_gas {
+        wallet.set_max_fee_per_gas(max_fee_per_gas)
+    }
+
+    let vault_sk = crate::keys::get_vault_secret_key()?;
+
+    println!("Restoring vault from snapshot {snapshot}...");
+    let total_cost = client
+        .vault_restore_snapshot(&vault_sk, snapshot, wallet.into())
+        .await
+        .wrap_err(format!("Failed to restore vault from snapshot {snapshot}"))
+        .with_suggestion(|| "Use `vault history` to list the snapshots of your vault")?;
+
+    println!("✅ Successfully restored vault from snapshot {snapshot}");
+    println!("The previous content of the vault was kept as a new snapshot");
+    println!("Total cost: {total_cost} AttoTokens");
+    println!("Use `vault load` to write the restored user data to this device");
+    Ok(())
+}
+
+fn print_user_data_summary(user_data: &UserData) {
     println!("{} public file archive(s)", user_data.file_archives.len());
     println!(
         "{} private file archive(s)",
         user_data.private_file_archives.len()
     );
     println!("{} register(s)", user_data.register_addresses.len());
-    Ok(())
+    println!("{} pointer(s)", user_data.pointers.len());
+    println!("{} scratchpad(s)", user_data.scratchpads.len());
+    println!("{} graph(s)", user_data.graph_entries.len());
+    println!("{} app(s) with data", user_data.app_data.len());
 }
@@ -6,21 +6,36 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+use crate::access::data_dir::get_payment_ledger_path;
 use crate::wallet::fs::{select_wallet_private_key, store_private_key};
-use crate::wallet::input::request_password;
+use crate::wallet::input::{get_password_input, request_password};
 use crate::wallet::DUMMY_NETWORK;
+use autonomi::client::payment::{PaymentFilter, PaymentLedger, PaymentRecord};
+use autonomi::client::quote::DataTypes;
 use autonomi::get_evm_network;
-use autonomi::Wallet;
-use color_eyre::eyre::eyre;
+use autonomi::Amount;
+use autonomi::{RewardsAddress, Wallet, XorName};
+use clap::ValueEnum;
+use color_eyre::eyre::{eyre, Context};
 use color_eyre::Result;
 use prettytable::{Cell, Row, Table};
+use serde::Serialize;
+use std::path::PathBuf;
+use std::str::FromStr;
 
 const WALLET_PASSWORD_REQUIRED: bool = false;
 
-pub fn create(no_password: bool, password: Option<String>) -> Result<()> {
+pub fn create(no_password: bool, password: Option<String>, mnemonic: bool) -> Result<()> {
     let maybe_encryption_password = maybe_request_password(no_password, password)?;
 
-    let wallet_private_key = Wallet::random_private_key();
+    let (wallet_private_key, maybe_mnemonic) = if mnemonic {
+        let mnemonic = Wallet::random_mnemonic();
+        let private_key = Wallet::private_key_from_mnemonic(&mnemonic, 0)
+            .map_err(|e| eyre!("Unexpected error: Failed to derive key from mnemonic: {e}"))?;
+        (private_key, Some(mnemonic))
+    } else {
+        (Wallet::random_private_key(), None)
+    };
 
     let wallet_address = Wallet::new_from_private_key(DUMMY_NETWORK, &wallet_private_key)
         .map_err(|e| eyre!("Unexpected error: Failed to create wallet from private key: {e}"))?
@@ -32,19 +47,44 @@ pub fn create(no_password: bool, password: Option<String>) -> Result<()> {
 
     println!("Wallet address: {wallet_address}");
     println!("Wallet private key: {wallet_private_key}");
+    if let Some(mnemonic) = maybe_mnemonic {
+        println!("Wallet mnemonic: {mnemonic}");
+        println!("Write the mnemonic down and keep it safe, it can be used to recover the wallet with `wallet import --mnemonic`");
+    }
     println!("Stored wallet in: {file_path:?}");
 
     Ok(())
 }
 
-pub fn import(
-    mut wallet_private_key: String,
-    no_password: bool,
-    password: Option<String>,
-) -> Result<()> {
-    // Validate imported key
-    Wallet::new_from_private_key(DUMMY_NETWORK, &wallet_private_key)
-        .map_err(|_| eyre!("Please provide a valid private key in hex format"))?;
+/// Where to import a wallet from.
+pub enum ImportSource {
+    /// A hex-encoded private key
+    PrivateKey(String),
+    /// A BIP-39 mnemonic phrase prompted for, with the BIP-44 account index to derive
+    Mnemonic(u32),
+    /// An Ethereum V3 JSON keystore file
+    Keystore(PathBuf),
+}
+
+pub fn import(source: ImportSource, no_password: bool, password: Option<String>) -> Result<()> {
+    let mut wallet_private_key = match source {
+        ImportSource::PrivateKey(private_key) => {
+            // Validate imported key
+            Wallet::new_from_private_key(DUMMY_NETWORK, &private_key)
+                .map_err(|_| eyre!("Please provide a valid private key in hex format"))?;
+            private_key
+        }
+        ImportSource::Mnemonic(account_index) => {
+            let mnemonic = get_password_input("Enter mnemonic phrase: ");
+            Wallet::private_key_from_mnemonic(&mnemonic, account_index)
+                .map_err(|e| eyre!("Please provide a valid BIP-39 mnemonic phrase: {e}"))?
+        }
+        ImportSource::Keystore(path) => {
+            let keystore_password = get_password_input("Enter password to decrypt keystore: ");
+            Wallet::private_key_from_keystore(&path, &keystore_password)
+                .map_err(|e| eyre!("Failed to decrypt keystore {path:?}: {e}"))?
+        }
+    };
 
     let maybe_encryption_password = maybe_request_password(no_password, password)?;
 
@@ -67,7 +107,7 @@ pub fn import(
     Ok(())
 }
 
-pub fn export() -> Result<()> {
+pub fn export(keystore_dir: Option<PathBuf>) -> Result<()> {
     let wallet_private_key = select_wallet_private_key()?;
 
     let wallet_address = Wallet::new_from_private_key(DUMMY_NETWORK, &wallet_private_key)
@@ -76,7 +116,21 @@ pub fn export() -> Result<()> {
         .to_string();
 
     println!("Wallet address: {wallet_address}");
-    println!("Wallet private key: {wallet_private_key}");
+
+    if let Some(dir) = keystore_dir {
+        let password =
+            request_password(true).expect("a password is always returned when it is required");
+        let file_path = Wallet::write_keystore(
+            &wallet_private_key,
+            &dir,
+            &format!("{wallet_address}.json"),
+            &password,
+        )
+        .map_err(|e| eyre!("Failed to write keystore: {e}"))?;
+        println!("Wallet keystore written to: {file_path:?}");
+    } else {
+        println!("Wallet private key: {wallet_private_key}");
+    }
 
     Ok(())
 }
@@ -107,6 +161,235 @@ pub async fn balance(local: bool) -> Result<()> {
     Ok(())
 }
 
+pub async fn allowance(local: bool) -> Result<()> {
+    let network = get_evm_network(local)?;
+    let wallet = crate::wallet::load_wallet(&network)?;
+
+    let allowance = wallet.data_payments_allowance().await?;
+
+    println!("Wallet address: {}", wallet.address());
+    println!(
+        "Data payments contract: {}",
+        network.data_payments_address()
+    );
+    if allowance == Amount::MAX {
+        println!("Allowance: unlimited");
+    } else {
+        println!("Allowance: {allowance}");
+    }
+
+    Ok(())
+}
+
+pub async fn revoke(local: bool) -> Result<()> {
+    let network = get_evm_network(local)?;
+    let wallet = crate::wallet::load_wallet(&network)?;
+
+    println!("Revoking the allowance of the data payments contract...");
+    let tx_hash = wallet.revoke_data_payments_allowance().await?;
+    info!(
+        "Revoked the allowance of wallet {} in tx {tx_hash}",
+        wallet.address()
+    );
+
+    println!("Allowance revoked in transaction: {tx_hash}");
+
+    Ok(())
+}
+
+/// Output formats of `wallet history`.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum HistoryFormat {
+    Table,
+    Csv,
+    Json,
+}
+
+/// Data types to filter `wallet history` by.
+#[derive(Clone, Copy, Debug, ValueEnum)]
+pub enum HistoryDataType {
+    Chunk,
+    GraphEntry,
+    Pointer,
+    Scratchpad,
+}
+
+impl From<HistoryDataType> for DataTypes {
+    fn from(data_type: HistoryDataType) -> Self {
+        match data_type {
+            HistoryDataType::Chunk => DataTypes::Chunk,
+            HistoryDataType::GraphEntry => DataTypes::GraphEntry,
+            HistoryDataType::Pointer => DataTypes::Pointer,
+            HistoryDataType::Scratchpad => DataTypes::Scratchpad,
+        }
+    }
+}
+
+/// A payment of the history, with its values formatted for export.
+#[derive(Serialize)]
+struct PaymentView {
+    timestamp: u64,
+    wallet: String,
+    data_type: String,
+    content_address: String,
+    price_atto: String,
+    quote_payments: Vec<QuotePaymentView>,
+}
+
+#[derive(Serialize)]
+struct QuotePaymentView {
+    quote_hash: String,
+    rewards_address: String,
+    amount_atto: String,
+    tx_hash: Option<String>,
+}
+
+impl From<&PaymentRecord> for PaymentView {
+    fn from(record: &PaymentRecord) -> Self {
+        Self {
+            timestamp: record.timestamp,
+            wallet: record.wallet.to_string(),
+            data_type: format!("{:?}", record.data_type),
+            content_address: hex::encode(record.content_addr.0),
+            price_atto: record.price.as_atto().to_string(),
+            quote_payments: record
+                .quote_payments
+                .iter()
+                .map(|payment| QuotePaymentView {
+                    quote_hash: payment.quote_hash.to_string(),
+                    rewards_address: payment.rewards_address.to_string(),
+                    amount_atto: payment.amount.as_atto().to_string(),
+                    tx_hash: payment.tx_hash.map(|tx_hash| tx_hash.to_string()),
+                })
+                .collect(),
+        }
+    }
+}
+
+pub fn history(
+    since: Option<u64>,
+    until: Option<u64>,
+    wallet: Option<String>,
+    data_type: Option<HistoryDataType>,
+    address: Option<String>,
+    format: HistoryFormat,
+    output: Option<PathBuf>,
+) -> Result<()> {
+    let wallet = wallet
+        .map(|wallet| {
+            RewardsAddress::from_str(&wallet)
+                .map_err(|_| eyre!("Please provide a valid wallet address"))
+        })
+        .transpose()?;
+    let content_addr = address
+        .map(|address| {
+            hex::decode(&address)
+                .ok()
+                .and_then(|bytes| <[u8; 32]>::try_from(bytes).ok())
+                .map(XorName)
+                .ok_or_else(|| eyre!("Please provide a valid hex-encoded address"))
+        })
+        .transpose()?;
+    let filter = PaymentFilter {
+        since,
+        until,
+        wallet,
+        data_type: data_type.map(DataTypes::from),
+        content_addr,
+    };
+
+    let ledger = PaymentLedger::new(get_payment_ledger_path()?);
+    let records = ledger
+        .query(&filter)
+        .wrap_err("Failed to read the payment history")?;
+    info!(
+        "Found {} payments in {:?} matching {filter:?}",
+        records.len(),
+        ledger.path()
+    );
+    let payments: Vec<PaymentView> = records.iter().map(PaymentView::from).collect();
+
+    let formatted = match format {
+        HistoryFormat::Table => {
+            let total: Amount = records.iter().map(|record| record.price.as_atto()).sum();
+            let mut table = payments_table(&payments).to_string();
+            table.push_str(&format!(
+                "Total: {total} AttoTokens paid for {} records\n",
+                payments.len()
+            ));
+            table
+        }
+        HistoryFormat::Csv => payments_csv(&payments),
+        HistoryFormat::Json => serde_json::to_string_pretty(&payments)? + "\n",
+    };
+
+    match output {
+        Some(path) => {
+            std::fs::write(&path, formatted)
+                .wrap_err(format!("Failed to write the payment history to {path:?}"))?;
+            println!("Wrote {} payments to {path:?}", payments.len());
+        }
+        None => print!("{formatted}"),
+    }
+
+    Ok(())
+}
+
+fn payments_table(payments: &[PaymentView]) -> Table {
+    let mut table = Table::new();
+
+    table.add_row(Row::new(vec![
+        Cell::new("Timestamp"),
+        Cell::new("Data Type"),
+        Cell::new("Address"),
+        Cell::new("Price (AttoTokens)"),
+        Cell::new("Transactions"),
+    ]));
+
+    for payment in payments {
+        let mut tx_hashes: Vec<&str> = payment
+            .quote_payments
+            .iter()
+            .filter_map(|quote_payment| quote_payment.tx_hash.as_deref())
+            .collect();
+        tx_hashes.sort();
+        tx_hashes.dedup();
+
+        table.add_row(Row::new(vec![
+            Cell::new(&payment.timestamp.to_string()),
+            Cell::new(&payment.data_type),
+            Cell::new(&payment.content_address),
+            Cell::new(&payment.price_atto),
+            Cell::new(&tx_hashes.join("\n")),
+        ]));
+    }
+
+    table
+}
+
+/// One line per quote payment, so that each transaction can be reconciled.
+fn payments_csv(payments: &[PaymentView]) -> String {
+    let mut csv = String::from(
+        "timestamp,wallet,data_type,content_address,quote_hash,rewards_address,amount_atto,tx_hash\n",
+    );
+    for payment in payments {
+        for quote_payment in &payment.quote_payments {
+            csv.push_str(&format!(
+                "{},{},{},{},{},{},{},{}\n",
+                payment.timestamp,
+                payment.wallet,
+                payment.data_type,
+                payment.content_address,
+                quote_payment.quote_hash,
+                quote_payment.rewards_address,
+                quote_payment.amount_atto,
+                quote_payment.tx_hash.as_deref().unwrap_or_default(),
+            ));
+        }
+    }
+    csv
+}
+
 fn maybe_request_password(no_password: bool, password: Option<String>) -> Result<Option<String>> {
     if no_password && password.is_some() {
         return Err(eyre!(
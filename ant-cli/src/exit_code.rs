@@ -9,8 +9,11 @@
 #![allow(dead_code)]
 
 use autonomi::{
-    client::{analyze::AnalysisError, payment::PayError, ConnectError, GetError, PutError},
-    files::{DownloadError, UploadError},
+    client::{
+        analyze::AnalysisError, payment::PayError, register::RegisterError, ConnectError, GetError,
+        PutError,
+    },
+    files::{DownloadError, FolderError, UploadError},
     BootstrapError,
 };
 use color_eyre::eyre::Report;
@@ -21,6 +24,11 @@ pub const IO_ERROR: i32 = 12;
 const NETWORK_ERROR: i32 = 13;
 const PROTOCOL_ERROR: i32 = 14;
 const SELF_ENCRYPTION_ERROR: i32 = 15;
+pub(crate) const INCOMPLETE_UPLOAD_EXIT_CODE: i32 = 16;
+pub(crate) const PAYMENT_POLICY_EXIT_CODE: i32 = 24;
+pub(crate) const HASH_MISMATCH_EXIT_CODE: i32 = 33;
+const UNSAFE_ARCHIVE_EXIT_CODE: i32 = 34;
+const REGISTER_ERROR: i32 = 46;
 
 pub type ExitCodeError = (Report, i32);
 
@@ -32,6 +40,8 @@ pub(crate) fn upload_exit_code(err: &UploadError) -> i32 {
         UploadError::GetError(err) => get_error_exit_code(err),
         UploadError::Serialization(_) => SERIALIZATION_ERROR,
         UploadError::Deserialization(_) => SERIALIZATION_ERROR,
+        UploadError::Incomplete(_) => INCOMPLETE_UPLOAD_EXIT_CODE,
+        UploadError::PaidArchiveMismatch(_) => INVALID_INPUT_EXIT_CODE,
     }
 }
 
@@ -39,6 +49,10 @@ pub(crate) fn download_exit_code(err: &DownloadError) -> i32 {
     match err {
         DownloadError::GetError(get_error) => get_error_exit_code(get_error),
         DownloadError::IoError(_) => IO_ERROR,
+        DownloadError::HashMismatch(_) => HASH_MISMATCH_EXIT_CODE,
+        DownloadError::UnsafePath(_) | DownloadError::UnsafeSymlink { .. } => {
+            UNSAFE_ARCHIVE_EXIT_CODE
+        }
     }
 }
 
@@ -48,6 +62,10 @@ pub(crate) fn pay_error_exit_code(err: &PayError) -> i32 {
         PayError::EvmWalletError(_) => 22,
         PayError::SelfEncryption(_) => SELF_ENCRYPTION_ERROR,
         PayError::Cost(_) => 23,
+        PayError::Policy(_) => PAYMENT_POLICY_EXIT_CODE,
+        PayError::PartialPayment { error, .. } => pay_error_exit_code(error),
+        PayError::Shared(error) => pay_error_exit_code(error),
+        PayError::BatchAborted => 25,
     }
 }
 
@@ -83,6 +101,17 @@ pub(crate) fn put_error_exit_code(err: &PutError) -> i32 {
     }
 }
 
+pub(crate) fn folder_error_exit_code(err: &FolderError) -> i32 {
+    match err {
+        FolderError::Register(RegisterError::GetError(err)) => get_error_exit_code(err),
+        FolderError::Register(RegisterError::PutError(err)) => put_error_exit_code(err),
+        FolderError::Register(_) => REGISTER_ERROR,
+        FolderError::Serialization(_) => SERIALIZATION_ERROR,
+        FolderError::Deserialization(_) => SERIALIZATION_ERROR,
+        FolderError::NotPublic => INVALID_INPUT_EXIT_CODE,
+    }
+}
+
 pub(crate) fn bootstrap_error_exit_code(err: &BootstrapError) -> i32 {
     match err {
         BootstrapError::NoBootstrapPeersFound => 51,
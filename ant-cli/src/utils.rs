@@ -31,6 +31,7 @@ pub fn collect_upload_summary(
                             record_count += upload_summary.records_paid;
                             records_already_paid += upload_summary.records_already_paid;
                         }
+                        Some(_) => {}
                         None => break,
                     }
                 }
@@ -40,12 +41,10 @@ pub fn collect_upload_summary(
 
         // try to drain the event receiver in case there are any more events
         while let Ok(event) = event_receiver.try_recv() {
-            match event {
-                ClientEvent::UploadComplete(upload_summary) => {
-                    tokens_spent += upload_summary.tokens_spent;
-                    record_count += upload_summary.records_paid;
-                    records_already_paid += upload_summary.records_already_paid;
-                }
+            if let ClientEvent::UploadComplete(upload_summary) = event {
+                tokens_spent += upload_summary.tokens_spent;
+                record_count += upload_summary.records_paid;
+                records_already_paid += upload_summary.records_already_paid;
             }
         }
 
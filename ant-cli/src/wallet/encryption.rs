@@ -6,8 +6,12 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
+//! Legacy format of the password protected wallets, they are now stored as Ethereum V3 JSON keystore files.
+//! Wallets in this format are only decrypted, to migrate them to a keystore file.
+
 use color_eyre::eyre::eyre;
 use color_eyre::Result;
+#[cfg(test)]
 use rand::Rng;
 use ring::aead::{BoundKey, Nonce, NonceSequence};
 use ring::error::Unspecified;
@@ -29,6 +33,7 @@ impl NonceSequence for NonceSeq {
     }
 }
 
+#[cfg(test)]
 pub fn encrypt_private_key(private_key: &str, password: &str) -> Result<String> {
     // Generate a random salt
     // Salt is used to ensure unique derived keys even for identical passwords
@@ -6,7 +6,7 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use crate::wallet::encryption::{decrypt_private_key, encrypt_private_key};
+use crate::wallet::encryption::decrypt_private_key;
 use crate::wallet::input::{get_password_input, get_wallet_selection_input};
 use crate::wallet::DUMMY_NETWORK;
 use autonomi::{Network, RewardsAddress, Wallet};
@@ -16,10 +16,16 @@ use const_hex::traits::FromHex;
 use prettytable::{Cell, Row, Table};
 use std::ffi::OsString;
 use std::io::Read;
-use std::path::PathBuf;
+use std::path::{Path, PathBuf};
 use std::sync::OnceLock;
 
+/// Extension of the Ethereum V3 JSON keystore files, used for password protected wallets.
+const KEYSTORE_EXT: &str = ".json";
+/// Extension of the wallets encrypted with the legacy format of [`crate::wallet::encryption`].
+/// They are migrated to keystore files the first time they are loaded.
 const ENCRYPTED_PRIVATE_KEY_EXT: &str = ".encrypted";
+/// Extension given to legacy encrypted wallets once they are migrated, they are kept as a backup.
+const MIGRATED_PRIVATE_KEY_EXT: &str = ".migrated";
 
 pub static SELECTED_WALLET_ADDRESS: OnceLock<String> = OnceLock::new();
 
@@ -37,7 +43,7 @@ pub(crate) fn get_client_wallet_dir_path() -> Result<PathBuf> {
 
 /// Writes the private key (hex-encoded) to disk.
 ///
-/// When a password is set, the private key file will be encrypted.
+/// When a password is set, the private key is stored in an Ethereum V3 JSON keystore file.
 pub(crate) fn store_private_key(
     private_key: &str,
     encryption_password: Option<String>,
@@ -52,11 +58,9 @@ pub(crate) fn store_private_key(
     // If `encryption_password` is provided, the private key will be encrypted with the password.
     // Else it will be saved as plain text.
     if let Some(password) = encryption_password.as_ref() {
-        let encrypted_key = encrypt_private_key(private_key, password)?;
-        let file_name = format!("{wallet_address}{ENCRYPTED_PRIVATE_KEY_EXT}");
-        let file_path = wallets_folder.join(file_name);
-
-        std::fs::write(file_path.clone(), encrypted_key).wrap_err("Failed to store private key")?;
+        let file_name = format!("{wallet_address}{KEYSTORE_EXT}");
+        let file_path = Wallet::write_keystore(private_key, &wallets_folder, &file_name, password)
+            .wrap_err("Failed to store private key")?;
 
         Ok(file_path.into_os_string())
     } else {
@@ -71,42 +75,69 @@ pub(crate) fn store_private_key(
 /// Loads the private key (hex-encoded) from disk.
 ///
 /// If the private key file is encrypted, the function will prompt for the decryption password in the CLI.
+/// Wallets encrypted with the legacy format are migrated to a keystore file with the same password.
 pub(crate) fn load_private_key(wallet_address: &str) -> Result<String> {
     let wallets_folder = get_client_wallet_dir_path()?;
 
-    let mut file_name = wallet_address.to_string();
-
-    // Check if a file with the encrypted extension exists
+    // Favour the plain file, then the keystore, in case several of them exist
+    let plain_file_path = wallets_folder.join(wallet_address);
+    let keystore_file_path = wallets_folder.join(format!("{wallet_address}{KEYSTORE_EXT}"));
     let encrypted_file_path =
         wallets_folder.join(format!("{wallet_address}{ENCRYPTED_PRIVATE_KEY_EXT}"));
 
-    let is_plain = wallets_folder.join(&file_name).exists();
+    if plain_file_path.exists() {
+        return read_wallet_file(&plain_file_path);
+    }
 
-    // Trick to favour the plain file in case they both exist
-    let is_encrypted = encrypted_file_path.exists() && !is_plain;
+    if keystore_file_path.exists() {
+        let password = get_password_input("Enter password to decrypt wallet:");
+        return Wallet::private_key_from_keystore(&keystore_file_path, &password)
+            .map_err(|e| eyre!("Failed to decrypt private key: {e}"));
+    }
 
-    if is_encrypted {
-        file_name.push_str(ENCRYPTED_PRIVATE_KEY_EXT);
+    // If the file is encrypted, prompt for the password and decrypt the key.
+    let encrypted_key = read_wallet_file(&encrypted_file_path)?;
+    let password = get_password_input("Enter password to decrypt wallet:");
+    let private_key = decrypt_private_key(&encrypted_key, &password)
+        .map_err(|e| eyre!("Failed to decrypt private key: {e}"))?;
+
+    if let Err(err) = migrate_encrypted_wallet(&private_key, &password, &encrypted_file_path) {
+        warn!("Failed to migrate wallet {wallet_address} to a keystore file: {err:?}");
+        println!("Failed to migrate the wallet to a keystore file: {err}");
     }
 
-    let file_path = wallets_folder.join(file_name);
+    Ok(private_key)
+}
 
+fn read_wallet_file(file_path: &Path) -> Result<String> {
     let mut file =
-        std::fs::File::open(&file_path).map_err(|e| eyre!("Private key file not found: {e}"))?;
+        std::fs::File::open(file_path).map_err(|e| eyre!("Private key file not found: {e}"))?;
 
     let mut buffer = String::new();
     file.read_to_string(&mut buffer)
         .map_err(|_| eyre!("Invalid private key file"))?;
 
-    // If the file is encrypted, prompt for the password and decrypt the key.
-    if is_encrypted {
-        let password = get_password_input("Enter password to decrypt wallet:");
+    Ok(buffer)
+}
 
-        decrypt_private_key(&buffer, &password)
-            .map_err(|e| eyre!("Failed to decrypt private key: {e}"))
-    } else {
-        Ok(buffer)
-    }
+/// Store a wallet encrypted with the legacy format as a keystore file, keeping the legacy file as a backup.
+fn migrate_encrypted_wallet(
+    private_key: &str,
+    password: &str,
+    encrypted_file_path: &Path,
+) -> Result<()> {
+    let keystore_file_path = store_private_key(private_key, Some(password.to_string()))?;
+
+    let mut backup_file_path = encrypted_file_path.as_os_str().to_owned();
+    backup_file_path.push(MIGRATED_PRIVATE_KEY_EXT);
+    std::fs::rename(encrypted_file_path, &backup_file_path)
+        .wrap_err("Failed to back up the legacy wallet file")?;
+
+    info!("Migrated wallet {encrypted_file_path:?} to keystore {keystore_file_path:?}");
+    println!("Migrated the wallet to the keystore file: {keystore_file_path:?}");
+    println!("The previous wallet file was kept as: {backup_file_path:?}");
+
+    Ok(())
 }
 
 pub(crate) fn load_wallet_from_address(wallet_address: &str, network: &Network) -> Result<Wallet> {
@@ -174,7 +205,8 @@ fn list_wallets(wallet_files: &[String]) {
     ]));
 
     for (index, wallet_file) in wallet_files.iter().enumerate() {
-        let encrypted = wallet_file.contains(ENCRYPTED_PRIVATE_KEY_EXT);
+        let encrypted =
+            wallet_file.ends_with(KEYSTORE_EXT) || wallet_file.ends_with(ENCRYPTED_PRIVATE_KEY_EXT);
 
         table.add_row(Row::new(vec![
             Cell::new(&(index + 1).to_string()),
@@ -201,5 +233,9 @@ fn get_wallet_files(wallets_folder: &PathBuf) -> Result<Vec<String>> {
 }
 
 fn filter_wallet_file_extension(wallet_file: &str) -> String {
-    wallet_file.replace(ENCRYPTED_PRIVATE_KEY_EXT, "")
+    wallet_file
+        .strip_suffix(KEYSTORE_EXT)
+        .or_else(|| wallet_file.strip_suffix(ENCRYPTED_PRIVATE_KEY_EXT))
+        .unwrap_or(wallet_file)
+        .to_string()
 }
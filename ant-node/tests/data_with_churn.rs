@@ -100,8 +100,8 @@ async fn data_availability_during_churn() -> Result<()> {
         .await
         .unwrap();
     let peer = network.bootstrap_peer().await;
-    let config = ClientConfig {
-        init_peers_config: InitialPeersConfig {
+    let config = ClientConfig::default()
+        .with_init_peers_config(InitialPeersConfig {
             first: false,
             local: true,
             addrs: vec![peer],
@@ -109,10 +109,8 @@ async fn data_availability_during_churn() -> Result<()> {
             disable_mainnet_contacts: true,
             ignore_cache: false,
             network_contacts_url: vec![],
-        },
-        evm_network: evm_network.clone(),
-        strategy: autonomi::ClientOperatingStrategy::default(),
-    };
+        })
+        .with_evm_network(evm_network.clone());
     let client = Client::init_with_config(config).await.unwrap();
 
     let test_duration = if let Ok(str) = std::env::var("TEST_DURATION_MINS") {
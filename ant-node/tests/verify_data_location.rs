@@ -99,8 +99,8 @@ async fn verify_data_location() -> Result<()> {
         .await
         .unwrap();
     let peer = network.bootstrap_peer().await;
-    let config = ClientConfig {
-        init_peers_config: InitialPeersConfig {
+    let config = ClientConfig::default()
+        .with_init_peers_config(InitialPeersConfig {
             first: false,
             local: true,
             addrs: vec![peer],
@@ -108,10 +108,8 @@ async fn verify_data_location() -> Result<()> {
             disable_mainnet_contacts: true,
             ignore_cache: false,
             network_contacts_url: vec![],
-        },
-        evm_network: evm_network.clone(),
-        strategy: autonomi::ClientOperatingStrategy::default(),
-    };
+        })
+        .with_evm_network(evm_network.clone());
     let client = Client::init_with_config(config).await.unwrap();
 
     // let node_rpc_address = get_all_rpc_addresses(true)?;
@@ -19,7 +19,7 @@ async fn main() -> Result<(), Box<dyn std::error::Error>> {
     let _data_fetched = client.data_get_public(&data_addr).await?;
 
     // Put and fetch directory from local file system.
-    let (_cost, dir_addr) = client
+    let (_cost, dir_addr, _report) = client
         .dir_upload_public("files/to/upload".into(), wallet.into())
         .await?;
     client
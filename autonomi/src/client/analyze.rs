@@ -243,14 +243,20 @@ async fn analyze_datamap(
 
     let data_map_level: DataMapLevel =
         rmp_serde::from_slice(datamap.0.value()).map_err(|_| AnalysisError::UnrecognizedInput)?;
-    let (map, points_to_a_data_map) = match data_map_level {
+    let (chunks, points_to_a_data_map) = match data_map_level {
         DataMapLevel::Additional(map) => {
             println_if_verbose!("Identified a DataMap whose contents is another DataMap, the content might be pretty big...");
-            (map, true)
+            (chunk_list_from_datamap(map), true)
         }
         DataMapLevel::First(map) => {
             println_if_verbose!("Identified a DataMap which directly contains data...");
-            (map, false)
+            (chunk_list_from_datamap(map), false)
+        }
+        DataMapLevel::Inline(_) => {
+            println_if_verbose!(
+                "Identified a DataMap holding data too small to be self-encrypted..."
+            );
+            (vec![], false)
         }
     };
 
@@ -295,12 +301,12 @@ async fn analyze_datamap(
     let analysis = match stored_at {
         Some(addr) => Analysis::DataMap {
             address: addr,
-            chunks: chunk_list_from_datamap(map),
+            chunks,
             data,
             points_to_a_data_map,
         },
         None => Analysis::RawDataMap {
-            chunks: chunk_list_from_datamap(map),
+            chunks,
             data,
             points_to_a_data_map,
         },
@@ -0,0 +1,343 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::io;
+use std::path::{Path, PathBuf};
+use std::sync::atomic::{AtomicU64, Ordering};
+use std::sync::{Arc, Mutex};
+use std::time::SystemTime;
+
+use bytes::Bytes;
+use xor_name::XorName;
+
+use crate::client::data_types::chunk::{Chunk, ChunkAddress};
+
+/// Configuration of the on-disk cache of the chunks fetched from the Network.
+///
+/// Chunks are content addressed so they never change: the cache only evicts the least recently used
+/// chunks when it is full, and checks the content of a cached chunk against its address when reading it.
+#[derive(Debug, Clone)]
+pub struct ChunkCacheConfig {
+    /// Directory to store the cached chunks in
+    pub dir: PathBuf,
+    /// Maximum total size of the cached chunks, in bytes
+    pub max_size: u64,
+}
+
+impl ChunkCacheConfig {
+    pub fn new(dir: PathBuf, max_size: u64) -> Self {
+        Self { dir, max_size }
+    }
+}
+
+/// Statistics of the chunk cache since the client was initialized, see [`crate::Client::chunk_cache_stats`].
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub struct ChunkCacheStats {
+    /// Chunks read from the cache
+    pub hits: u64,
+    /// Chunks not found in the cache, or found corrupted
+    pub misses: u64,
+    /// Chunks removed to make room for new ones
+    pub evictions: u64,
+    /// Number of chunks in the cache
+    pub entries: u64,
+    /// Total size of the chunks in the cache, in bytes
+    pub size: u64,
+}
+
+/// The cached chunks, from the least to the most recently used
+#[derive(Default)]
+struct LruIndex {
+    by_use: BTreeMap<u64, XorName>,
+    entries: HashMap<XorName, (u64, u64)>,
+    next_use: u64,
+    size: u64,
+}
+
+impl LruIndex {
+    /// Mark a chunk as the most recently used, adding it if missing
+    fn touch(&mut self, name: XorName, size: u64) {
+        let used = self.next_use;
+        self.next_use += 1;
+        if let Some((previous_use, previous_size)) = self.entries.insert(name, (used, size)) {
+            self.by_use.remove(&previous_use);
+            self.size -= previous_size;
+        }
+        self.by_use.insert(used, name);
+        self.size += size;
+    }
+
+    fn remove(&mut self, name: &XorName) {
+        if let Some((used, size)) = self.entries.remove(name) {
+            self.by_use.remove(&used);
+            self.size -= size;
+        }
+    }
+
+    /// Remove and return the least recently used chunk
+    fn pop_oldest(&mut self) -> Option<XorName> {
+        let (_, name) = self.by_use.pop_first()?;
+        if let Some((_, size)) = self.entries.remove(&name) {
+            self.size -= size;
+        }
+        Some(name)
+    }
+}
+
+/// On-disk LRU cache of chunks, one file per chunk named after its address.
+pub(crate) struct ChunkCache {
+    dir: PathBuf,
+    max_size: u64,
+    index: Mutex<LruIndex>,
+    hits: AtomicU64,
+    misses: AtomicU64,
+    evictions: AtomicU64,
+    next_tmp: AtomicU64,
+}
+
+impl ChunkCache {
+    /// Open the cache, indexing the chunks already in its directory by last use.
+    pub(crate) fn open(config: &ChunkCacheConfig) -> io::Result<Self> {
+        fs::create_dir_all(&config.dir)?;
+        let mut found = vec![];
+        for entry in fs::read_dir(&config.dir)? {
+            let entry = entry?;
+            // a chunk being written when the client stopped
+            if entry.path().extension().is_some_and(|ext| ext == "tmp") {
+                remove_chunk_file(&entry.path());
+                continue;
+            }
+            let Some(name) = entry.file_name().to_str().and_then(xorname_from_hex) else {
+                continue;
+            };
+            let metadata = entry.metadata()?;
+            let used = metadata.modified().unwrap_or(SystemTime::UNIX_EPOCH);
+            found.push((used, name, metadata.len()));
+        }
+        found.sort();
+
+        let cache = Self {
+            dir: config.dir.clone(),
+            max_size: config.max_size,
+            index: Mutex::new(LruIndex::default()),
+            hits: AtomicU64::new(0),
+            misses: AtomicU64::new(0),
+            evictions: AtomicU64::new(0),
+            next_tmp: AtomicU64::new(0),
+        };
+        {
+            let mut index = cache.lock_index();
+            for (_, name, size) in found {
+                index.touch(name, size);
+            }
+        }
+        cache.evict();
+        debug!(
+            "Opened chunk cache at {:?} with {:?}",
+            cache.dir,
+            cache.stats()
+        );
+        Ok(cache)
+    }
+
+    /// Get a chunk from the cache on the blocking thread pool, see [`ChunkCache::get`].
+    pub(crate) async fn get_async(self: &Arc<Self>, addr: ChunkAddress) -> Option<Chunk> {
+        let cache = Arc::clone(self);
+        tokio::task::spawn_blocking(move || cache.get(&addr))
+            .await
+            .unwrap_or_else(|err| {
+                warn!("Failed to read chunk {addr:?} from the cache: {err}");
+                None
+            })
+    }
+
+    /// Add a chunk to the cache on the blocking thread pool, see [`ChunkCache::insert`].
+    pub(crate) async fn insert_async(self: &Arc<Self>, chunk: Chunk) {
+        let cache = Arc::clone(self);
+        if let Err(err) = tokio::task::spawn_blocking(move || cache.insert(&chunk)).await {
+            warn!("Failed to cache chunk: {err}");
+        }
+    }
+
+    /// Get a chunk from the cache, making sure its content matches its address.
+    pub(crate) fn get(&self, addr: &ChunkAddress) -> Option<Chunk> {
+        let name = *addr.xorname();
+        let path = self.chunk_path(&name);
+        let value = match fs::read(&path) {
+            Ok(value) => Bytes::from(value),
+            Err(_) => {
+                self.lock_index().remove(&name);
+                self.misses.fetch_add(1, Ordering::Relaxed);
+                return None;
+            }
+        };
+
+        let chunk = Chunk::new(value);
+        if *chunk.name() != name {
+            warn!("Cached chunk at {path:?} does not match its address {addr:?}, removing it");
+            self.remove(&name);
+            self.misses.fetch_add(1, Ordering::Relaxed);
+            return None;
+        }
+
+        // the modification time records the last use across restarts
+        if let Err(err) = fs::OpenOptions::new()
+            .write(true)
+            .open(&path)
+            .and_then(|f| f.set_modified(SystemTime::now()))
+        {
+            debug!("Failed to update the last use of cached chunk {path:?}: {err}");
+        }
+        self.lock_index().touch(name, chunk.size() as u64);
+        self.hits.fetch_add(1, Ordering::Relaxed);
+        Some(chunk)
+    }
+
+    /// Add a chunk to the cache, evicting the least recently used chunks if needed.
+    pub(crate) fn insert(&self, chunk: &Chunk) {
+        let size = chunk.size() as u64;
+        if size > self.max_size {
+            return;
+        }
+        let name = *chunk.name();
+        let path = self.chunk_path(&name);
+
+        // write then rename, so readers never see a partially written chunk,
+        // with a temporary file per write as the same chunk can be inserted concurrently
+        let tmp = self.next_tmp.fetch_add(1, Ordering::Relaxed);
+        let tmp_path = path.with_extension(format!("{}-{tmp}.tmp", std::process::id()));
+        let res = fs::write(&tmp_path, chunk.value()).and_then(|_| fs::rename(&tmp_path, &path));
+        if let Err(err) = res {
+            warn!("Failed to cache chunk {name:?} at {path:?}: {err}");
+            let _ = fs::remove_file(&tmp_path);
+            return;
+        }
+        self.lock_index().touch(name, size);
+        self.evict();
+    }
+
+    pub(crate) fn stats(&self) -> ChunkCacheStats {
+        let index = self.lock_index();
+        ChunkCacheStats {
+            hits: self.hits.load(Ordering::Relaxed),
+            misses: self.misses.load(Ordering::Relaxed),
+            evictions: self.evictions.load(Ordering::Relaxed),
+            entries: index.entries.len() as u64,
+            size: index.size,
+        }
+    }
+
+    /// Remove the least recently used chunks until the cache fits in its maximum size
+    fn evict(&self) {
+        loop {
+            let name = {
+                let mut index = self.lock_index();
+                if index.size <= self.max_size {
+                    return;
+                }
+                match index.pop_oldest() {
+                    Some(name) => name,
+                    None => return,
+                }
+            };
+            debug!("Evicting chunk {name:?} from the cache");
+            remove_chunk_file(&self.chunk_path(&name));
+            self.evictions.fetch_add(1, Ordering::Relaxed);
+        }
+    }
+
+    fn remove(&self, name: &XorName) {
+        self.lock_index().remove(name);
+        remove_chunk_file(&self.chunk_path(name));
+    }
+
+    fn chunk_path(&self, name: &XorName) -> PathBuf {
+        self.dir.join(hex::encode(name.0))
+    }
+
+    fn lock_index(&self) -> std::sync::MutexGuard<'_, LruIndex> {
+        // the index is always left consistent, so a poisoned lock can be used
+        self.index
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+fn remove_chunk_file(path: &Path) {
+    if let Err(err) = fs::remove_file(path) {
+        if err.kind() != io::ErrorKind::NotFound {
+            warn!("Failed to remove cached chunk {path:?}: {err}");
+        }
+    }
+}
+
+fn xorname_from_hex(hex: &str) -> Option<XorName> {
+    let bytes: [u8; 32] = hex::decode(hex).ok()?.try_into().ok()?;
+    Some(XorName(bytes))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn chunk(byte: u8, size: usize) -> Chunk {
+        Chunk::new(Bytes::from(vec![byte; size]))
+    }
+
+    #[test]
+    fn lru_eviction_and_verification() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ChunkCacheConfig::new(dir.path().to_path_buf(), 250);
+        let cache = ChunkCache::open(&config).unwrap();
+
+        let (a, b, c) = (chunk(1, 100), chunk(2, 100), chunk(3, 100));
+        cache.insert(&a);
+        cache.insert(&b);
+        assert_eq!(cache.get(a.address()), Some(a.clone()));
+        // b is the least recently used
+        cache.insert(&c);
+        assert_eq!(cache.get(b.address()), None);
+        assert_eq!(cache.get(c.address()), Some(c.clone()));
+
+        let stats = cache.stats();
+        assert_eq!((stats.hits, stats.misses, stats.evictions), (2, 1, 1));
+        assert_eq!((stats.entries, stats.size), (2, 200));
+
+        // a corrupted chunk is a miss and is removed
+        fs::write(cache.chunk_path(a.name()), b"corrupted").unwrap();
+        assert_eq!(cache.get(a.address()), None);
+        assert_eq!(cache.stats().entries, 1);
+
+        // chunks are found again when reopening the cache
+        let reopened = ChunkCache::open(&config).unwrap();
+        assert_eq!(reopened.stats().entries, 1);
+        assert_eq!(reopened.get(c.address()), Some(c));
+    }
+
+    #[test]
+    fn stray_temporary_files_are_removed_on_open() {
+        let dir = tempfile::tempdir().unwrap();
+        let config = ChunkCacheConfig::new(dir.path().to_path_buf(), 250);
+        let a = chunk(1, 100);
+        let tmp_path = dir
+            .path()
+            .join(format!("{}.1234-0.tmp", hex::encode(a.name().0)));
+        fs::write(&tmp_path, b"partial").unwrap();
+
+        let cache = ChunkCache::open(&config).unwrap();
+        assert!(!tmp_path.exists());
+        assert_eq!(cache.stats().entries, 0);
+
+        cache.insert(&a);
+        cache.insert(&a);
+        assert_eq!(fs::read_dir(dir.path()).unwrap().count(), 1);
+        assert_eq!(cache.get(a.address()), Some(a));
+    }
+}
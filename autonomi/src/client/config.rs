@@ -11,13 +11,18 @@ use ant_networking::{GetRecordCfg, PutRecordCfg, VerificationKind};
 use ant_protocol::messages::ChunkProof;
 use libp2p::{kad::Record, PeerId};
 use rand::{thread_rng, Rng};
-use std::{collections::HashSet, num::NonZero};
+use std::{collections::HashSet, num::NonZero, path::PathBuf};
 
+pub use super::chunk_cache::{ChunkCacheConfig, ChunkCacheStats};
+pub use super::payment_policy::{PaymentApproval, PaymentPolicy, PaymentPolicyError};
 pub use ant_bootstrap::{error::Error as BootstrapError, InitialPeersConfig};
 pub use ant_networking::{ResponseQuorum, RetryStrategy};
 
 /// Configuration for the [`crate::Client`] which can be provided through: [`crate::Client::init_with_config`].
+///
+/// New options may be added, so it is built from [`ClientConfig::default`] with the `with_*` methods.
 #[derive(Debug, Clone, Default)]
+#[non_exhaustive]
 pub struct ClientConfig {
     /// Configurations to fetch the initial peers which is used to bootstrap the network.
     /// Also contains the configurations to the bootstrap cache.
@@ -28,6 +33,54 @@ pub struct ClientConfig {
 
     /// Strategy for data operations by the client.
     pub strategy: ClientOperatingStrategy,
+
+    /// On-disk cache of the chunks fetched from the network, disabled if `None`.
+    pub chunk_cache: Option<ChunkCacheConfig>,
+
+    /// Limits on what the client may spend, and an optional approval of each payment.
+    pub payment_policy: PaymentPolicy,
+
+    /// File of the local ledger of the payments made by the client, disabled if `None`.
+    /// See [`crate::client::payment::PaymentLedger`].
+    pub payment_ledger: Option<PathBuf>,
+}
+
+impl ClientConfig {
+    /// Set the configurations to fetch the initial peers.
+    pub fn with_init_peers_config(mut self, init_peers_config: InitialPeersConfig) -> Self {
+        self.init_peers_config = init_peers_config;
+        self
+    }
+
+    /// Set the EVM network to use for quotations and payments.
+    pub fn with_evm_network(mut self, evm_network: EvmNetwork) -> Self {
+        self.evm_network = evm_network;
+        self
+    }
+
+    /// Set the strategy for data operations by the client.
+    pub fn with_strategy(mut self, strategy: ClientOperatingStrategy) -> Self {
+        self.strategy = strategy;
+        self
+    }
+
+    /// Enable the on-disk cache of the chunks fetched from the network.
+    pub fn with_chunk_cache(mut self, chunk_cache: ChunkCacheConfig) -> Self {
+        self.chunk_cache = Some(chunk_cache);
+        self
+    }
+
+    /// Set the limits on what the client may spend.
+    pub fn with_payment_policy(mut self, payment_policy: PaymentPolicy) -> Self {
+        self.payment_policy = payment_policy;
+        self
+    }
+
+    /// Record the payments made by the client in a local ledger at this path.
+    pub fn with_payment_ledger(mut self, path: PathBuf) -> Self {
+        self.payment_ledger = Some(path);
+        self
+    }
 }
 
 /// Strategy configuration for data operations by the client.
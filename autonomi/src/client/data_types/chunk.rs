@@ -24,7 +24,7 @@ use ant_protocol::{
 };
 use bytes::Bytes;
 use libp2p::kad::Record;
-use self_encryption::{decrypt_full_set, DataMap, EncryptedChunk};
+use self_encryption::{decrypt_full_set, ChunkInfo, DataMap, EncryptedChunk};
 use serde::{Deserialize, Serialize};
 use std::{
     hash::{DefaultHasher, Hash, Hasher},
@@ -111,6 +111,14 @@ impl std::fmt::Debug for DataMapChunk {
     }
 }
 
+/// What a wrapped data map resolves to.
+pub(crate) enum ResolvedDataMap {
+    /// The data map of the source data, whose chunks are on the network
+    DataMap(DataMap),
+    /// The source data itself, too small to be self-encrypted
+    Inline(Bytes),
+}
+
 fn hash_to_short_string(input: &str) -> String {
     let mut hasher = DefaultHasher::new();
     input.hash(&mut hasher);
@@ -123,6 +131,13 @@ impl Client {
     pub async fn chunk_get(&self, addr: &ChunkAddress) -> Result<Chunk, GetError> {
         info!("Getting chunk: {addr:?}");
 
+        if let Some(cache) = self.chunk_cache.as_ref() {
+            if let Some(chunk) = cache.get_async(*addr).await {
+                debug!("Got chunk {addr:?} from the cache");
+                return Ok(chunk);
+            }
+        }
+
         let key = NetworkAddress::from(*addr).to_record_key();
         debug!("Fetching chunk from network at: {key:?}");
 
@@ -136,6 +151,9 @@ impl Client {
 
         if let Ok(true) = RecordHeader::is_record_of_type_chunk(&record) {
             let chunk: Chunk = try_deserialize_record(&record)?;
+            if let Some(cache) = self.chunk_cache.as_ref() {
+                cache.insert_async(chunk.clone()).await;
+            }
             Ok(chunk)
         } else {
             error!(
@@ -388,25 +406,31 @@ impl Client {
         &self,
         data_map_bytes: &Bytes,
     ) -> Result<Bytes, GetError> {
+        match self.resolve_data_map(data_map_bytes).await? {
+            ResolvedDataMap::DataMap(data_map) => self.fetch_from_data_map(&data_map).await,
+            ResolvedDataMap::Inline(data) => Ok(data),
+        }
+    }
+
+    /// Unpack a wrapped data map, fetching any additional data map levels, down to the data map of the source data.
+    pub(crate) async fn resolve_data_map(
+        &self,
+        data_map_bytes: &Bytes,
+    ) -> Result<ResolvedDataMap, GetError> {
         let mut data_map_level: DataMapLevel = rmp_serde::from_slice(data_map_bytes)
             .map_err(GetError::InvalidDataMap)
             .inspect_err(|err| error!("Error deserializing data map: {err:?}"))?;
 
         loop {
-            let data_map = match &data_map_level {
-                DataMapLevel::First(map) => map,
-                DataMapLevel::Additional(map) => map,
-            };
-            let data = self.fetch_from_data_map(data_map).await?;
-
-            match &data_map_level {
-                DataMapLevel::First(_) => break Ok(data),
-                DataMapLevel::Additional(_) => {
+            match data_map_level {
+                DataMapLevel::First(map) => break Ok(ResolvedDataMap::DataMap(map)),
+                DataMapLevel::Inline(data) => break Ok(ResolvedDataMap::Inline(data)),
+                DataMapLevel::Additional(map) => {
+                    let data = self.fetch_from_data_map(&map).await?;
                     data_map_level = rmp_serde::from_slice(&data).map_err(|err| {
                         error!("Error deserializing data map: {err:?}");
                         GetError::InvalidDataMap(err)
                     })?;
-                    continue;
                 }
             };
         }
@@ -415,8 +439,23 @@ impl Client {
     /// Fetch and decrypt all chunks in the data map.
     pub(crate) async fn fetch_from_data_map(&self, data_map: &DataMap) -> Result<Bytes, GetError> {
         debug!("Fetching encrypted data chunks from data map {data_map:?}");
+        let encrypted_chunks = self.fetch_encrypted_chunks(data_map.infos()).await?;
+
+        let data = decrypt_full_set(data_map, &encrypted_chunks).map_err(|e| {
+            error!("Error decrypting encrypted_chunks: {e:?}");
+            GetError::Decryption(crate::self_encryption::Error::SelfEncryption(e))
+        })?;
+        debug!("Successfully decrypted all the chunks");
+        Ok(data)
+    }
+
+    /// Fetch the encrypted chunks described by the given chunk infos.
+    pub(crate) async fn fetch_encrypted_chunks(
+        &self,
+        infos: Vec<ChunkInfo>,
+    ) -> Result<Vec<EncryptedChunk>, GetError> {
         let mut download_tasks = vec![];
-        for info in data_map.infos() {
+        for info in infos {
             download_tasks.push(async move {
                 match self
                     .chunk_get(&ChunkAddress::new(info.dst_hash))
@@ -441,18 +480,12 @@ impl Client {
                 }
             });
         }
-        debug!("Successfully fetched all the encrypted chunks");
         let encrypted_chunks =
             process_tasks_with_max_concurrency(download_tasks, *CHUNK_DOWNLOAD_BATCH_SIZE)
                 .await
                 .into_iter()
                 .collect::<Result<Vec<EncryptedChunk>, GetError>>()?;
-
-        let data = decrypt_full_set(data_map, &encrypted_chunks).map_err(|e| {
-            error!("Error decrypting encrypted_chunks: {e:?}");
-            GetError::Decryption(crate::self_encryption::Error::SelfEncryption(e))
-        })?;
-        debug!("Successfully decrypted all the chunks");
-        Ok(data)
+        debug!("Successfully fetched all the encrypted chunks");
+        Ok(encrypted_chunks)
     }
 }
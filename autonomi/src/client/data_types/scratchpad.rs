@@ -287,6 +287,16 @@ impl Client {
             return Err(ScratchpadError::CannotUpdateNewScratchpad);
         };
 
+        self.scratchpad_put_update(scratchpad).await
+    }
+
+    /// Store a new version of an existing scratchpad on the network, without paying for it.
+    pub(crate) async fn scratchpad_put_update(
+        &self,
+        scratchpad: Scratchpad,
+    ) -> Result<(), ScratchpadError> {
+        let address = *scratchpad.address();
+
         // make sure the scratchpad is valid
         Self::scratchpad_verify(&scratchpad)?;
 
@@ -14,6 +14,10 @@ use xor_name::XorName;
 pub mod private;
 /// Public data on the network, readable by anyone with the DataAddr
 pub mod public;
+/// Streaming and range reads of data on the network
+pub mod stream;
+
+pub use stream::DataStream;
 
 /// A [`DataAddress`] which points to a DataMap
 #[derive(Clone, Copy, Eq, PartialEq, Ord, PartialOrd, Hash)]
@@ -0,0 +1,317 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::future::Future;
+use std::io::SeekFrom;
+use std::ops::Range;
+use std::pin::Pin;
+use std::sync::{Arc, Mutex};
+use std::task::{Context, Poll};
+
+use bytes::Bytes;
+use self_encryption::{DataMap, EncryptedChunk};
+use tokio::io::{AsyncRead, AsyncSeek, ReadBuf};
+
+use super::DataAddress;
+use crate::client::data_types::chunk::{ChunkAddress, DataMapChunk, ResolvedDataMap};
+use crate::client::GetError;
+use crate::Client;
+
+type ChunkFuture = Pin<Box<dyn Future<Output = Result<Bytes, GetError>> + Send>>;
+
+/// A reader over self-encrypted data on the network.
+///
+/// Only the chunks covering the bytes that are read are fetched and decrypted,
+/// so the data never needs to fit in memory and arbitrary byte ranges can be served,
+/// either through [`DataStream::read_range`] or through the [`AsyncRead`] and [`AsyncSeek`] implementations.
+///
+/// # Example
+///
+/// ```no_run
+/// use autonomi::Client;
+/// use tokio::io::{AsyncReadExt, AsyncSeekExt};
+/// # #[tokio::main]
+/// # async fn main() -> Result<(), Box<dyn std::error::Error>> {
+/// # let client = Client::init().await?;
+/// # let data_map = todo!();
+/// let mut stream = client.data_stream(&data_map).await?;
+/// // read 1024 bytes starting at position 4096
+/// let range = stream.read_range(4096, 1024).await?;
+/// // or use it as an async reader
+/// stream.seek(std::io::SeekFrom::Start(4096)).await?;
+/// let mut buf = vec![0; 1024];
+/// stream.read_exact(&mut buf).await?;
+/// # Ok(())
+/// # }
+/// ```
+pub struct DataStream {
+    client: Client,
+    source: Arc<Source>,
+    /// Position of the first byte of each chunk in the original data, by chunk index
+    chunk_starts: Vec<u64>,
+    size: u64,
+    position: u64,
+    /// The last chunk that was decrypted while reading
+    current_chunk: Option<(usize, Bytes)>,
+    /// The chunk being fetched while reading.
+    /// Only ever accessed mutably, the mutex is there to keep the stream `Sync`.
+    pending_chunk: Option<(usize, Mutex<ChunkFuture>)>,
+}
+
+// Where the content of a stream comes from.
+enum Source {
+    // Self-encrypted chunks on the network
+    Chunks(DataMap),
+    // Data too small to be self-encrypted, held in the data map chunk itself
+    Inline(Bytes),
+}
+
+impl DataStream {
+    fn new(client: Client, data_map: ResolvedDataMap) -> Self {
+        let source = match data_map {
+            ResolvedDataMap::DataMap(data_map) => Source::Chunks(data_map),
+            ResolvedDataMap::Inline(data) => Source::Inline(data),
+        };
+        let (chunk_starts, size) = match &source {
+            Source::Chunks(data_map) => (chunk_starts(data_map), data_map.file_size() as u64),
+            Source::Inline(data) => (vec![0], data.len() as u64),
+        };
+        Self {
+            client,
+            source: Arc::new(source),
+            chunk_starts,
+            size,
+            position: 0,
+            current_chunk: None,
+            pending_chunk: None,
+        }
+    }
+
+    /// Size in bytes of the original data.
+    pub fn size(&self) -> u64 {
+        self.size
+    }
+
+    /// Fetch and decrypt `len` bytes starting at `position`.
+    ///
+    /// The returned range is cut short at the end of the data, and is empty if `position` is past the end.
+    /// This does not move the reading position of the stream.
+    pub async fn read_range(&self, position: u64, len: usize) -> Result<Bytes, GetError> {
+        if len == 0 || position >= self.size {
+            return Ok(Bytes::new());
+        }
+        let end = self.size.min(position.saturating_add(len as u64));
+        let first_chunk = chunk_index_at(&self.chunk_starts, position);
+        let last_chunk = chunk_index_at(&self.chunk_starts, end - 1);
+
+        let bytes = fetch_chunks(&self.client, &self.source, first_chunk..last_chunk + 1).await?;
+        let start = (position - self.chunk_starts[first_chunk]) as usize;
+        Ok(bytes.slice(start..start + (end - position) as usize))
+    }
+
+    /// Number of chunks the data is made of.
+    pub(crate) fn chunk_count(&self) -> usize {
+        self.chunk_starts.len()
+    }
+
+    /// Fetch and decrypt the chunks with the given indexes, returning their concatenated content.
+    pub(crate) async fn read_chunks(&self, indexes: Range<usize>) -> Result<Bytes, GetError> {
+        fetch_chunks(&self.client, &self.source, indexes).await
+    }
+}
+
+impl AsyncRead for DataStream {
+    fn poll_read(
+        mut self: Pin<&mut Self>,
+        cx: &mut Context<'_>,
+        buf: &mut ReadBuf<'_>,
+    ) -> Poll<std::io::Result<()>> {
+        if self.position >= self.size || buf.remaining() == 0 {
+            return Poll::Ready(Ok(()));
+        }
+        let index = chunk_index_at(&self.chunk_starts, self.position);
+
+        let has_chunk = matches!(&self.current_chunk, Some((current, _)) if *current == index);
+        if !has_chunk {
+            let mut future = match self.pending_chunk.take() {
+                Some((pending, future)) if pending == index => future,
+                _ => {
+                    let client = self.client.clone();
+                    let source = Arc::clone(&self.source);
+                    let future: ChunkFuture =
+                        Box::pin(
+                            async move { fetch_chunks(&client, &source, index..index + 1).await },
+                        );
+                    Mutex::new(future)
+                }
+            };
+
+            let poll = future
+                .get_mut()
+                .unwrap_or_else(|poisoned| poisoned.into_inner())
+                .as_mut()
+                .poll(cx);
+            match poll {
+                Poll::Ready(result) => {
+                    let chunk = result.map_err(std::io::Error::other)?;
+                    self.current_chunk = Some((index, chunk));
+                }
+                Poll::Pending => {
+                    self.pending_chunk = Some((index, future));
+                    return Poll::Pending;
+                }
+            }
+        }
+
+        let offset = (self.position - self.chunk_starts[index]) as usize;
+        let copied = match &self.current_chunk {
+            Some((_, chunk)) if offset < chunk.len() => {
+                let len = buf.remaining().min(chunk.len() - offset);
+                buf.put_slice(&chunk[offset..offset + len]);
+                len
+            }
+            _ => 0,
+        };
+        self.position += copied as u64;
+        Poll::Ready(Ok(()))
+    }
+}
+
+impl AsyncSeek for DataStream {
+    fn start_seek(mut self: Pin<&mut Self>, position: SeekFrom) -> std::io::Result<()> {
+        let new_position = match position {
+            SeekFrom::Start(offset) => Some(offset),
+            SeekFrom::End(offset) => self.size.checked_add_signed(offset),
+            SeekFrom::Current(offset) => self.position.checked_add_signed(offset),
+        };
+        match new_position {
+            Some(new_position) => {
+                self.position = new_position;
+                Ok(())
+            }
+            None => Err(std::io::Error::new(
+                std::io::ErrorKind::InvalidInput,
+                "invalid seek to a negative or overflowing position",
+            )),
+        }
+    }
+
+    fn poll_complete(self: Pin<&mut Self>, _cx: &mut Context<'_>) -> Poll<std::io::Result<u64>> {
+        Poll::Ready(Ok(self.position))
+    }
+}
+
+impl Client {
+    /// Open a [`DataStream`] over (private) data on the network.
+    ///
+    /// Only the data map is fetched here, the data itself is fetched as it is read.
+    pub async fn data_stream(&self, data_map: &DataMapChunk) -> Result<DataStream, GetError> {
+        info!(
+            "Opening a stream over private data from Data Map {:?}",
+            data_map.0.address()
+        );
+        let data_map = self.resolve_data_map(data_map.0.value()).await?;
+        Ok(DataStream::new(self.clone(), data_map))
+    }
+
+    /// Open a [`DataStream`] over public data on the network.
+    ///
+    /// Only the data map is fetched here, the data itself is fetched as it is read.
+    pub async fn data_stream_public(&self, addr: &DataAddress) -> Result<DataStream, GetError> {
+        info!("Opening a stream over data from Data Address: {addr:?}");
+        let data_map_chunk = self.chunk_get(&ChunkAddress::new(*addr.xorname())).await?;
+        let data_map = self.resolve_data_map(data_map_chunk.value()).await?;
+        Ok(DataStream::new(self.clone(), data_map))
+    }
+}
+
+async fn fetch_chunks(
+    client: &Client,
+    source: &Source,
+    indexes: Range<usize>,
+) -> Result<Bytes, GetError> {
+    let data_map = match source {
+        Source::Chunks(data_map) => data_map,
+        // inline data is a single chunk
+        Source::Inline(data) if indexes.contains(&0) => return Ok(data.clone()),
+        Source::Inline(_) => return Ok(Bytes::new()),
+    };
+    let infos = data_map.infos();
+    let infos = infos.get(indexes.clone()).unwrap_or_default().to_vec();
+    debug!("Fetching chunks {indexes:?} of data map {data_map:?}");
+    let encrypted_chunks = client.fetch_encrypted_chunks(infos).await?;
+    decrypt_chunks(data_map, &encrypted_chunks)
+}
+
+// Decrypt a contiguous subset of the chunks of a data map.
+fn decrypt_chunks(data_map: &DataMap, chunks: &[EncryptedChunk]) -> Result<Bytes, GetError> {
+    self_encryption::decrypt_range(data_map, chunks, 0, usize::MAX).map_err(|err| {
+        error!("Error decrypting encrypted chunks: {err:?}");
+        GetError::Decryption(crate::self_encryption::Error::SelfEncryption(err))
+    })
+}
+
+// Position of the first byte of each chunk in the original data, by chunk index.
+fn chunk_starts(data_map: &DataMap) -> Vec<u64> {
+    data_map
+        .infos()
+        .iter()
+        .scan(0u64, |start, info| {
+            let chunk_start = *start;
+            *start += info.src_size as u64;
+            Some(chunk_start)
+        })
+        .collect()
+}
+
+// Index of the chunk holding the byte at the given position.
+fn chunk_index_at(chunk_starts: &[u64], position: u64) -> usize {
+    chunk_starts
+        .partition_point(|start| *start <= position)
+        .saturating_sub(1)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use self_encryption::MAX_CHUNK_SIZE;
+
+    #[test]
+    fn decrypt_chunk_ranges() {
+        let data: Vec<u8> = (0..(4 * *MAX_CHUNK_SIZE + 321))
+            .map(|i| (i % 253) as u8)
+            .collect();
+        let (data_map, mut encrypted_chunks) =
+            self_encryption::encrypt(Bytes::from(data.clone())).unwrap();
+        encrypted_chunks.sort_by_key(|chunk| chunk.index);
+
+        let starts = chunk_starts(&data_map);
+        assert_eq!(starts.len(), encrypted_chunks.len());
+        assert_eq!(starts[0], 0);
+        assert_eq!(chunk_index_at(&starts, 0), 0);
+        assert_eq!(chunk_index_at(&starts, starts[1] - 1), 0);
+        assert_eq!(chunk_index_at(&starts, starts[1]), 1);
+        assert_eq!(
+            chunk_index_at(&starts, data.len() as u64 - 1),
+            starts.len() - 1
+        );
+
+        for first in 0..encrypted_chunks.len() {
+            for last in first..encrypted_chunks.len() {
+                let decrypted =
+                    decrypt_chunks(&data_map, &encrypted_chunks[first..last + 1]).unwrap();
+                let start = starts[first] as usize;
+                let end = starts
+                    .get(last + 1)
+                    .map(|end| *end as usize)
+                    .unwrap_or(data.len());
+                assert_eq!(&decrypted[..], &data[start..end]);
+            }
+        }
+    }
+}
@@ -24,7 +24,8 @@ use crate::{
 use bytes::Bytes;
 use serde::{Deserialize, Serialize};
 
-use super::Metadata;
+use super::diff::{merge_archives, ArchiveDiff, ConflictPolicy, MergeConflict};
+use super::{ArchiveEntries, ContentInfo, Metadata, NodeType, UnixAttributes};
 
 /// Private archive data map, allowing access to the [`PrivateArchive`] data.
 pub type PrivateArchiveDataMap = DataMapChunk;
@@ -40,23 +41,35 @@ pub struct PrivateArchive {
     ///           |         |             |
     ///           V         V             V
     map: BTreeMap<PathBuf, (DataMapChunk, Metadata)>,
+    /// Directories and symlinks, which have no content on the network.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    nodes: BTreeMap<PathBuf, (NodeType, Metadata)>,
+    /// POSIX attributes of the entries of the archive, by path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    unix_attributes: BTreeMap<PathBuf, UnixAttributes>,
+    /// Hash and content type of the files of the archive, by path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    content_info: BTreeMap<PathBuf, ContentInfo>,
 }
 
 /// This type essentially wraps archive in version marker. E.g. in JSON format:
 /// `{ "V0": { "map": <xxx> } }`
+///
+/// Directories, POSIX attributes and the hash and content type of files are optional fields that older clients
+/// ignore, archives holding them are still written as `V0`. Only archives holding symlinks are written as `V1`,
+/// which older clients refuse rather than silently leaving the symlinks out.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum PrivateArchiveVersioned {
     V0(PrivateArchive),
+    V1(PrivateArchive),
 }
 
 impl PrivateArchive {
     /// Create a new emtpy local archive
     /// Note that this does not upload the archive to the network
     pub fn new() -> Self {
-        Self {
-            map: BTreeMap::new(),
-        }
+        Self::default()
     }
 
     /// Rename a file in an archive
@@ -71,11 +84,29 @@ impl PrivateArchive {
             .unwrap_or(Duration::from_secs(0))
             .as_secs();
         meta.modified = now;
+        if let Some(attributes) = self.unix_attributes.remove(old_path) {
+            self.unix_attributes
+                .insert(new_path.to_path_buf(), attributes);
+        }
+        if let Some(content_info) = self.content_info.remove(old_path) {
+            self.content_info
+                .insert(new_path.to_path_buf(), content_info);
+        }
         self.map.insert(new_path.to_path_buf(), (data_addr, meta));
         debug!("Renamed file successfully in the private archive, old path: {old_path:?} new_path: {new_path:?}");
         Ok(())
     }
 
+    /// Remove a file from an archive, returning its entry if it was in the archive.
+    /// Note that this does not upload the archive to the network.
+    pub fn remove_file(&mut self, path: &Path) -> Option<(DataMapChunk, Metadata)> {
+        let removed = self.map.remove(path)?;
+        self.unix_attributes.remove(path);
+        self.content_info.remove(path);
+        debug!("Removed file from the private archive, path: {path:?}");
+        Some(removed)
+    }
+
     /// Add a file to a local archive. Note that this does not upload the archive to the network.
     pub fn add_file(&mut self, path: PathBuf, data_map: DataMapChunk, meta: Metadata) {
         self.map.insert(path.clone(), (data_map, meta));
@@ -115,15 +146,27 @@ impl PrivateArchive {
     /// Deserialize from bytes.
     pub fn from_bytes(data: Bytes) -> Result<PrivateArchive, rmp_serde::decode::Error> {
         let root: PrivateArchiveVersioned = rmp_serde::from_slice(&data[..])?;
-        // Currently we have only `V0`. If we add `V1`, then we need an upgrade/migration path here.
-        let PrivateArchiveVersioned::V0(root) = root;
+        // `V1` only adds optional fields to `V0`, both decode to the same structure.
+        let root = match root {
+            PrivateArchiveVersioned::V0(root) | PrivateArchiveVersioned::V1(root) => root,
+        };
 
         Ok(root)
     }
 
     /// Serialize to bytes.
     pub fn to_bytes(&self) -> Result<Bytes, rmp_serde::encode::Error> {
-        let versioned = PrivateArchiveVersioned::V0(self.clone());
+        // Older clients decoding a `V0` archive ignore the fields they don't know about,
+        // only symlinks would silently be lost.
+        let has_symlinks = self
+            .nodes
+            .values()
+            .any(|(node_type, _)| matches!(node_type, NodeType::Symlink(_)));
+        let versioned = if has_symlinks {
+            PrivateArchiveVersioned::V1(self.clone())
+        } else {
+            PrivateArchiveVersioned::V0(self.clone())
+        };
         let root_serialized = rmp_serde::to_vec_named(&versioned)?;
         let root_serialized = Bytes::from(root_serialized);
 
@@ -135,6 +178,117 @@ impl PrivateArchive {
     /// Note that if there are duplicate entries for the same filename, the files from the other archive will be the ones that are kept.
     pub fn merge(&mut self, other: &PrivateArchive) {
         self.map.extend(other.map.clone());
+        self.nodes.extend(other.nodes.clone());
+        self.unix_attributes.extend(other.unix_attributes.clone());
+        self.content_info.extend(other.content_info.clone());
+    }
+
+    /// Compare the files of this archive with those of a `new` archive.
+    pub fn diff(&self, new: &PrivateArchive) -> ArchiveDiff {
+        ArchiveDiff::between(&self.map, &new.map)
+    }
+
+    /// Merge with another archive, resolving the files whose content differs in both archives with the given policy.
+    ///
+    /// Unlike [`PrivateArchive::merge`], the conflicts and how they were resolved are returned.
+    /// Directories and symlinks of the other archive are only added where this archive has no entry.
+    pub fn merge_with_policy(
+        &mut self,
+        other: &PrivateArchive,
+        policy: ConflictPolicy,
+    ) -> Vec<MergeConflict> {
+        merge_archives(self, other, policy)
+    }
+
+    /// Add a directory to a local archive, e.g. to preserve an empty directory.
+    /// Note that this does not upload the archive to the network
+    pub fn add_directory(&mut self, path: PathBuf, meta: Metadata) {
+        self.nodes.insert(path, (NodeType::Directory, meta));
+    }
+
+    /// Add a symbolic link pointing to `target` to a local archive.
+    /// Note that this does not upload the archive to the network
+    pub fn add_symlink(&mut self, path: PathBuf, target: PathBuf, meta: Metadata) {
+        self.nodes.insert(path, (NodeType::Symlink(target), meta));
+    }
+
+    /// Set the POSIX attributes of an entry of the archive.
+    /// Note that this does not upload the archive to the network
+    pub fn set_unix_attributes(&mut self, path: PathBuf, attributes: UnixAttributes) {
+        self.unix_attributes.insert(path, attributes);
+    }
+
+    /// The POSIX attributes of an entry of the archive, if they were recorded.
+    pub fn unix_attributes(&self, path: &Path) -> Option<&UnixAttributes> {
+        self.unix_attributes.get(path)
+    }
+
+    /// Set the hash and content type of a file of the archive.
+    /// Note that this does not upload the archive to the network
+    pub fn set_content_info(&mut self, path: PathBuf, content_info: ContentInfo) {
+        self.content_info.insert(path, content_info);
+    }
+
+    /// The hash and content type of a file of the archive, if they were recorded.
+    pub fn content_info(&self, path: &Path) -> Option<&ContentInfo> {
+        self.content_info.get(path)
+    }
+
+    /// The type of an entry of the archive, if there is one at this path.
+    pub fn node_type(&self, path: &Path) -> Option<NodeType> {
+        if self.map.contains_key(path) {
+            return Some(NodeType::File);
+        }
+        self.nodes.get(path).map(|(node_type, _)| node_type.clone())
+    }
+
+    /// Iterate over the directories and symlinks of the archive.
+    ///
+    /// Returns an iterator over ([`PathBuf`], [`NodeType`], [`Metadata`])
+    pub fn nodes(&self) -> impl Iterator<Item = (&PathBuf, &NodeType, &Metadata)> {
+        self.nodes
+            .iter()
+            .map(|(path, (node_type, meta))| (path, node_type, meta))
+    }
+}
+
+impl ArchiveEntries for PrivateArchive {
+    type Content = DataMapChunk;
+
+    fn file_entries(&self) -> &BTreeMap<PathBuf, (DataMapChunk, Metadata)> {
+        &self.map
+    }
+
+    fn file_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, (DataMapChunk, Metadata)> {
+        &mut self.map
+    }
+
+    fn node_entries(&self) -> &BTreeMap<PathBuf, (NodeType, Metadata)> {
+        &self.nodes
+    }
+
+    fn node_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, (NodeType, Metadata)> {
+        &mut self.nodes
+    }
+
+    fn unix_attribute_entries(&self) -> &BTreeMap<PathBuf, UnixAttributes> {
+        &self.unix_attributes
+    }
+
+    fn unix_attribute_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, UnixAttributes> {
+        &mut self.unix_attributes
+    }
+
+    fn content_info_entries(&self) -> &BTreeMap<PathBuf, ContentInfo> {
+        &self.content_info
+    }
+
+    fn content_info_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, ContentInfo> {
+        &mut self.content_info
+    }
+
+    fn set_unix_attributes(&mut self, path: PathBuf, attributes: UnixAttributes) {
+        PrivateArchive::set_unix_attributes(self, path, attributes)
     }
 }
 
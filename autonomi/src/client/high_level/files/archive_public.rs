@@ -26,7 +26,8 @@ use crate::{
     Client,
 };
 
-use super::Metadata;
+use super::diff::{merge_archives, ArchiveDiff, ConflictPolicy, MergeConflict};
+use super::{ArchiveEntries, ContentInfo, Metadata, NodeType, UnixAttributes};
 
 /// The address of a public archive on the network. Points to an [`PublicArchive`].
 pub type ArchiveAddress = DataAddress;
@@ -41,23 +42,35 @@ pub struct PublicArchive {
     ///           |         |            |
     ///           V         V            V
     map: BTreeMap<PathBuf, (DataAddress, Metadata)>,
+    /// Directories and symlinks, which have no content on the network.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    nodes: BTreeMap<PathBuf, (NodeType, Metadata)>,
+    /// POSIX attributes of the entries of the archive, by path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    unix_attributes: BTreeMap<PathBuf, UnixAttributes>,
+    /// Hash and content type of the files of the archive, by path.
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    content_info: BTreeMap<PathBuf, ContentInfo>,
 }
 
 /// This type essentially wraps archive in version marker. E.g. in JSON format:
 /// `{ "V0": { "map": <xxx> } }`
+///
+/// Directories, POSIX attributes and the hash and content type of files are optional fields that older clients
+/// ignore, archives holding them are still written as `V0`. Only archives holding symlinks are written as `V1`,
+/// which older clients refuse rather than silently leaving the symlinks out.
 #[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
 #[non_exhaustive]
 pub enum PublicArchiveVersioned {
     V0(PublicArchive),
+    V1(PublicArchive),
 }
 
 impl PublicArchive {
     /// Create a new emtpy local archive
     /// Note that this does not upload the archive to the network
     pub fn new() -> Self {
-        Self {
-            map: BTreeMap::new(),
-        }
+        Self::default()
     }
 
     /// Rename a file in an archive.
@@ -72,11 +85,29 @@ impl PublicArchive {
             .unwrap_or(Duration::from_secs(0))
             .as_secs();
         meta.modified = now;
+        if let Some(attributes) = self.unix_attributes.remove(old_path) {
+            self.unix_attributes
+                .insert(new_path.to_path_buf(), attributes);
+        }
+        if let Some(content_info) = self.content_info.remove(old_path) {
+            self.content_info
+                .insert(new_path.to_path_buf(), content_info);
+        }
         self.map.insert(new_path.to_path_buf(), (data_addr, meta));
         debug!("Renamed file successfully in the archive, old path: {old_path:?} new_path: {new_path:?}");
         Ok(())
     }
 
+    /// Remove a file from an archive, returning its entry if it was in the archive.
+    /// Note that this does not upload the archive to the network
+    pub fn remove_file(&mut self, path: &Path) -> Option<(DataAddress, Metadata)> {
+        let removed = self.map.remove(path)?;
+        self.unix_attributes.remove(path);
+        self.content_info.remove(path);
+        debug!("Removed file from the archive, path: {path:?}");
+        Some(removed)
+    }
+
     /// Add a file to a local archive
     /// Note that this does not upload the archive to the network
     pub fn add_file(&mut self, path: PathBuf, data_addr: DataAddress, meta: Metadata) {
@@ -113,15 +144,27 @@ impl PublicArchive {
     /// Deserialize from bytes.
     pub fn from_bytes(data: Bytes) -> Result<PublicArchive, rmp_serde::decode::Error> {
         let root: PublicArchiveVersioned = rmp_serde::from_slice(&data[..])?;
-        // Currently we have only `V0`. If we add `V1`, then we need an upgrade/migration path here.
-        let PublicArchiveVersioned::V0(root) = root;
+        // `V1` only adds optional fields to `V0`, both decode to the same structure.
+        let root = match root {
+            PublicArchiveVersioned::V0(root) | PublicArchiveVersioned::V1(root) => root,
+        };
 
         Ok(root)
     }
 
     /// Serialize to bytes.
     pub fn to_bytes(&self) -> Result<Bytes, rmp_serde::encode::Error> {
-        let versioned = PublicArchiveVersioned::V0(self.clone());
+        // Older clients decoding a `V0` archive ignore the fields they don't know about,
+        // only symlinks would silently be lost.
+        let has_symlinks = self
+            .nodes
+            .values()
+            .any(|(node_type, _)| matches!(node_type, NodeType::Symlink(_)));
+        let versioned = if has_symlinks {
+            PublicArchiveVersioned::V1(self.clone())
+        } else {
+            PublicArchiveVersioned::V0(self.clone())
+        };
         let root_serialized = rmp_serde::to_vec_named(&versioned)?;
         let root_serialized = Bytes::from(root_serialized);
 
@@ -133,6 +176,119 @@ impl PublicArchive {
     /// Note that if there are duplicate entries for the same filename, the files from the other archive will be the ones that are kept.
     pub fn merge(&mut self, other: &PublicArchive) {
         self.map.extend(other.map.clone());
+        self.nodes.extend(other.nodes.clone());
+        self.unix_attributes.extend(other.unix_attributes.clone());
+        self.content_info.extend(other.content_info.clone());
+    }
+
+    /// Compare the files of this archive with those of a `new` archive.
+    pub fn diff(&self, new: &PublicArchive) -> ArchiveDiff {
+        ArchiveDiff::between(&self.map, &new.map)
+    }
+
+    /// Merge with another archive, resolving the files whose content differs in both archives with the given policy.
+    ///
+    /// Unlike [`PublicArchive::merge`], the conflicts and how they were resolved are returned.
+    /// Directories and symlinks of the other archive are only added where this archive has no entry.
+    pub fn merge_with_policy(
+        &mut self,
+        other: &PublicArchive,
+        policy: ConflictPolicy,
+    ) -> Vec<MergeConflict> {
+        merge_archives(self, other, policy)
+    }
+
+    /// Add a directory to a local archive, e.g. to preserve an empty directory.
+    /// Note that this does not upload the archive to the network
+    pub fn add_directory(&mut self, path: PathBuf, meta: Metadata) {
+        self.nodes.insert(path, (NodeType::Directory, meta));
+    }
+
+    /// Add a symbolic link pointing to `target` to a local archive.
+    /// Note that this does not upload the archive to the network
+    pub fn add_symlink(&mut self, path: PathBuf, target: PathBuf, meta: Metadata) {
+        self.nodes.insert(path, (NodeType::Symlink(target), meta));
+    }
+
+    /// Set the POSIX attributes of an entry of the archive.
+    /// The owner is not kept, public archives do not reveal the account IDs of the uploader.
+    /// Note that this does not upload the archive to the network
+    pub fn set_unix_attributes(&mut self, path: PathBuf, attributes: UnixAttributes) {
+        self.unix_attributes
+            .insert(path, attributes.without_owner());
+    }
+
+    /// The POSIX attributes of an entry of the archive, if they were recorded.
+    pub fn unix_attributes(&self, path: &Path) -> Option<&UnixAttributes> {
+        self.unix_attributes.get(path)
+    }
+
+    /// Set the hash and content type of a file of the archive.
+    /// Note that this does not upload the archive to the network
+    pub fn set_content_info(&mut self, path: PathBuf, content_info: ContentInfo) {
+        self.content_info.insert(path, content_info);
+    }
+
+    /// The hash and content type of a file of the archive, if they were recorded.
+    pub fn content_info(&self, path: &Path) -> Option<&ContentInfo> {
+        self.content_info.get(path)
+    }
+
+    /// The type of an entry of the archive, if there is one at this path.
+    pub fn node_type(&self, path: &Path) -> Option<NodeType> {
+        if self.map.contains_key(path) {
+            return Some(NodeType::File);
+        }
+        self.nodes.get(path).map(|(node_type, _)| node_type.clone())
+    }
+
+    /// Iterate over the directories and symlinks of the archive.
+    ///
+    /// Returns an iterator over ([`PathBuf`], [`NodeType`], [`Metadata`])
+    pub fn nodes(&self) -> impl Iterator<Item = (&PathBuf, &NodeType, &Metadata)> {
+        self.nodes
+            .iter()
+            .map(|(path, (node_type, meta))| (path, node_type, meta))
+    }
+}
+
+impl ArchiveEntries for PublicArchive {
+    type Content = DataAddress;
+
+    fn file_entries(&self) -> &BTreeMap<PathBuf, (DataAddress, Metadata)> {
+        &self.map
+    }
+
+    fn file_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, (DataAddress, Metadata)> {
+        &mut self.map
+    }
+
+    fn node_entries(&self) -> &BTreeMap<PathBuf, (NodeType, Metadata)> {
+        &self.nodes
+    }
+
+    fn node_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, (NodeType, Metadata)> {
+        &mut self.nodes
+    }
+
+    fn unix_attribute_entries(&self) -> &BTreeMap<PathBuf, UnixAttributes> {
+        &self.unix_attributes
+    }
+
+    fn unix_attribute_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, UnixAttributes> {
+        &mut self.unix_attributes
+    }
+
+    fn content_info_entries(&self) -> &BTreeMap<PathBuf, ContentInfo> {
+        &self.content_info
+    }
+
+    fn content_info_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, ContentInfo> {
+        &mut self.content_info
+    }
+
+    fn set_unix_attributes(&mut self, path: PathBuf, attributes: UnixAttributes) {
+        PublicArchive::set_unix_attributes(self, path, attributes)
     }
 }
 
@@ -218,6 +374,7 @@ mod test {
     use xor_name::XorName;
 
     use super::*;
+    use crate::client::files::ContentHash;
 
     #[test]
     fn compatibility() {
@@ -227,6 +384,7 @@ mod test {
         pub enum FuturePublicArchiveVersioned {
             V0(PublicArchive),
             V1(PublicArchive),
+            V2(PublicArchive),
             #[serde(other)]
             Unsupported,
         }
@@ -250,7 +408,7 @@ mod test {
         let _: FuturePublicArchiveVersioned = rmp_serde::from_slice(&arch_serialized[..]).unwrap();
 
         // Now we break forward compatibility by introducing a new version not supported by the old code.
-        let future_arch = FuturePublicArchiveVersioned::V1(arch.clone());
+        let future_arch = FuturePublicArchiveVersioned::V2(arch.clone());
         let future_arch_serialized = rmp_serde::to_vec_named(&future_arch).unwrap();
         // The old archive will not be able to decode this.
         assert!(PublicArchive::from_bytes(Bytes::from(future_arch_serialized)).is_err());
@@ -262,6 +420,115 @@ mod test {
             rmp_serde::from_slice(&versioned_arch_serialized[..]).unwrap();
     }
 
+    #[test]
+    fn directories_symlinks_and_attributes() {
+        let mut arch = PublicArchive::new();
+        arch.add_file(
+            PathBuf::from("dir/file"),
+            DataAddress::new(XorName::random(&mut rand::thread_rng())),
+            Metadata::new_with_size(1),
+        );
+        let bytes = arch.to_bytes().unwrap();
+        let versioned: PublicArchiveVersioned = rmp_serde::from_slice(&bytes).unwrap();
+        assert!(matches!(versioned, PublicArchiveVersioned::V0(_)));
+
+        let attributes = UnixAttributes {
+            mode: Some(0o750),
+            uid: Some(1000),
+            gid: None,
+        };
+        arch.add_directory(PathBuf::from("dir/empty"), Metadata::new_with_size(0));
+        arch.add_symlink(
+            PathBuf::from("dir/link"),
+            PathBuf::from("file"),
+            Metadata::new_with_size(0),
+        );
+        arch.set_unix_attributes(PathBuf::from("dir/file"), attributes);
+
+        let bytes = arch.to_bytes().unwrap();
+        let versioned: PublicArchiveVersioned = rmp_serde::from_slice(&bytes).unwrap();
+        assert!(matches!(versioned, PublicArchiveVersioned::V1(_)));
+
+        // without symlinks the archive is V0 again
+        let mut without_symlink = arch.clone();
+        without_symlink.nodes.remove(Path::new("dir/link"));
+        let versioned: PublicArchiveVersioned =
+            rmp_serde::from_slice(&without_symlink.to_bytes().unwrap()).unwrap();
+        assert!(matches!(versioned, PublicArchiveVersioned::V0(_)));
+
+        let decoded = PublicArchive::from_bytes(bytes).unwrap();
+        assert_eq!(
+            decoded.node_type(Path::new("dir/file")),
+            Some(NodeType::File)
+        );
+        assert_eq!(
+            decoded.node_type(Path::new("dir/empty")),
+            Some(NodeType::Directory)
+        );
+        assert_eq!(
+            decoded.node_type(Path::new("dir/link")),
+            Some(NodeType::Symlink(PathBuf::from("file")))
+        );
+        // the owner is not kept in public archives
+        assert_eq!(
+            decoded.unix_attributes(Path::new("dir/file")),
+            Some(&UnixAttributes {
+                mode: Some(0o750),
+                uid: None,
+                gid: None,
+            })
+        );
+        assert_eq!(decoded.nodes().count(), 2);
+    }
+
+    #[test]
+    fn content_info_keeps_v0() {
+        let mut arch = PublicArchive::new();
+        arch.add_file(
+            PathBuf::from("index.html"),
+            DataAddress::new(XorName::random(&mut rand::thread_rng())),
+            Metadata::new_with_size(4),
+        );
+        let content_info = ContentInfo {
+            hash: Some(ContentHash::sha256(b"<p/>")),
+            content_type: Some("text/html".to_string()),
+        };
+        arch.set_content_info(PathBuf::from("index.html"), content_info.clone());
+
+        let bytes = arch.to_bytes().unwrap();
+        let versioned: PublicArchiveVersioned = rmp_serde::from_slice(&bytes).unwrap();
+        assert!(matches!(versioned, PublicArchiveVersioned::V0(_)));
+
+        // older clients still read the files of the archive
+        #[derive(Deserialize)]
+        struct OldPublicArchive {
+            map: BTreeMap<PathBuf, (DataAddress, Metadata)>,
+        }
+        #[derive(Deserialize)]
+        enum OldPublicArchiveVersioned {
+            V0(OldPublicArchive),
+        }
+        let OldPublicArchiveVersioned::V0(old) = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(&old.map, arch.map());
+
+        let mut decoded = PublicArchive::from_bytes(bytes).unwrap();
+        assert_eq!(
+            decoded.content_info(Path::new("index.html")),
+            Some(&content_info)
+        );
+        decoded
+            .rename_file(Path::new("index.html"), Path::new("home.html"))
+            .unwrap();
+        assert_eq!(
+            decoded.content_info(Path::new("home.html")),
+            Some(&content_info)
+        );
+
+        assert!(decoded.remove_file(Path::new("home.html")).is_some());
+        assert_eq!(decoded.content_info(Path::new("home.html")), None);
+        assert!(decoded.remove_file(Path::new("home.html")).is_none());
+    }
+
     #[test]
     fn forward_compatibility() {
         // What we do here is we create a new `Metadata` and use that in the `Archive` structs.
@@ -0,0 +1,263 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::PathBuf;
+
+use super::{ArchiveEntries, Metadata};
+
+/// The changes to the files of an archive, from a base archive to a new one.
+///
+/// Files are compared by content: a file whose content did not change but whose metadata did is not modified.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct ArchiveDiff {
+    /// Files that are only in the new archive
+    pub added: Vec<PathBuf>,
+    /// Files that are only in the base archive
+    pub removed: Vec<PathBuf>,
+    /// Files that are in both archives, with a different content
+    pub modified: Vec<PathBuf>,
+    /// Files of the base archive found at another path in the new archive, with the same content, as (old, new)
+    pub renamed: Vec<(PathBuf, PathBuf)>,
+}
+
+impl ArchiveDiff {
+    /// Compare the files of two archives, `T` being how the content of a file is referred to.
+    pub(crate) fn between<T: PartialEq>(
+        base: &BTreeMap<PathBuf, (T, Metadata)>,
+        new: &BTreeMap<PathBuf, (T, Metadata)>,
+    ) -> Self {
+        let mut diff = Self::default();
+        let mut removed = vec![];
+        for (path, (content, _)) in base {
+            match new.get(path) {
+                Some((new_content, _)) if new_content == content => {}
+                Some(_) => diff.modified.push(path.clone()),
+                None => removed.push(path),
+            }
+        }
+        let mut added: Vec<_> = new
+            .keys()
+            .filter(|path| !base.contains_key(*path))
+            .collect();
+
+        // a removed file whose content was added elsewhere was renamed
+        for old_path in removed {
+            let (content, _) = &base[old_path];
+            match added.iter().position(|path| new[*path].0 == *content) {
+                Some(index) => {
+                    let new_path = added.remove(index);
+                    diff.renamed.push((old_path.clone(), new_path.clone()));
+                }
+                None => diff.removed.push(old_path.clone()),
+            }
+        }
+        diff.added = added.into_iter().cloned().collect();
+        diff
+    }
+
+    /// Returns true if the files of both archives are the same.
+    pub fn is_empty(&self) -> bool {
+        self.added.is_empty()
+            && self.removed.is_empty()
+            && self.modified.is_empty()
+            && self.renamed.is_empty()
+    }
+}
+
+impl fmt::Display for ArchiveDiff {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} added, {} removed, {} modified, {} renamed",
+            self.added.len(),
+            self.removed.len(),
+            self.modified.len(),
+            self.renamed.len()
+        )
+    }
+}
+
+/// How to resolve a conflict when merging archives, i.e. a file with a different content in both archives.
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq)]
+pub enum ConflictPolicy {
+    /// Keep the file that was modified last, according to its metadata, or ours when they were modified at the same time
+    #[default]
+    NewestModified,
+    /// Always keep our file
+    Ours,
+    /// Always keep their file
+    Theirs,
+}
+
+/// Which archive an entry was kept from when merging.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum MergeSide {
+    /// The archive merged into
+    Ours,
+    /// The archive merged from
+    Theirs,
+}
+
+/// A file with a different content in both merged archives, and how it was resolved.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct MergeConflict {
+    /// Path of the file in the archives
+    pub path: PathBuf,
+    /// The archive the kept file comes from
+    pub kept: MergeSide,
+}
+
+/// Merge the files of `theirs` into `ours`, resolving conflicts with the given policy.
+///
+/// Returns the conflicts and the paths of the files that were taken from `theirs`.
+pub(crate) fn merge_files<T: PartialEq + Clone>(
+    ours: &mut BTreeMap<PathBuf, (T, Metadata)>,
+    theirs: &BTreeMap<PathBuf, (T, Metadata)>,
+    policy: ConflictPolicy,
+) -> (Vec<MergeConflict>, Vec<PathBuf>) {
+    let mut conflicts = vec![];
+    let mut taken = vec![];
+    for (path, (their_content, their_meta)) in theirs {
+        let kept = match ours.get(path) {
+            None => MergeSide::Theirs,
+            Some((our_content, _)) if our_content == their_content => continue,
+            Some((_, our_meta)) => {
+                let kept = match policy {
+                    ConflictPolicy::Ours => MergeSide::Ours,
+                    ConflictPolicy::Theirs => MergeSide::Theirs,
+                    ConflictPolicy::NewestModified if their_meta.modified > our_meta.modified => {
+                        MergeSide::Theirs
+                    }
+                    ConflictPolicy::NewestModified => MergeSide::Ours,
+                };
+                conflicts.push(MergeConflict {
+                    path: path.clone(),
+                    kept,
+                });
+                kept
+            }
+        };
+        if kept == MergeSide::Theirs {
+            ours.insert(path.clone(), (their_content.clone(), their_meta.clone()));
+            taken.push(path.clone());
+        }
+    }
+    (conflicts, taken)
+}
+
+/// Merge the entries of `theirs` into `ours`, resolving the files whose content differs with the given policy.
+///
+/// The hash, content type and attributes of the files taken from `theirs` come along with them.
+/// Directories and symlinks of `theirs` are only added where `ours` has no entry.
+pub(crate) fn merge_archives<A: ArchiveEntries>(
+    ours: &mut A,
+    theirs: &A,
+    policy: ConflictPolicy,
+) -> Vec<MergeConflict> {
+    let (conflicts, taken) = merge_files(ours.file_entries_mut(), theirs.file_entries(), policy);
+    for path in taken {
+        match theirs.content_info_entries().get(&path) {
+            Some(content_info) => ours
+                .content_info_entries_mut()
+                .insert(path.clone(), content_info.clone()),
+            None => ours.content_info_entries_mut().remove(&path),
+        };
+        match theirs.unix_attribute_entries().get(&path) {
+            Some(attributes) => ours.unix_attribute_entries_mut().insert(path, *attributes),
+            None => ours.unix_attribute_entries_mut().remove(&path),
+        };
+    }
+    for (path, node) in theirs.node_entries() {
+        if ours.file_entries().contains_key(path) || ours.node_entries().contains_key(path) {
+            continue;
+        }
+        ours.node_entries_mut().insert(path.clone(), node.clone());
+        if let Some(attributes) = theirs.unix_attribute_entries().get(path) {
+            ours.unix_attribute_entries_mut()
+                .insert(path.clone(), *attributes);
+        }
+    }
+    conflicts
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn entries(files: &[(&str, u8, u64)]) -> BTreeMap<PathBuf, (u8, Metadata)> {
+        files
+            .iter()
+            .map(|(path, content, modified)| {
+                let mut meta = Metadata::new_with_size(1);
+                meta.modified = *modified;
+                (PathBuf::from(path), (*content, meta))
+            })
+            .collect()
+    }
+
+    #[test]
+    fn diff_archives() {
+        let base = entries(&[
+            ("same", 1, 0),
+            ("changed", 2, 0),
+            ("gone", 3, 0),
+            ("old", 4, 0),
+        ]);
+        let new = entries(&[
+            ("same", 1, 5),
+            ("changed", 9, 0),
+            ("new", 4, 0),
+            ("extra", 7, 0),
+        ]);
+
+        let diff = ArchiveDiff::between(&base, &new);
+        assert_eq!(diff.added, [PathBuf::from("extra")]);
+        assert_eq!(diff.removed, [PathBuf::from("gone")]);
+        assert_eq!(diff.modified, [PathBuf::from("changed")]);
+        assert_eq!(diff.renamed, [(PathBuf::from("old"), PathBuf::from("new"))]);
+        assert!(ArchiveDiff::between(&base, &base).is_empty());
+    }
+
+    #[test]
+    fn merge_with_policies() {
+        let ours = entries(&[("a", 1, 10), ("b", 2, 10), ("c", 3, 0)]);
+        let theirs = entries(&[("a", 5, 20), ("b", 6, 5), ("c", 3, 0), ("d", 4, 0)]);
+
+        let mut merged = ours.clone();
+        let (conflicts, taken) = merge_files(&mut merged, &theirs, ConflictPolicy::NewestModified);
+        assert_eq!(
+            conflicts,
+            [
+                MergeConflict {
+                    path: PathBuf::from("a"),
+                    kept: MergeSide::Theirs
+                },
+                MergeConflict {
+                    path: PathBuf::from("b"),
+                    kept: MergeSide::Ours
+                },
+            ]
+        );
+        assert_eq!(taken, [PathBuf::from("a"), PathBuf::from("d")]);
+        assert_eq!(merged[&PathBuf::from("a")].0, 5);
+        assert_eq!(merged[&PathBuf::from("b")].0, 2);
+
+        let mut merged = ours.clone();
+        let (conflicts, _) = merge_files(&mut merged, &theirs, ConflictPolicy::Ours);
+        assert!(conflicts.iter().all(|c| c.kept == MergeSide::Ours));
+        assert_eq!(merged[&PathBuf::from("a")].0, 1);
+        assert_eq!(merged.len(), 4);
+
+        let mut merged = ours;
+        let (conflicts, _) = merge_files(&mut merged, &theirs, ConflictPolicy::Theirs);
+        assert_eq!(conflicts.len(), 2);
+        assert_eq!(merged[&PathBuf::from("b")].0, 6);
+    }
+}
@@ -0,0 +1,245 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use thiserror::Error;
+
+use super::archive_public::ArchiveAddress;
+use super::{PrivateArchive, PublicArchive};
+use crate::client::high_level::register::{
+    ForkResolution, RegisterAddress, RegisterDataVisibility, RegisterError, SecretKey,
+};
+use crate::client::payment::PaymentOption;
+use crate::client::Client;
+use crate::AttoTokens;
+
+/// A folder is a mutable archive: a register whose entries refer to the successive versions of an archive.
+///
+/// The address of a folder never changes, it can be shared once and always resolves to the latest version.
+/// Each version is uploaded like a regular archive, so all the previous versions stay available.
+pub type FolderAddress = RegisterAddress;
+
+/// The archive of a version of a folder, which is public or private.
+#[derive(Debug, Clone)]
+pub enum FolderArchive {
+    /// A public archive, along with its address
+    Public(ArchiveAddress, PublicArchive),
+    Private(PrivateArchive),
+}
+
+/// Errors that can occur when dealing with folders.
+#[derive(Error, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum FolderError {
+    #[error("Underlying register error: {0}")]
+    Register(#[from] RegisterError),
+    #[error("Failed to serialize the archive: {0}")]
+    Serialization(#[from] rmp_serde::encode::Error),
+    #[error("Failed to deserialize the archive: {0}")]
+    Deserialization(#[from] rmp_serde::decode::Error),
+    #[error("The folder is private, it cannot be read as a public folder")]
+    NotPublic,
+}
+
+impl Client {
+    /// Create the key of a folder from a SecretKey and a name, see [`Client::register_key_from_name`].
+    pub fn folder_key_from_name(owner: &SecretKey, name: &str) -> SecretKey {
+        Self::register_key_from_name(owner, &format!("folder/{name}"))
+    }
+
+    /// Create a new public folder, with the given archive as first version.
+    pub async fn folder_create_public(
+        &self,
+        owner: &SecretKey,
+        archive: &PublicArchive,
+        payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, FolderAddress), FolderError> {
+        let bytes = archive.to_bytes()?;
+        let (cost, addr) = self
+            .register_create_data(owner, bytes, RegisterDataVisibility::Public, payment_option)
+            .await?;
+        debug!("Created public folder at {addr}");
+        Ok((cost, addr))
+    }
+
+    /// Publish a new version of a public folder.
+    pub async fn folder_publish_public(
+        &self,
+        owner: &SecretKey,
+        archive: &PublicArchive,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, FolderError> {
+        let bytes = archive.to_bytes()?;
+        let cost = self
+            .register_update_data(owner, bytes, RegisterDataVisibility::Public, payment_option)
+            .await?;
+        debug!(
+            "Published a new version of public folder {}",
+            RegisterAddress::new(owner.public_key())
+        );
+        Ok(cost)
+    }
+
+    /// Get the latest version of a public folder, along with its archive address.
+    ///
+    /// If the folder was published concurrently, the version published last wins.
+    pub async fn folder_get_public(
+        &self,
+        addr: &FolderAddress,
+    ) -> Result<(ArchiveAddress, PublicArchive), FolderError> {
+        let value = self
+            .register_get_with_resolution(addr, &ForkResolution::LastWriterWins)
+            .await?;
+        let archive_addr = self
+            .register_data_address(&value)
+            .await?
+            .ok_or(FolderError::NotPublic)?;
+        let bytes = self.register_resolve_data(&value, None).await?;
+        Ok((archive_addr, PublicArchive::from_bytes(bytes)?))
+    }
+
+    /// Get the archive addresses of all the versions of a public folder, from the first to the latest.
+    pub async fn folder_history_public(
+        &self,
+        addr: &FolderAddress,
+    ) -> Result<Vec<ArchiveAddress>, FolderError> {
+        let mut history = self.register_history(addr);
+        let mut versions = vec![];
+        while let Some(value) = history.next().await? {
+            let archive_addr = self
+                .register_data_address(&value)
+                .await?
+                .ok_or(FolderError::NotPublic)?;
+            versions.push(archive_addr);
+        }
+        Ok(versions)
+    }
+
+    /// Create a new private folder, with the given archive as first version.
+    ///
+    /// The folder can only be read with its data key, see [`Client::register_data_key`].
+    pub async fn folder_create(
+        &self,
+        owner: &SecretKey,
+        archive: &PrivateArchive,
+        payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, FolderAddress), FolderError> {
+        let bytes = archive.to_bytes()?;
+        let (cost, addr) = self
+            .register_create_data(
+                owner,
+                bytes,
+                RegisterDataVisibility::Private,
+                payment_option,
+            )
+            .await?;
+        debug!("Created private folder at {addr}");
+        Ok((cost, addr))
+    }
+
+    /// Publish a new version of a private folder.
+    pub async fn folder_publish(
+        &self,
+        owner: &SecretKey,
+        archive: &PrivateArchive,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, FolderError> {
+        let bytes = archive.to_bytes()?;
+        let cost = self
+            .register_update_data(
+                owner,
+                bytes,
+                RegisterDataVisibility::Private,
+                payment_option,
+            )
+            .await?;
+        debug!(
+            "Published a new version of private folder {}",
+            RegisterAddress::new(owner.public_key())
+        );
+        Ok(cost)
+    }
+
+    /// Get the latest version of a private folder with its data key.
+    ///
+    /// If the folder was published concurrently, the version published last wins.
+    pub async fn folder_get(
+        &self,
+        addr: &FolderAddress,
+        data_key: &SecretKey,
+    ) -> Result<PrivateArchive, FolderError> {
+        let value = self
+            .register_get_with_resolution(addr, &ForkResolution::LastWriterWins)
+            .await?;
+        let bytes = self.register_resolve_data(&value, Some(data_key)).await?;
+        Ok(PrivateArchive::from_bytes(bytes)?)
+    }
+
+    /// Get all the versions of a private folder, from the first to the latest.
+    pub async fn folder_history(
+        &self,
+        addr: &FolderAddress,
+        data_key: &SecretKey,
+    ) -> Result<Vec<PrivateArchive>, FolderError> {
+        let versions = self
+            .register_history(addr)
+            .with_data_key(data_key.clone())
+            .collect_data()
+            .await?;
+        let mut archives = vec![];
+        for (_, bytes) in versions {
+            archives.push(PrivateArchive::from_bytes(bytes)?);
+        }
+        Ok(archives)
+    }
+
+    /// Get the latest version of a folder, whether it is public or private, fetching it only once.
+    ///
+    /// The data key is only needed if the folder is private, see [`Client::register_data_key`].
+    /// If the folder was published concurrently, the version published last wins.
+    pub async fn folder_get_latest(
+        &self,
+        addr: &FolderAddress,
+        data_key: Option<&SecretKey>,
+    ) -> Result<FolderArchive, FolderError> {
+        let value = self
+            .register_get_with_resolution(addr, &ForkResolution::LastWriterWins)
+            .await?;
+        let archive = match self
+            .register_resolve_data_and_address(&value, data_key)
+            .await?
+        {
+            (Some(archive_addr), bytes) => {
+                FolderArchive::Public(archive_addr, PublicArchive::from_bytes(bytes)?)
+            }
+            (None, bytes) => FolderArchive::Private(PrivateArchive::from_bytes(bytes)?),
+        };
+        Ok(archive)
+    }
+
+    /// Returns true if the latest version of the folder is public.
+    pub async fn folder_is_public(&self, addr: &FolderAddress) -> Result<bool, FolderError> {
+        let value = self
+            .register_get_with_resolution(addr, &ForkResolution::LastWriterWins)
+            .await?;
+        Ok(self.register_data_address(&value).await?.is_some())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn folder_keys_do_not_clash_with_registers() {
+        let owner = SecretKey::random();
+        let folder_key = Client::folder_key_from_name(&owner, "photos");
+        assert_eq!(folder_key, Client::folder_key_from_name(&owner, "photos"));
+        assert_ne!(folder_key, Client::register_key_from_name(&owner, "photos"));
+        assert_ne!(folder_key, Client::folder_key_from_name(&owner, "videos"));
+    }
+}
@@ -15,46 +15,102 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use super::archive_private::{PrivateArchive, PrivateArchiveDataMap};
+use super::fs_public::metadata_from_entry;
+use super::fs_shared::{
+    add_node_from_entry, archive_nodes, check_paid_archive, content_info, file_content_addrs,
+    file_content_type, is_unchanged, restore_nodes, single_file_report, unix_attributes_from_entry,
+    write_stream_to_file, WalkedEntry,
+};
+use super::journal::UploadJournal;
+use super::payment_batch::PaymentBatcher;
+use super::report::{FileUploadOutcome, SkipReason, UploadReport};
 use super::{get_relative_file_path_from_abs_file_and_folder_path, FILE_UPLOAD_BATCH_SIZE};
-use super::{DownloadError, UploadError};
+use super::{ContentHash, DownloadError, UploadError};
 
-use crate::client::payment::PaymentOption;
-use crate::client::PutError;
-use crate::client::{data_types::chunk::DataMapChunk, utils::process_tasks_with_max_concurrency};
+use crate::client::payment::{PaymentOption, Receipt};
+use crate::client::{
+    data_types::chunk::DataMapChunk, utils::process_tasks_with_max_concurrency, PutError,
+};
 use crate::self_encryption::encrypt;
-use crate::{AttoTokens, Client};
-use ant_protocol::storage::{Chunk, DataTypes};
-use bytes::Bytes;
+use crate::{Amount, AttoTokens, Client, Wallet as EvmWallet};
 use std::path::PathBuf;
-use xor_name::XorName;
 
 impl Client {
     /// Download a private file from network to local file system
+    ///
+    /// The file is written as its chunks are fetched, it never has to fit in memory.
     pub async fn file_download(
         &self,
         data_access: &DataMapChunk,
         to_dest: PathBuf,
     ) -> Result<(), DownloadError> {
-        let data = self.data_get(data_access).await?;
-        if let Some(parent) = to_dest.parent() {
-            tokio::fs::create_dir_all(parent).await?;
-            debug!("Created parent directories for {to_dest:?}");
-        }
-        tokio::fs::write(to_dest.clone(), data).await?;
+        let stream = self.data_stream(data_access).await?;
+        write_stream_to_file(&stream, &to_dest, None).await?;
         debug!("Downloaded file to {to_dest:?}");
         Ok(())
     }
 
+    /// Same as [`Client::file_download`], but checks the downloaded content against the expected hash.
+    ///
+    /// On mismatch the file is removed and [`DownloadError::HashMismatch`] is returned.
+    pub async fn file_download_verified(
+        &self,
+        data_access: &DataMapChunk,
+        to_dest: PathBuf,
+        expected_hash: &ContentHash,
+    ) -> Result<(), DownloadError> {
+        let stream = self.data_stream(data_access).await?;
+        write_stream_to_file(&stream, &to_dest, Some(expected_hash)).await?;
+        debug!("Downloaded and verified file to {to_dest:?}");
+        Ok(())
+    }
+
     /// Download a private directory from network to local file system
+    ///
+    /// Files whose hash was recorded in the archive are checked against it, see [`DownloadError::HashMismatch`].
+    /// The recorded permissions are applied, but not the owner, see [`Client::dir_download_with_owner`].
     pub async fn dir_download(
         &self,
         archive_access: &PrivateArchiveDataMap,
         to_dest: PathBuf,
+    ) -> Result<(), DownloadError> {
+        self.dir_download_restoring(archive_access, to_dest, false)
+            .await
+    }
+
+    /// Same as [`Client::dir_download`], but also changes the owner of the downloaded entries to the recorded one.
+    ///
+    /// This usually needs elevated privileges, failing to change the owner is only logged.
+    pub async fn dir_download_with_owner(
+        &self,
+        archive_access: &PrivateArchiveDataMap,
+        to_dest: PathBuf,
+    ) -> Result<(), DownloadError> {
+        self.dir_download_restoring(archive_access, to_dest, true)
+            .await
+    }
+
+    async fn dir_download_restoring(
+        &self,
+        archive_access: &PrivateArchiveDataMap,
+        to_dest: PathBuf,
+        restore_owner: bool,
     ) -> Result<(), DownloadError> {
         let archive = self.archive_get(archive_access).await?;
         for (path, addr, _meta) in archive.iter() {
-            self.file_download(addr, to_dest.join(path)).await?;
+            match archive
+                .content_info(path)
+                .and_then(|info| info.hash.as_ref())
+            {
+                Some(hash) => {
+                    self.file_download_verified(addr, to_dest.join(path), hash)
+                        .await?
+                }
+                None => self.file_download(addr, to_dest.join(path)).await?,
+            }
         }
+        let (nodes, attributes) = archive_nodes(&archive);
+        restore_nodes(&to_dest, nodes, attributes, restore_owner).await?;
         debug!("Downloaded directory to {to_dest:?}");
         Ok(())
     }
@@ -62,149 +118,139 @@ impl Client {
     /// Upload the content of all files in a directory to the network.
     /// The directory is recursively walked and each file is uploaded to the network.
     ///
-    /// The data maps of these (private) files are not uploaded but returned within the [`PrivateArchive`] return type.
+    /// Files are streamed: they are encrypted, paid for and uploaded in windows of chunks,
+    /// so they never need to fit in memory. Files too small to be self-encrypted are held in their data map.
+    ///
+    /// The data maps of these (private) files are not uploaded but returned within the [`PrivateArchive`] return type,
+    /// along with the [`UploadReport`] of the outcome of every file, listing the files that were skipped.
+    ///
+    /// If any file fails to be encrypted or uploaded, this returns [`UploadError::Incomplete`] with the report instead.
     pub async fn dir_content_upload(
         &self,
         dir_path: PathBuf,
         payment_option: PaymentOption,
-    ) -> Result<(AttoTokens, PrivateArchive), UploadError> {
+    ) -> Result<(AttoTokens, PrivateArchive, UploadReport), UploadError> {
+        let (cost, archive, report) = self
+            .dir_content_upload_with_journal(dir_path, payment_option, None, None)
+            .await?;
+        if report.has_failures() {
+            return Err(UploadError::Incomplete(Box::new(report)));
+        }
+        Ok((cost, archive, report))
+    }
+
+    async fn dir_content_upload_with_journal(
+        &self,
+        dir_path: PathBuf,
+        payment_option: PaymentOption,
+        journal: Option<&UploadJournal>,
+        previous: Option<&PrivateArchive>,
+    ) -> Result<(AttoTokens, PrivateArchive, UploadReport), UploadError> {
         info!("Uploading directory as private: {dir_path:?}");
         let start = tokio::time::Instant::now();
 
-        let mut encryption_tasks = vec![];
+        let payments = PaymentBatcher::new(self, payment_option);
+        let mut upload_tasks = vec![];
+        let mut private_archive = PrivateArchive::new();
+        let mut report = UploadReport::new();
 
         for entry in walkdir::WalkDir::new(&dir_path) {
             let entry = entry?;
 
-            if entry.file_type().is_dir() {
-                continue;
+            match WalkedEntry::of(&entry)? {
+                WalkedEntry::File => {}
+                WalkedEntry::Directory => {
+                    add_node_from_entry(&mut private_archive, &entry, &dir_path, None);
+                    continue;
+                }
+                WalkedEntry::Symlink(target) => {
+                    add_node_from_entry(&mut private_archive, &entry, &dir_path, Some(target));
+                    continue;
+                }
+                WalkedEntry::Unsupported => {
+                    let outcome = FileUploadOutcome::Skipped(SkipReason::NotAFile);
+                    report.insert(entry.path().to_path_buf(), outcome);
+                    continue;
+                }
+            }
+
+            // carry over the files that did not change since the previous archive
+            if let Some(previous) = previous {
+                let relative_path =
+                    get_relative_file_path_from_abs_file_and_folder_path(entry.path(), &dir_path);
+                let metadata = metadata_from_entry(&entry);
+                if let Some((data, previous_metadata)) = previous.map().get(&relative_path) {
+                    if is_unchanged(previous_metadata, &metadata) {
+                        debug!("Skipping unchanged file: {relative_path:?}");
+                        if let Some(attributes) = unix_attributes_from_entry(&entry) {
+                            private_archive.set_unix_attributes(relative_path.clone(), attributes);
+                        }
+                        if let Some(content_info) = previous.content_info(&relative_path) {
+                            private_archive
+                                .set_content_info(relative_path.clone(), content_info.clone());
+                        }
+                        private_archive.add_file(relative_path, data.clone(), metadata);
+                        report.insert(entry.path().to_path_buf(), FileUploadOutcome::Unchanged);
+                        continue;
+                    }
+                }
             }
 
             let dir_path = dir_path.clone();
+            let payments = &payments;
 
-            encryption_tasks.push(async move {
+            upload_tasks.push(async move {
                 let file_path = entry.path().to_path_buf();
+                let name = file_path.to_string_lossy().to_string();
 
-                info!("Encrypting file: {file_path:?}..");
+                info!("Uploading file: {name}..");
                 #[cfg(feature = "loud")]
-                println!("Encrypting file: {file_path:?}..");
-
-                let data = tokio::fs::read(&file_path)
+                println!("Uploading file: {name}..");
+
+                let content_type = match file_content_type(&file_path).await {
+                    Ok(content_type) => content_type,
+                    Err(err) => {
+                        return (
+                            file_path,
+                            Err(FileUploadOutcome::EncryptionFailed(err.into())),
+                        )
+                    }
+                };
+
+                let streamed = match self
+                    .stream_upload_file(&file_path, payments, false, journal)
                     .await
-                    .map_err(|err| format!("Could not read file {file_path:?}: {err:?}"))?;
-                let data = Bytes::from(data);
-
-                if data.len() < 3 {
-                    let err_msg =
-                        format!("Skipping file {file_path:?}, as it is smaller than 3 bytes");
-                    return Err(err_msg);
-                }
-
-                let now = ant_networking::time::Instant::now();
-
-                let (data_map_chunk, chunks) = encrypt(data).map_err(|err| err.to_string())?;
-
-                debug!("Encryption of {file_path:?} took: {:.2?}", now.elapsed());
-
-                let xor_names: Vec<_> = chunks
-                    .iter()
-                    .map(|chunk| (*chunk.name(), chunk.size()))
-                    .collect();
-
-                let metadata = super::fs_public::metadata_from_entry(&entry);
+                {
+                    Ok(streamed) => streamed,
+                    Err(err) => return (file_path, Err(err.into())),
+                };
+
+                info!(
+                    "Successfully uploaded {name} ({} chunks)",
+                    streamed.chunks_uploaded
+                );
+                #[cfg(feature = "loud")]
+                println!(
+                    "Successfully uploaded {name} ({} chunks)",
+                    streamed.chunks_uploaded
+                );
 
+                let metadata = metadata_from_entry(&entry);
+                let attributes = unix_attributes_from_entry(&entry);
                 let relative_path =
                     get_relative_file_path_from_abs_file_and_folder_path(&file_path, &dir_path);
+                let content_info = content_info(streamed.content_hash, content_type);
 
-                Ok((
-                    file_path.to_string_lossy().to_string(),
-                    xor_names,
-                    chunks,
-                    (relative_path, DataMapChunk::from(data_map_chunk), metadata),
-                ))
+                (
+                    file_path,
+                    Ok((relative_path, metadata, attributes, content_info, streamed)),
+                )
             });
         }
 
-        let mut combined_xor_names: Vec<(XorName, usize)> = vec![];
-        let mut combined_chunks: Vec<(String, Vec<Chunk>)> = vec![];
-        let mut private_archive = PrivateArchive::new();
-
-        let encryption_results =
-            process_tasks_with_max_concurrency(encryption_tasks, *FILE_UPLOAD_BATCH_SIZE).await;
-
-        for encryption_result in encryption_results {
-            match encryption_result {
-                Ok((file_path, xor_names, chunked_file, file_data)) => {
-                    info!("Successfully encrypted file: {file_path:?}");
-                    #[cfg(feature = "loud")]
-                    println!("Successfully encrypted file: {file_path:?}");
-
-                    combined_xor_names.extend(xor_names);
-                    combined_chunks.push((file_path, chunked_file));
-                    let (relative_path, data_map_chunk, file_metadata) = file_data;
-                    private_archive.add_file(relative_path, data_map_chunk, file_metadata);
-                }
-                Err(err_msg) => {
-                    error!("Error during file encryption: {err_msg}");
-                }
-            }
-        }
-
-        info!("Paying for {} chunks..", combined_xor_names.len());
-        #[cfg(feature = "loud")]
-        println!("Paying for {} chunks..", combined_xor_names.len());
-
-        let (receipt, skipped_payments_amount) = self
-            .pay_for_content_addrs(
-                DataTypes::Chunk,
-                combined_xor_names.into_iter(),
-                payment_option,
-            )
-            .await
-            .inspect_err(|err| error!("Error paying for data: {err:?}"))
-            .map_err(PutError::from)?;
-
-        info!("{skipped_payments_amount} chunks were free");
-
-        let files_to_upload_amount = combined_chunks.len();
-
-        let mut upload_tasks = vec![];
-
-        for (name, chunks) in combined_chunks {
-            let receipt_clone = receipt.clone();
-
-            upload_tasks.push(async move {
-                info!("Uploading file: {name} ({} chunks)..", chunks.len());
-                #[cfg(feature = "loud")]
-                println!("Uploading file: {name} ({} chunks)..", chunks.len());
-
-                // todo: handle failed uploads
-                let mut failed_uploads = self
-                    .upload_chunks_with_retries(chunks.iter().collect(), &receipt_clone)
-                    .await;
-
-                let chunks_uploaded = chunks.len() - failed_uploads.len();
-
-                // Return the last chunk upload error
-                if let Some(last_chunk_fail) = failed_uploads.pop() {
-                    error!(
-                        "Error uploading chunk ({:?}): {:?}",
-                        last_chunk_fail.0.address(),
-                        last_chunk_fail.1
-                    );
-
-                    (name, Err(UploadError::from(last_chunk_fail.1)))
-                } else {
-                    info!("Successfully uploaded {name} ({} chunks)", chunks.len());
-                    #[cfg(feature = "loud")]
-                    println!("Successfully uploaded {name} ({} chunks)", chunks.len());
+        let files_to_upload_amount = upload_tasks.len();
 
-                    (name, Ok(chunks_uploaded))
-                }
-            });
-        }
-
-        let uploads =
+        let results =
             process_tasks_with_max_concurrency(upload_tasks, *FILE_UPLOAD_BATCH_SIZE).await;
 
         info!(
@@ -220,22 +266,51 @@ impl Client {
             start.elapsed()
         );
 
+        let mut tokens_spent = Amount::ZERO;
+        let mut skipped_payments_amount = 0;
+
+        for (file_path, result) in results {
+            match result {
+                Ok((relative_path, metadata, attributes, content_info, streamed)) => {
+                    if let Some(content_info) = content_info {
+                        private_archive.set_content_info(relative_path.clone(), content_info);
+                    }
+                    if let Some(attributes) = attributes {
+                        private_archive.set_unix_attributes(relative_path.clone(), attributes);
+                    }
+                    private_archive.add_file(
+                        relative_path,
+                        DataMapChunk::from(streamed.data_map_chunk),
+                        metadata,
+                    );
+                    tokens_spent += streamed.tokens_spent;
+                    skipped_payments_amount += streamed.chunks_already_paid;
+                    let outcome = FileUploadOutcome::Uploaded {
+                        chunks: streamed.chunks_uploaded,
+                    };
+                    report.insert(file_path, outcome);
+                }
+                Err(outcome) => report.insert(file_path, outcome),
+            }
+        }
+
         let total_cost = self
-            .process_upload_results(uploads, receipt, skipped_payments_amount)
-            .await?;
+            .process_upload_results(&report, tokens_spent, skipped_payments_amount)
+            .await;
 
-        Ok((total_cost, private_archive))
+        Ok((total_cost, private_archive, report))
     }
 
     /// Same as [`Client::dir_content_upload`] but also uploads the archive (privately) to the network.
     ///
-    /// Returns the [`PrivateArchiveDataMap`] allowing the private archive to be downloaded from the network.
+    /// Returns the [`PrivateArchiveDataMap`] allowing the private archive to be downloaded from the network,
+    /// along with the [`UploadReport`] of the outcome of every file.
     pub async fn dir_upload(
         &self,
         dir_path: PathBuf,
         payment_option: PaymentOption,
-    ) -> Result<(AttoTokens, PrivateArchiveDataMap), UploadError> {
-        let (cost1, archive) = self
+    ) -> Result<(AttoTokens, PrivateArchiveDataMap, UploadReport), UploadError> {
+        let (cost1, archive, report) = self
             .dir_content_upload(dir_path, payment_option.clone())
             .await?;
         let (cost2, archive_addr) = self.archive_put(&archive, payment_option).await?;
@@ -243,11 +318,181 @@ impl Client {
             error!("Total cost overflowed: {cost1:?} + {cost2:?}");
             cost1
         });
-        Ok((total_cost, archive_addr))
+        Ok((total_cost, archive_addr, report))
+    }
+
+    /// Same as [`Client::dir_upload`] but records its progress in an [`UploadJournal`],
+    /// so that an interrupted upload can be resumed by calling this again with the same journal.
+    ///
+    /// Files that were completely uploaded are skipped, as are the chunks already stored,
+    /// and payments recorded in the journal are reused as long as their quotes have not expired.
+    /// The journal is left on disk, remove it with [`UploadJournal::remove`] once the upload succeeded.
+    ///
+    /// Returns the [`PrivateArchiveDataMap`] allowing the private archive to be downloaded from the network,
+    /// along with the [`UploadReport`] of the outcome of every file.
+    /// If any file failed, the archive is not uploaded and [`UploadError::Incomplete`] is returned instead.
+    pub async fn dir_upload_resumable(
+        &self,
+        dir_path: PathBuf,
+        payment_option: PaymentOption,
+        journal: &UploadJournal,
+    ) -> Result<(AttoTokens, PrivateArchiveDataMap, UploadReport), UploadError> {
+        if !journal.is_empty().await {
+            info!(
+                "Resuming upload of {dir_path:?} from the journal at {:?}",
+                journal.path()
+            );
+        }
+        let (cost1, archive, report) = self
+            .dir_content_upload_with_journal(dir_path, payment_option.clone(), Some(journal), None)
+            .await?;
+        if report.has_failures() {
+            return Err(UploadError::Incomplete(Box::new(report)));
+        }
+        let (cost2, archive_addr) = self.archive_put(&archive, payment_option).await?;
+        let total_cost = cost1.checked_add(cost2).unwrap_or_else(|| {
+            error!("Total cost overflowed: {cost1:?} + {cost2:?}");
+            cost1
+        });
+        Ok((total_cost, archive_addr, report))
+    }
+
+    /// Upload the changes made to a directory since it was uploaded as the `previous` archive.
+    ///
+    /// Files whose size and modification time match their entry in `previous` are neither read nor uploaded,
+    /// their [`DataMapChunk`] is carried over to the new archive. Other files are uploaded as with [`Client::dir_content_upload`],
+    /// and files that were removed from the directory are left out of the new archive.
+    /// Paths are compared as they are stored in archives, relative to the parent of `dir_path`,
+    /// so the directory must keep its name.
+    ///
+    /// This returns, but does not upload (!), the new [`PrivateArchive`], along with the [`UploadReport`] of the outcome
+    /// of every file, where the files that were carried over are [`FileUploadOutcome::Unchanged`].
+    /// If any file fails to be encrypted or uploaded, this returns [`UploadError::Incomplete`] instead.
+    pub async fn dir_sync(
+        &self,
+        dir_path: PathBuf,
+        previous: &PrivateArchive,
+        payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, PrivateArchive, UploadReport), UploadError> {
+        info!("Syncing directory: {dir_path:?}");
+        let (cost, archive, report) = self
+            .dir_content_upload_with_journal(dir_path, payment_option, None, Some(previous))
+            .await?;
+        if report.has_failures() {
+            return Err(UploadError::Incomplete(Box::new(report)));
+        }
+        Ok((cost, archive, report))
+    }
+
+    /// Upload a directory paid for with [`Client::dir_pay`], possibly on another machine, using the returned
+    /// `receipt` and `paid_archive`.
+    ///
+    /// The files are uploaded as with [`Client::dir_upload`], then the paid archive is uploaded as is.
+    /// If the files of the directory differ from those of the paid archive,
+    /// [`UploadError::PaidArchiveMismatch`] is returned and the archive is not uploaded.
+    /// If a `journal` is given, the upload can be resumed as with [`Client::dir_upload_resumable`].
+    ///
+    /// Returns the [`PrivateArchiveDataMap`] of the uploaded archive, along with the [`UploadReport`] of the outcome of every file.
+    pub async fn dir_upload_paid(
+        &self,
+        dir_path: PathBuf,
+        paid_archive: &PrivateArchive,
+        receipt: Receipt,
+        journal: Option<&UploadJournal>,
+    ) -> Result<(AttoTokens, PrivateArchiveDataMap, UploadReport), UploadError> {
+        let payment_option = PaymentOption::Receipt(receipt);
+        let (cost1, archive, report) = self
+            .dir_content_upload_with_journal(dir_path, payment_option.clone(), journal, None)
+            .await?;
+        if report.has_failures() {
+            return Err(UploadError::Incomplete(Box::new(report)));
+        }
+        check_paid_archive(paid_archive, &archive)?;
+        let (cost2, archive_addr) = self.archive_put(paid_archive, payment_option).await?;
+        let total_cost = cost1.checked_add(cost2).unwrap_or_else(|| {
+            error!("Total cost overflowed: {cost1:?} + {cost2:?}");
+            cost1
+        });
+        Ok((total_cost, archive_addr, report))
+    }
+
+    /// Pay for the upload of a directory with [`Client::dir_upload`], without uploading anything.
+    ///
+    /// The returned [`Receipt`] covers the files and the archive, it can be exported
+    /// with [`crate::client::payment::receipt_to_bytes`] and used to do the upload later, possibly on another machine,
+    /// with [`Client::dir_upload_paid`]. The directory must not change in between, and the upload must happen
+    /// before the quotes expire.
+    ///
+    /// The returned [`PrivateArchive`] is the one that was paid for: it records the local metadata of the files,
+    /// so it differs from one built on another machine. Export it with [`PrivateArchive::to_bytes`] along with the receipt.
+    ///
+    /// The addresses covered by the given `receipt` are not paid for again, and the returned receipt includes them.
+    /// Pass the partial receipt of a failed payment, see [`crate::client::payment::PayError::partial_receipt`],
+    /// to finish paying, or an empty receipt.
+    pub async fn dir_pay(
+        &self,
+        dir_path: PathBuf,
+        wallet: &EvmWallet,
+        receipt: Receipt,
+    ) -> Result<(AttoTokens, Receipt, PrivateArchive), UploadError> {
+        info!("Paying for the private upload of directory: {dir_path:?}");
+        let mut archive = PrivateArchive::new();
+        let mut content_addrs = vec![];
+
+        // mirrors the archive built by the upload, so that the archive chunks are the same
+        for entry in walkdir::WalkDir::new(&dir_path) {
+            let entry = entry?;
+            match WalkedEntry::of(&entry)? {
+                WalkedEntry::File => {}
+                WalkedEntry::Directory => {
+                    add_node_from_entry(&mut archive, &entry, &dir_path, None);
+                    continue;
+                }
+                WalkedEntry::Symlink(target) => {
+                    add_node_from_entry(&mut archive, &entry, &dir_path, Some(target));
+                    continue;
+                }
+                WalkedEntry::Unsupported => continue,
+            }
+
+            let file_path = entry.path().to_path_buf();
+
+            let (data_map_chunk, addrs, hash) =
+                file_content_addrs(&file_path, self.content_hashes).await?;
+            content_addrs.extend(addrs);
+
+            let relative_path =
+                get_relative_file_path_from_abs_file_and_folder_path(&file_path, &dir_path);
+            if let Some(attributes) = unix_attributes_from_entry(&entry) {
+                archive.set_unix_attributes(relative_path.clone(), attributes);
+            }
+            if let Some(content_info) = content_info(hash, file_content_type(&file_path).await?) {
+                archive.set_content_info(relative_path.clone(), content_info);
+            }
+            archive.add_file(
+                relative_path,
+                DataMapChunk::from(data_map_chunk),
+                metadata_from_entry(&entry),
+            );
+        }
+
+        // the data map of a private archive is not uploaded
+        let (_, archive_chunks) = encrypt(archive.to_bytes()?).map_err(PutError::from)?;
+        content_addrs.extend(
+            archive_chunks
+                .iter()
+                .map(|chunk| (*chunk.name(), chunk.size())),
+        );
+
+        let (cost, receipt) = self.pay_for_upload(content_addrs, wallet, receipt).await?;
+        Ok((cost, receipt, archive))
     }
 
     /// Upload the content of a private file to the network.
-    /// Reads file, splits into chunks, uploads chunks, uploads datamap, returns [`DataMapChunk`] (pointing to the datamap)
+    /// Streams the file through self-encryption, uploading its chunks,
+    /// and returns the [`DataMapChunk`] (pointing to the datamap) without uploading it.
+    ///
+    /// The file is never fully loaded in memory, see [`crate::files::STREAM_UPLOAD_WINDOW_SIZE`].
     pub async fn file_content_upload(
         &self,
         path: PathBuf,
@@ -257,9 +502,22 @@ impl Client {
         #[cfg(feature = "loud")]
         println!("Uploading file: {path:?}");
 
-        let data = tokio::fs::read(path).await?;
-        let data = Bytes::from(data);
-        let (total_cost, addr) = self.data_put(data, payment_option).await?;
+        let streamed = self
+            .stream_upload_file(
+                &path,
+                &PaymentBatcher::new(self, payment_option),
+                false,
+                None,
+            )
+            .await?;
+        let total_cost = self
+            .process_upload_results(
+                &single_file_report(&path, &streamed),
+                streamed.tokens_spent,
+                streamed.chunks_already_paid,
+            )
+            .await;
+        let addr = DataMapChunk::from(streamed.data_map_chunk);
         debug!("Uploaded file successfully in the privateAchive: {addr:?}");
         Ok((total_cost, addr))
     }
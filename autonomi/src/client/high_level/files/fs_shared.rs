@@ -1,49 +1,718 @@
+use super::fs_public::metadata_from_entry;
+use super::journal::{JournaledFile, UploadJournal};
+use super::payment_batch::PaymentBatcher;
+use super::report::{FileUploadOutcome, UploadReport};
+use super::{
+    get_relative_file_path_from_abs_file_and_folder_path, ArchiveEntries, ContentHash,
+    ContentHasher, ContentInfo, HashMismatch, Metadata, NodeType, UnixAttributes,
+    STREAM_UPLOAD_WINDOW_SIZE,
+};
+use crate::client::data_types::chunk::{ChunkAddress, DataMapChunk, CHUNK_DOWNLOAD_BATCH_SIZE};
+use crate::client::high_level::data::DataStream;
 use crate::client::payment::Receipt;
-use crate::client::{ClientEvent, UploadSummary};
-use crate::files::UploadError;
+use crate::client::{ClientEvent, PutError, UploadSummary};
+use crate::files::{DownloadError, UploadError};
+use crate::self_encryption::StreamingEncryptor;
 use crate::Client;
-use ant_evm::{Amount, AttoTokens};
+use ant_evm::{Amount, AttoTokens, EvmWallet};
+use ant_networking::time::{Duration, SystemTime};
+use ant_protocol::storage::{Chunk, DataTypes};
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
+use tokio::io::AsyncWriteExt;
+use xor_name::XorName;
+
+/// Number of bytes at the start of a file its content type is sniffed from
+const CONTENT_SNIFF_SIZE: u64 = 8 * 1024;
+
+/// Size and last modification time (as a UNIX timestamp) of a file.
+pub(crate) async fn file_size_and_modified(path: &Path) -> Result<(u64, u64), UploadError> {
+    let fs_metadata = tokio::fs::metadata(path).await?;
+    let modified = fs_metadata
+        .modified()
+        .unwrap_or(SystemTime::UNIX_EPOCH)
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs();
+    Ok((fs_metadata.len(), modified))
+}
+
+/// Run blocking file IO or self-encryption on the blocking thread pool, so as not to stall the async tasks.
+pub(crate) async fn run_blocking<T: Send + 'static>(
+    f: impl FnOnce() -> T + Send + 'static,
+) -> std::io::Result<T> {
+    tokio::task::spawn_blocking(f)
+        .await
+        .map_err(std::io::Error::other)
+}
+
+/// Names and sizes of the chunks a file is self-encrypted into, along with its data map chunk,
+/// and the hash of its content if `with_hash` is set.
+///
+/// The file is streamed through self-encryption, the chunks themselves are not kept.
+#[allow(clippy::type_complexity)]
+pub(crate) async fn file_content_addrs(
+    path: &Path,
+    with_hash: bool,
+) -> Result<(Chunk, Vec<(XorName, usize)>, Option<ContentHash>), UploadError> {
+    let path = path.to_path_buf();
+    run_blocking(move || blocking_file_content_addrs(&path, with_hash)).await?
+}
+
+#[allow(clippy::type_complexity)]
+fn blocking_file_content_addrs(
+    path: &Path,
+    with_hash: bool,
+) -> Result<(Chunk, Vec<(XorName, usize)>, Option<ContentHash>), UploadError> {
+    let mut encryptor = streaming_encryptor(path, with_hash).map_err(PutError::from)?;
+    let mut content_addrs = vec![];
+    while let Some(chunk) = encryptor.next_chunk().map_err(PutError::from)? {
+        content_addrs.push((*chunk.name(), chunk.size()));
+    }
+    let (data_map_chunk, additional_chunks, hash) =
+        encryptor.finish_with_sha256().map_err(PutError::from)?;
+    content_addrs.extend(
+        additional_chunks
+            .iter()
+            .map(|chunk| (*chunk.name(), chunk.size())),
+    );
+    Ok((data_map_chunk, content_addrs, hash.map(ContentHash::Sha256)))
+}
+
+fn streaming_encryptor(
+    path: &Path,
+    with_hash: bool,
+) -> Result<StreamingEncryptor, crate::self_encryption::Error> {
+    if with_hash {
+        StreamingEncryptor::from_file_with_sha256(path)
+    } else {
+        StreamingEncryptor::from_file(path)
+    }
+}
+
+/// Content type of a file, guessed from its extension, or sniffed from its first bytes.
+pub(crate) async fn file_content_type(path: &Path) -> std::io::Result<Option<String>> {
+    let path = path.to_path_buf();
+    run_blocking(move || blocking_file_content_type(&path)).await?
+}
+
+fn blocking_file_content_type(path: &Path) -> std::io::Result<Option<String>> {
+    use std::io::Read;
+
+    if let Some(mime) = mime_guess::from_path(path).first() {
+        return Ok(Some(mime.to_string()));
+    }
+    let mut head = vec![];
+    std::fs::File::open(path)?
+        .take(CONTENT_SNIFF_SIZE)
+        .read_to_end(&mut head)?;
+    Ok(infer::get(&head).map(|kind| kind.mime_type().to_string()))
+}
+
+/// What is recorded about the content of a file in an archive, if anything.
+pub(crate) fn content_info(
+    hash: Option<ContentHash>,
+    content_type: Option<String>,
+) -> Option<ContentInfo> {
+    (hash.is_some() || content_type.is_some()).then_some(ContentInfo { hash, content_type })
+}
+
+/// Write the content of a [`DataStream`] to a file, [`CHUNK_DOWNLOAD_BATCH_SIZE`] chunks at a time,
+/// so that the file never has to fit in memory.
+///
+/// If an `expected_hash` is given, the written content is checked against it.
+/// On mismatch the file is removed and [`DownloadError::HashMismatch`] is returned.
+pub(crate) async fn write_stream_to_file(
+    stream: &DataStream,
+    to_dest: &Path,
+    expected_hash: Option<&ContentHash>,
+) -> Result<(), DownloadError> {
+    if let Some(parent) = to_dest.parent() {
+        tokio::fs::create_dir_all(parent).await?;
+        debug!("Created parent directories {parent:?} for {to_dest:?}");
+    }
+
+    let mut file = tokio::fs::File::create(to_dest).await?;
+    let mut hasher = ContentHasher::new();
+    let chunk_count = stream.chunk_count();
+    let mut start = 0;
+    while start < chunk_count {
+        let end = chunk_count.min(start + *CHUNK_DOWNLOAD_BATCH_SIZE);
+        let bytes = stream.read_chunks(start..end).await?;
+        hasher.update(&bytes);
+        file.write_all(&bytes).await?;
+        start = end;
+    }
+    file.flush().await?;
+
+    if let Some(expected) = expected_hash {
+        let actual = hasher.finalize();
+        if actual != *expected {
+            error!("Downloaded content of {to_dest:?} does not match its hash: expected {expected}, got {actual}");
+            drop(file);
+            if let Err(err) = tokio::fs::remove_file(to_dest).await {
+                warn!("Failed to remove corrupted download {to_dest:?}: {err}");
+            }
+            return Err(DownloadError::HashMismatch(Box::new(HashMismatch {
+                path: to_dest.to_path_buf(),
+                expected: *expected,
+                actual,
+            })));
+        }
+    }
+    Ok(())
+}
+
+/// What a directory walk entry is recorded as in an archive.
+pub(crate) enum WalkedEntry {
+    /// A regular file, whose content is uploaded
+    File,
+    /// A directory, recorded to preserve empty directories and their attributes
+    Directory,
+    /// A symbolic link, recorded with its target rather than followed
+    Symlink(PathBuf),
+    /// Anything else, e.g. a socket or a device, which is skipped
+    Unsupported,
+}
+
+impl WalkedEntry {
+    /// Classify an entry of a walk that does not follow symlinks.
+    pub(crate) fn of(entry: &walkdir::DirEntry) -> std::io::Result<Self> {
+        let file_type = entry.file_type();
+        Ok(if file_type.is_dir() {
+            Self::Directory
+        } else if file_type.is_symlink() {
+            Self::Symlink(std::fs::read_link(entry.path())?)
+        } else if file_type.is_file() {
+            Self::File
+        } else {
+            Self::Unsupported
+        })
+    }
+}
+
+/// Returns true if a file has the same size and modification time as when it was recorded in an archive.
+///
+/// An unknown modification time never matches, as the file could have changed.
+pub(crate) fn is_unchanged(previous: &Metadata, current: &Metadata) -> bool {
+    current.modified != 0 && previous.size == current.size && previous.modified == current.modified
+}
+
+/// Make sure that the files uploaded from a directory are the ones of the archive that was paid for,
+/// as that archive is uploaded rather than the one built by the upload.
+pub(crate) fn check_paid_archive<A: ArchiveEntries>(
+    paid: &A,
+    uploaded: &A,
+) -> Result<(), UploadError> {
+    let paid_files = paid.file_entries();
+    let uploaded_files = uploaded.file_entries();
+    let mismatch = paid_files
+        .iter()
+        .find(|(path, (content, _))| {
+            uploaded_files.get(*path).map(|(uploaded, _)| uploaded) != Some(content)
+        })
+        .map(|(path, _)| path)
+        .or_else(|| {
+            uploaded_files
+                .keys()
+                .find(|path| !paid_files.contains_key(*path))
+        });
+    match mismatch {
+        Some(path) => {
+            error!("File {path:?} differs from the archive that was paid for");
+            Err(UploadError::PaidArchiveMismatch(path.clone()))
+        }
+        None => Ok(()),
+    }
+}
+
+/// Recreate the directories and symlinks of an archive under `to_dest`, once its files are downloaded,
+/// then apply the recorded POSIX attributes of every entry.
+///
+/// Nothing is created if an entry would end up outside of `to_dest`, see [`check_nodes_confined`].
+/// Symlinks are only recreated, and attributes only applied, on Unix.
+/// The recorded owner is only restored if `restore_owner` is set.
+/// Failing to apply attributes (e.g. changing the owner without the privileges to do so) is only logged.
+pub(crate) async fn restore_nodes(
+    to_dest: &Path,
+    nodes: Vec<(PathBuf, NodeType)>,
+    attributes: Vec<(PathBuf, UnixAttributes)>,
+    restore_owner: bool,
+) -> Result<(), DownloadError> {
+    check_nodes_confined(&nodes)?;
+
+    for (path, node_type) in &nodes {
+        if *node_type == NodeType::Directory {
+            tokio::fs::create_dir_all(to_dest.join(path)).await?;
+        }
+    }
+
+    for (path, node_type) in &nodes {
+        let NodeType::Symlink(target) = node_type else {
+            continue;
+        };
+        let link = to_dest.join(path);
+        if let Some(parent) = link.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        #[cfg(unix)]
+        tokio::fs::symlink(target, &link).await?;
+        #[cfg(not(unix))]
+        warn!("Not recreating symlink {link:?} to {target:?}: only supported on Unix");
+    }
+
+    #[cfg(unix)]
+    {
+        let is_symlink = |path: &PathBuf| {
+            nodes
+                .iter()
+                .any(|(node, node_type)| node == path && matches!(node_type, NodeType::Symlink(_)))
+        };
+        // deepest entries first, so that restricting the permissions of a directory
+        // does not prevent applying the attributes of its content
+        let mut attributes = attributes;
+        attributes.sort_by_key(|(path, _)| std::cmp::Reverse(path.components().count()));
+        for (path, attrs) in attributes {
+            let attrs = if restore_owner {
+                attrs
+            } else {
+                attrs.without_owner()
+            };
+            apply_unix_attributes(&to_dest.join(&path), &attrs, is_symlink(&path));
+        }
+    }
+    #[cfg(not(unix))]
+    if !attributes.is_empty() {
+        let _ = restore_owner;
+        debug!(
+            "Not applying POSIX attributes of {} entries: only supported on Unix",
+            attributes.len()
+        );
+    }
+
+    Ok(())
+}
+
+/// Make sure that restoring the directories and symlinks of an archive cannot touch anything outside
+/// of the download directory: paths must be relative, and symlinks must point inside the download directory.
+///
+/// Symlink targets are resolved lexically from the directory of the link. As the resolution does not
+/// follow symlinks, a target that goes through another symlink of the archive is rejected as well.
+fn check_nodes_confined(nodes: &[(PathBuf, NodeType)]) -> Result<(), DownloadError> {
+    use std::path::Component;
+
+    let symlinks: HashSet<&Path> = nodes
+        .iter()
+        .filter(|(_, node_type)| matches!(node_type, NodeType::Symlink(_)))
+        .map(|(path, _)| path.as_path())
+        .collect();
+    for (path, node_type) in nodes {
+        if !path
+            .components()
+            .all(|component| matches!(component, Component::Normal(_)))
+        {
+            error!("Refusing to restore archive entry {path:?} outside of the download directory");
+            return Err(DownloadError::UnsafePath(path.clone()));
+        }
+        let NodeType::Symlink(target) = node_type else {
+            continue;
+        };
+        let unsafe_symlink = || {
+            error!("Refusing to restore symlink {path:?} pointing outside of the download directory, to {target:?}");
+            DownloadError::UnsafeSymlink {
+                link: path.clone(),
+                target: target.clone(),
+            }
+        };
+        let mut resolved = path.parent().map(Path::to_path_buf).unwrap_or_default();
+        let mut components = target.components().peekable();
+        while let Some(component) = components.next() {
+            match component {
+                Component::Normal(name) => {
+                    resolved.push(name);
+                    if components.peek().is_some() && symlinks.contains(resolved.as_path()) {
+                        return Err(unsafe_symlink());
+                    }
+                }
+                Component::CurDir => {}
+                Component::ParentDir => {
+                    if !resolved.pop() {
+                        return Err(unsafe_symlink());
+                    }
+                }
+                Component::RootDir | Component::Prefix(_) => return Err(unsafe_symlink()),
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(unix)]
+fn apply_unix_attributes(path: &Path, attrs: &UnixAttributes, is_symlink: bool) {
+    use std::os::unix::fs::PermissionsExt;
+
+    if attrs.uid.is_some() || attrs.gid.is_some() {
+        let chown = if is_symlink {
+            std::os::unix::fs::lchown(path, attrs.uid, attrs.gid)
+        } else {
+            std::os::unix::fs::chown(path, attrs.uid, attrs.gid)
+        };
+        if let Err(err) = chown {
+            debug!("Could not change the owner of {path:?}: {err}");
+        }
+    }
+    // the permissions of a symlink are not meaningful, setting them would follow the link
+    if let (Some(mode), false) = (attrs.mode, is_symlink) {
+        let permissions = std::fs::Permissions::from_mode(mode & UnixAttributes::PERMISSION_BITS);
+        if let Err(err) = std::fs::set_permissions(path, permissions) {
+            warn!("Could not set the permissions of {path:?}: {err}");
+        }
+    }
+}
+
+/// Failure to stream the content of a single file to the network.
+#[derive(Debug)]
+pub(crate) enum StreamUploadError {
+    /// The file could not be read or self-encrypted
+    Encryption(UploadError),
+    /// Some chunks could not be paid for or uploaded
+    Upload {
+        error: UploadError,
+        chunks: Vec<ChunkAddress>,
+    },
+}
+
+impl StreamUploadError {
+    fn encryption(err: impl Into<PutError>) -> Self {
+        Self::Encryption(UploadError::PutError(err.into()))
+    }
+
+    fn upload(err: impl Into<UploadError>) -> Self {
+        Self::Upload {
+            error: err.into(),
+            chunks: vec![],
+        }
+    }
+}
+
+impl From<StreamUploadError> for UploadError {
+    fn from(err: StreamUploadError) -> Self {
+        match err {
+            StreamUploadError::Encryption(err) => err,
+            StreamUploadError::Upload { error, .. } => error,
+        }
+    }
+}
+
+impl From<StreamUploadError> for FileUploadOutcome {
+    fn from(err: StreamUploadError) -> Self {
+        match err {
+            StreamUploadError::Encryption(err) => FileUploadOutcome::EncryptionFailed(err),
+            StreamUploadError::Upload { error, chunks } => {
+                FileUploadOutcome::UploadFailed { error, chunks }
+            }
+        }
+    }
+}
+
+/// Outcome of streaming the content of a single file to the network.
+pub(crate) struct StreamedFile {
+    /// The chunk holding the data map of the file
+    pub(crate) data_map_chunk: Chunk,
+    /// Number of chunks that were uploaded or already existed
+    pub(crate) chunks_uploaded: usize,
+    /// Number of chunks that were already paid for
+    pub(crate) chunks_already_paid: usize,
+    /// Total amount paid for the chunks of the file
+    pub(crate) tokens_spent: Amount,
+    /// Hash of the content of the file, if enabled with [`Client::enable_content_hashes`]
+    pub(crate) content_hash: Option<ContentHash>,
+}
+
+/// Report of the upload of a single file.
+pub(crate) fn single_file_report(path: &Path, streamed: &StreamedFile) -> UploadReport {
+    let mut report = UploadReport::new();
+    let outcome = FileUploadOutcome::Uploaded {
+        chunks: streamed.chunks_uploaded,
+    };
+    report.insert(path.to_path_buf(), outcome);
+    report
+}
 
 impl Client {
-    pub(crate) async fn process_upload_results(
+    /// Self-encrypt a file in windows of [`STREAM_UPLOAD_WINDOW_SIZE`] chunks, paying for and uploading
+    /// each window before encrypting the next one, so that the file never has to fit in memory.
+    /// The windows are paid for through `payments`, along with those of the other files uploaded with it.
+    /// The file is read and encrypted on the blocking thread pool.
+    ///
+    /// If `upload_data_map` is set, the data map chunk is uploaded as well, making the file public.
+    ///
+    /// If a `journal` is given, the progress is recorded in it, and whatever it already records
+    /// (a complete file, stored chunks, unexpired payments) is not done a second time.
+    pub(crate) async fn stream_upload_file(
         &self,
-        uploads: Vec<(String, Result<usize, UploadError>)>,
-        receipt: Receipt,
-        skipped_payments_amount: usize,
-    ) -> Result<AttoTokens, UploadError> {
-        let mut total_chunks_uploaded = 0;
-        let mut last_err: Option<UploadError> = None;
+        path: &Path,
+        payments: &PaymentBatcher,
+        upload_data_map: bool,
+        journal: Option<&UploadJournal>,
+    ) -> Result<StreamedFile, StreamUploadError> {
+        let (file_size, file_modified) = file_size_and_modified(path)
+            .await
+            .map_err(StreamUploadError::Encryption)?;
+        if let Some(journal) = journal {
+            if let Some(file) = journal.completed_file(path, file_size, file_modified).await {
+                info!("Skipping {path:?}, it was already uploaded according to the upload journal");
+                return Ok(StreamedFile {
+                    data_map_chunk: file.data_map_chunk.0,
+                    chunks_uploaded: 0,
+                    chunks_already_paid: 0,
+                    tokens_spent: Amount::ZERO,
+                    content_hash: file.content_hash,
+                });
+            }
+        }
 
-        for (name, result) in uploads {
-            match result {
-                Ok(chunks_uploaded) => {
-                    total_chunks_uploaded += chunks_uploaded;
+        let _active_file = payments.start_file();
+        let now = ant_networking::time::Instant::now();
+        let file_path = path.to_path_buf();
+        let with_hash = self.content_hashes;
+        let mut encryptor = run_blocking(move || streaming_encryptor(&file_path, with_hash))
+            .await
+            .map_err(|err| StreamUploadError::Encryption(err.into()))?
+            .map_err(StreamUploadError::encryption)?;
+        let mut chunk_names = vec![];
+        let mut chunks_uploaded = 0;
+        let mut chunks_already_paid = 0;
+        let mut tokens_spent = Amount::ZERO;
+
+        loop {
+            let (returned, chunks) = run_blocking(move || {
+                let chunks = encryptor.next_chunks(*STREAM_UPLOAD_WINDOW_SIZE);
+                (encryptor, chunks)
+            })
+            .await
+            .map_err(|err| StreamUploadError::Encryption(err.into()))?;
+            encryptor = returned;
+            let chunks = chunks.map_err(StreamUploadError::encryption)?;
+            if chunks.is_empty() {
+                break;
+            }
+
+            let (skipped, spent) = self
+                .pay_and_upload_chunks(&chunks, payments, journal)
+                .await?;
+            chunk_names.extend(chunks.iter().map(|chunk| *chunk.name()));
+            chunks_uploaded += chunks.len();
+            chunks_already_paid += skipped;
+            tokens_spent += spent;
+        }
+
+        let (data_map_chunk, mut remaining_chunks, content_hash) =
+            run_blocking(move || encryptor.finish_with_sha256())
+                .await
+                .map_err(|err| StreamUploadError::Encryption(err.into()))?
+                .map_err(StreamUploadError::encryption)?;
+        debug!(
+            "Streamed encryption of {path:?} took: {:.2?}",
+            now.elapsed()
+        );
+        chunk_names.extend(remaining_chunks.iter().map(|chunk| *chunk.name()));
+
+        if upload_data_map {
+            remaining_chunks.push(data_map_chunk.clone());
+        }
+        if !remaining_chunks.is_empty() {
+            let (skipped, spent) = self
+                .pay_and_upload_chunks(&remaining_chunks, payments, journal)
+                .await?;
+            chunks_uploaded += remaining_chunks.len();
+            chunks_already_paid += skipped;
+            tokens_spent += spent;
+        }
+
+        if let Some(journal) = journal {
+            let file = JournaledFile {
+                size: file_size,
+                modified: file_modified,
+                data_map_chunk: DataMapChunk::from(data_map_chunk.clone()),
+                chunks: chunk_names,
+                content_hash: content_hash.map(ContentHash::Sha256),
+            };
+            journal.record_file(path.to_path_buf(), file).await;
+            journal.save().await.map_err(StreamUploadError::upload)?;
+        }
+
+        Ok(StreamedFile {
+            data_map_chunk,
+            chunks_uploaded,
+            chunks_already_paid,
+            tokens_spent,
+            content_hash: content_hash.map(ContentHash::Sha256),
+        })
+    }
+
+    /// Pay for and upload a window of chunks.
+    ///
+    /// Returns the number of chunks that were already paid for and the amount spent on this window.
+    /// On failure, the error lists the chunks that could not be paid for or uploaded.
+    async fn pay_and_upload_chunks(
+        &self,
+        chunks: &[Chunk],
+        payments: &PaymentBatcher,
+        journal: Option<&UploadJournal>,
+    ) -> Result<(usize, Amount), StreamUploadError> {
+        let mut to_upload = vec![];
+        let mut to_pay = vec![];
+        let mut prepaid = Receipt::new();
+        for chunk in chunks {
+            if let Some(journal) = journal {
+                if journal.is_stored(chunk.name()).await {
+                    continue;
                 }
-                Err(err) => {
-                    error!("Error uploading file {name}: {err:?}");
-                    #[cfg(feature = "loud")]
-                    println!("Error uploading file {name}: {err:?}");
+                if let Some(payment) = journal.unexpired_payment(chunk.name()).await {
+                    prepaid.insert(*chunk.name(), payment);
+                    to_upload.push(chunk);
+                    continue;
+                }
+            }
+            to_pay.push((*chunk.name(), chunk.size()));
+            to_upload.push(chunk);
+        }
+        if !prepaid.is_empty() {
+            info!(
+                "Re-using {} unexpired payments from the upload journal",
+                prepaid.len()
+            );
+        }
 
-                    last_err = Some(err);
+        let (receipt, skipped_payments) = if to_pay.is_empty() {
+            (Receipt::new(), 0)
+        } else {
+            let result = payments
+                .pay(to_pay.clone())
+                .await
+                .inspect_err(|err| error!("Error paying for data: {err:?}"));
+            match result {
+                Ok(paid) => paid,
+                Err(err) => {
+                    // keep what was paid for, so that resuming the upload doesn't pay for it again
+                    if let (Some(journal), Some(partial)) = (journal, err.partial_receipt()) {
+                        journal.record_payments(partial).await;
+                        journal.save().await.map_err(StreamUploadError::upload)?;
+                    }
+                    return Err(StreamUploadError::Upload {
+                        error: PutError::from(err).into(),
+                        chunks: to_upload.iter().map(|chunk| *chunk.address()).collect(),
+                    });
                 }
             }
+        };
+
+        // only keep what was paid for this window, a receipt given as payment option may cover more
+        let paid: Receipt = to_pay
+            .iter()
+            .filter_map(|(name, _)| receipt.get(name).map(|payment| (*name, payment.clone())))
+            .collect();
+        let tokens_spent = paid
+            .values()
+            .map(|(_, cost)| cost.as_atto())
+            .sum::<Amount>();
+
+        if let Some(journal) = journal {
+            journal.record_payments(&paid).await;
+            journal.save().await.map_err(StreamUploadError::upload)?;
+        }
+
+        let receipt: Receipt = paid.into_iter().chain(prepaid).collect();
+        let names_to_upload: Vec<_> = to_upload.iter().map(|chunk| *chunk.name()).collect();
+        let failed_uploads = self.upload_chunks_with_retries(to_upload, &receipt).await;
+
+        if let Some(journal) = journal {
+            let failed: HashSet<_> = failed_uploads
+                .iter()
+                .map(|(chunk, _)| *chunk.name())
+                .collect();
+            journal
+                .record_stored(
+                    names_to_upload
+                        .into_iter()
+                        .filter(|name| !failed.contains(name)),
+                )
+                .await;
+            journal.save().await.map_err(StreamUploadError::upload)?;
         }
 
-        // todo: bundle the errors together in a new error type
-        // Throw an error if not all files were uploaded successfully
-        if let Some(err) = last_err {
-            return Err(err);
+        // Return the last chunk upload error, along with all the chunks that failed
+        let failed_chunks: Vec<_> = failed_uploads
+            .iter()
+            .map(|(chunk, _)| *chunk.address())
+            .collect();
+        if let Some((chunk, err)) = failed_uploads.into_iter().last() {
+            error!("Error uploading chunk ({:?}): {:?}", chunk.address(), err);
+            return Err(StreamUploadError::Upload {
+                error: err.into(),
+                chunks: failed_chunks,
+            });
         }
 
+        Ok((skipped_payments, tokens_spent))
+    }
+
+    /// Pay for the chunks with the given names and sizes, returning the receipt for the payments
+    /// so that the upload can be done later, possibly from another machine.
+    ///
+    /// The chunks covered by the given receipt are not paid for again, the returned receipt includes it.
+    pub(crate) async fn pay_for_upload(
+        &self,
+        content_addrs: Vec<(XorName, usize)>,
+        wallet: &EvmWallet,
+        receipt: Receipt,
+    ) -> Result<(AttoTokens, Receipt), UploadError> {
+        info!(
+            "Paying for {} addresses, {} already paid for",
+            content_addrs.len(),
+            receipt.len()
+        );
+        let (receipt, skipped_payments) = self
+            .pay_with_receipt(DataTypes::Chunk, content_addrs.into_iter(), wallet, receipt)
+            .await
+            .inspect_err(|err| error!("Error paying for data: {err:?}"))
+            .map_err(PutError::from)?;
         let tokens_spent = receipt
             .values()
             .map(|(_, cost)| cost.as_atto())
             .sum::<Amount>();
+        info!(
+            "Paid {tokens_spent} for {} addresses, {skipped_payments} were already paid for",
+            receipt.len()
+        );
+        Ok((AttoTokens::from_atto(tokens_spent), receipt))
+    }
+
+    /// Log the files that failed in the report and send the [`ClientEvent::UploadComplete`] summary.
+    ///
+    /// Returns the total amount spent.
+    pub(crate) async fn process_upload_results(
+        &self,
+        report: &UploadReport,
+        tokens_spent: Amount,
+        skipped_payments_amount: usize,
+    ) -> AttoTokens {
+        for (path, reason) in report.skipped() {
+            warn!("Skipped file {path:?}: {reason}");
+        }
+        for (path, outcome) in report.failed() {
+            error!("Error uploading file {path:?}: {outcome:?}");
+            #[cfg(feature = "loud")]
+            println!("Error uploading file {path:?}: {outcome}");
+        }
 
         // Reporting
         if let Some(channel) = self.client_event_sender.as_ref() {
             let summary = UploadSummary {
-                records_paid: total_chunks_uploaded.saturating_sub(skipped_payments_amount),
+                records_paid: report
+                    .chunks_uploaded()
+                    .saturating_sub(skipped_payments_amount),
                 records_already_paid: skipped_payments_amount,
                 tokens_spent,
             };
@@ -52,6 +721,167 @@ impl Client {
             }
         }
 
-        Ok(AttoTokens::from_atto(tokens_spent))
+        AttoTokens::from_atto(tokens_spent)
+    }
+}
+
+/// POSIX attributes of a directory entry, without following symlinks. Only available on Unix.
+pub(crate) fn unix_attributes_from_entry(entry: &walkdir::DirEntry) -> Option<UnixAttributes> {
+    match entry.metadata() {
+        Ok(fs_metadata) => UnixAttributes::from_fs_metadata(&fs_metadata),
+        Err(err) => {
+            tracing::warn!(
+                "Failed to get attributes of `{}`: {err}",
+                entry.path().display()
+            );
+            None
+        }
+    }
+}
+
+/// Record a directory, or a symlink to `symlink_target`, in an archive being built from a directory walk.
+pub(crate) fn add_node_from_entry(
+    archive: &mut impl ArchiveEntries,
+    entry: &walkdir::DirEntry,
+    dir_path: &Path,
+    symlink_target: Option<PathBuf>,
+) {
+    let relative_path =
+        get_relative_file_path_from_abs_file_and_folder_path(entry.path(), dir_path);
+    if let Some(attributes) = unix_attributes_from_entry(entry) {
+        archive.set_unix_attributes(relative_path.clone(), attributes);
+    }
+    let metadata = metadata_from_entry(entry);
+    let node_type = match symlink_target {
+        Some(target) => NodeType::Symlink(target),
+        None => NodeType::Directory,
+    };
+    archive
+        .node_entries_mut()
+        .insert(relative_path, (node_type, metadata));
+}
+
+/// The directories and symlinks of an archive, and the POSIX attributes of all its entries.
+#[allow(clippy::type_complexity)]
+pub(crate) fn archive_nodes(
+    archive: &impl ArchiveEntries,
+) -> (Vec<(PathBuf, NodeType)>, Vec<(PathBuf, UnixAttributes)>) {
+    let nodes: Vec<_> = archive
+        .node_entries()
+        .iter()
+        .map(|(path, (node_type, _meta))| (path.clone(), node_type.clone()))
+        .collect();
+    let unix_attributes = archive.unix_attribute_entries();
+    let attributes = archive
+        .file_entries()
+        .keys()
+        .chain(nodes.iter().map(|(path, _)| path))
+        .filter_map(|path| Some((path.clone(), *unix_attributes.get(path)?)))
+        .collect();
+    (nodes, attributes)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn content_info_of_file() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("index.html");
+        let content = b"<html><body>hello</body></html>";
+        std::fs::write(&path, content).unwrap();
+
+        let (_, _, hash) = file_content_addrs(&path, true).await.unwrap();
+        assert_eq!(hash, Some(ContentHash::sha256(content)));
+        let (_, _, hash) = file_content_addrs(&path, false).await.unwrap();
+        assert_eq!(hash, None);
+        let content_type = file_content_type(&path).await.unwrap();
+        assert_eq!(content_type.as_deref(), Some("text/html"));
+
+        // without an extension, the content type is sniffed from the content
+        let path = dir.path().join("image");
+        std::fs::write(&path, b"\x89PNG\r\n\x1a\n\0\0\0\rIHDR").unwrap();
+        let content_type = file_content_type(&path).await.unwrap();
+        assert_eq!(content_type.as_deref(), Some("image/png"));
+    }
+
+    #[test]
+    fn paid_archive_only_differs_in_metadata() {
+        use crate::client::files::archive_public::PublicArchive;
+        use crate::client::high_level::data::DataAddress;
+
+        let file = PathBuf::from("dir/file");
+        let addr = DataAddress::new(XorName::random(&mut rand::thread_rng()));
+        let mut paid = PublicArchive::new();
+        paid.add_file(file.clone(), addr, Metadata::new_with_size(1));
+
+        // e.g. uploaded from another machine, with other timestamps
+        let mut uploaded = PublicArchive::new();
+        uploaded.add_file(file.clone(), addr, Metadata::empty());
+        assert!(check_paid_archive(&paid, &uploaded).is_ok());
+
+        let mut changed = PublicArchive::new();
+        let other_addr = DataAddress::new(XorName::random(&mut rand::thread_rng()));
+        changed.add_file(file.clone(), other_addr, Metadata::new_with_size(1));
+        assert!(matches!(
+            check_paid_archive(&paid, &changed),
+            Err(UploadError::PaidArchiveMismatch(path)) if path == file
+        ));
+
+        let added = PathBuf::from("dir/added");
+        uploaded.add_file(added.clone(), addr, Metadata::empty());
+        assert!(matches!(
+            check_paid_archive(&paid, &uploaded),
+            Err(UploadError::PaidArchiveMismatch(path)) if path == added
+        ));
+    }
+
+    #[cfg(unix)]
+    #[tokio::test]
+    async fn malicious_archive_nodes_are_not_restored() {
+        let root = tempfile::tempdir().unwrap();
+        let to_dest = root.path().join("download");
+        let symlink = |path: &str, target: &str| {
+            (
+                PathBuf::from(path),
+                NodeType::Symlink(PathBuf::from(target)),
+            )
+        };
+
+        let malicious = [
+            vec![symlink("passwd", "/etc/passwd")],
+            vec![symlink("dir/outside", "../../secret")],
+            vec![(PathBuf::from("../escaped"), NodeType::Directory)],
+            vec![symlink("/tmp/link", "target")],
+            vec![
+                symlink("dir/up", ".."),
+                symlink("escape", "dir/up/../secret"),
+            ],
+        ];
+        for nodes in malicious {
+            let result = restore_nodes(&to_dest, nodes.clone(), vec![], false).await;
+            assert!(
+                matches!(
+                    result,
+                    Err(DownloadError::UnsafeSymlink { .. } | DownloadError::UnsafePath(_))
+                ),
+                "{nodes:?} was restored: {result:?}"
+            );
+        }
+        // nothing is created when an archive is rejected
+        assert!(!to_dest.exists());
+        assert!(!root.path().join("escaped").exists());
+
+        let nodes = vec![
+            (PathBuf::from("dir"), NodeType::Directory),
+            symlink("dir/link", "../file"),
+            symlink("self", "./dir/./link"),
+        ];
+        restore_nodes(&to_dest, nodes, vec![], false).await.unwrap();
+        assert_eq!(
+            std::fs::read_link(to_dest.join("dir/link")).unwrap(),
+            PathBuf::from("../file")
+        );
     }
 }
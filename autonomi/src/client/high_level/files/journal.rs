@@ -0,0 +1,363 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::collections::{BTreeMap, HashSet};
+use std::path::{Path, PathBuf};
+
+use ant_evm::{AttoTokens, ProofOfPayment};
+use serde::{Deserialize, Serialize};
+use tokio::io::AsyncWriteExt;
+use tokio::sync::Mutex;
+use xor_name::XorName;
+
+use super::{ContentHash, UploadError};
+use crate::client::data_types::chunk::DataMapChunk;
+use crate::client::payment::Receipt;
+use crate::client::payment_ledger::{push_record_frame, record_frames};
+
+/// On-disk journal of an upload in progress.
+///
+/// It records the payments made for the chunks of an upload, which chunks were stored on the network,
+/// and the data map and chunk list of every file that was completely uploaded.
+/// Resuming an interrupted upload with the same journal skips the files and chunks that made it to the network,
+/// and uses the recorded payments instead of paying a second time, as long as their quotes have not expired.
+///
+/// The journal is saved to disk as the upload progresses: the progress made since the last save is appended to it,
+/// each entry prefixed with its length, and it is compacted when opened.
+pub struct UploadJournal {
+    path: PathBuf,
+    state: Mutex<JournalState>,
+    // Progress recorded in the state since the last save, in the order it was recorded
+    unsaved: Mutex<Vec<JournalEntry>>,
+    // Length of the complete entries of the journal on disk.
+    // Also serializes the writes of the journal to disk.
+    saved_len: Mutex<u64>,
+}
+
+#[derive(Debug, Default)]
+struct JournalState {
+    /// Payments for chunks that are not stored on the network yet
+    payments: Receipt,
+    /// Chunks stored on the network, belonging to files that are not complete yet
+    stored_chunks: HashSet<XorName>,
+    /// Files that were completely uploaded
+    files: BTreeMap<PathBuf, JournaledFile>,
+}
+
+/// A file that was completely uploaded.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+pub(crate) struct JournaledFile {
+    /// Size of the file when it was uploaded
+    pub(crate) size: u64,
+    /// Last modification time of the file when it was uploaded, as a UNIX timestamp
+    pub(crate) modified: u64,
+    /// Data map of the file
+    pub(crate) data_map_chunk: DataMapChunk,
+    /// Chunks the file is made of, excluding the data map chunk
+    pub(crate) chunks: Vec<XorName>,
+    /// Hash of the content of the file, if it was computed
+    #[serde(default)]
+    pub(crate) content_hash: Option<ContentHash>,
+}
+
+/// Progress of an upload, as appended to the journal on disk.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum JournalEntry {
+    Payments(Receipt),
+    Stored(Vec<XorName>),
+    File(PathBuf, Box<JournaledFile>),
+}
+
+/// This type essentially wraps the journal entries in a version marker, so the format can evolve.
+#[derive(Serialize, Deserialize)]
+enum JournalEntryVersioned {
+    V0(JournalEntry),
+}
+
+impl JournalState {
+    fn apply(&mut self, entry: JournalEntry) {
+        match entry {
+            JournalEntry::Payments(receipt) => self.payments.extend(receipt),
+            JournalEntry::Stored(names) => {
+                for name in names {
+                    // the payment is of no use anymore once the chunk is stored
+                    self.payments.remove(&name);
+                    self.stored_chunks.insert(name);
+                }
+            }
+            JournalEntry::File(path, file) => {
+                // the file is skipped as a whole when resuming, no need to track its chunks anymore
+                for name in &file.chunks {
+                    self.stored_chunks.remove(name);
+                }
+                self.stored_chunks.remove(file.data_map_chunk.0.name());
+                self.files.insert(path, *file);
+            }
+        }
+    }
+
+    /// The entries recording the whole state, to compact the journal.
+    fn entries(&self) -> Vec<JournalEntry> {
+        let mut entries = vec![
+            JournalEntry::Payments(self.payments.clone()),
+            JournalEntry::Stored(self.stored_chunks.iter().copied().collect()),
+        ];
+        entries.extend(
+            self.files
+                .iter()
+                .map(|(path, file)| JournalEntry::File(path.clone(), Box::new(file.clone()))),
+        );
+        entries
+    }
+}
+
+fn encode_entries(entries: Vec<JournalEntry>) -> Result<Vec<u8>, UploadError> {
+    let mut bytes = vec![];
+    for entry in entries {
+        let encoded = rmp_serde::to_vec(&JournalEntryVersioned::V0(entry))?;
+        push_record_frame(&mut bytes, &encoded)?;
+    }
+    Ok(bytes)
+}
+
+impl UploadJournal {
+    /// Open the journal at the given path, or start an empty one if there is no journal there yet.
+    ///
+    /// Entries that cannot be read, such as an entry truncated by an interrupted save, are dropped,
+    /// the progress they recorded is made again.
+    pub async fn open(path: PathBuf) -> Result<Self, UploadError> {
+        let bytes = match tokio::fs::read(&path).await {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => {
+                return Ok(Self::new(path, JournalState::default(), 0));
+            }
+            Err(err) => return Err(err.into()),
+        };
+
+        let mut state = JournalState::default();
+        let (frames, end) = record_frames(&bytes);
+        if end < bytes.len() {
+            warn!("Ignoring a truncated entry at the end of the upload journal {path:?}");
+        }
+        for frame in frames {
+            match rmp_serde::from_slice(frame) {
+                Ok(JournalEntryVersioned::V0(entry)) => state.apply(entry),
+                Err(err) => {
+                    warn!("Skipping a corrupted entry of the upload journal {path:?}: {err}")
+                }
+            }
+        }
+        debug!(
+            "Loaded upload journal from {path:?}: {} pending payments, {} stored chunks, {} completed files",
+            state.payments.len(),
+            state.stored_chunks.len(),
+            state.files.len()
+        );
+
+        // compact the journal, so that it doesn't grow with the entries made obsolete by later ones
+        let bytes = encode_entries(state.entries())?;
+        // write to a temporary file first so an interruption never leaves a truncated journal
+        let tmp_path = path.with_extension("tmp");
+        tokio::fs::write(&tmp_path, &bytes).await?;
+        tokio::fs::rename(&tmp_path, &path).await?;
+
+        Ok(Self::new(path, state, bytes.len() as u64))
+    }
+
+    fn new(path: PathBuf, state: JournalState, saved_len: u64) -> Self {
+        Self {
+            path,
+            state: Mutex::new(state),
+            unsaved: Mutex::new(vec![]),
+            saved_len: Mutex::new(saved_len),
+        }
+    }
+
+    /// Path of the journal on disk.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Returns true if the journal has not recorded any progress.
+    pub async fn is_empty(&self) -> bool {
+        let state = self.state.lock().await;
+        state.payments.is_empty() && state.stored_chunks.is_empty() && state.files.is_empty()
+    }
+
+    /// Delete the journal from disk, typically once the upload is complete.
+    pub async fn remove(self) -> Result<(), UploadError> {
+        let _guard = self.saved_len.lock().await;
+        match tokio::fs::remove_file(&self.path).await {
+            Ok(()) => Ok(()),
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => Ok(()),
+            Err(err) => Err(err.into()),
+        }
+    }
+
+    /// Append the progress recorded since the last save to the journal on disk.
+    pub(crate) async fn save(&self) -> Result<(), UploadError> {
+        // take the write lock first so that the entries are written in the order they were recorded
+        let mut saved_len = self.saved_len.lock().await;
+        let entries = std::mem::take(&mut *self.unsaved.lock().await);
+        if entries.is_empty() {
+            return Ok(());
+        }
+        let bytes = encode_entries(entries)?;
+
+        if let Some(parent) = self.path.parent() {
+            tokio::fs::create_dir_all(parent).await?;
+        }
+        let mut file = tokio::fs::OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)
+            .await?;
+        // remove what a failed save may have left, so that it doesn't run into the new entries
+        if file.metadata().await?.len() != *saved_len {
+            file.set_len(*saved_len).await?;
+        }
+        // a single write, so that an interruption leaves at most the last entries truncated
+        file.write_all(&bytes).await?;
+        file.flush().await?;
+        *saved_len += bytes.len() as u64;
+        Ok(())
+    }
+
+    /// Apply progress to the state, and keep it to be saved.
+    async fn record(&self, entry: JournalEntry) {
+        let mut state = self.state.lock().await;
+        state.apply(entry.clone());
+        // pushed with the state locked, to be saved in the order it was applied
+        self.unsaved.lock().await.push(entry);
+    }
+
+    /// The completed file at the given path, if it has not changed since it was uploaded.
+    pub(crate) async fn completed_file(
+        &self,
+        path: &Path,
+        size: u64,
+        modified: u64,
+    ) -> Option<JournaledFile> {
+        let state = self.state.lock().await;
+        state
+            .files
+            .get(path)
+            .filter(|file| file.size == size && file.modified == modified)
+            .cloned()
+    }
+
+    /// Returns true if the chunk was stored on the network.
+    pub(crate) async fn is_stored(&self, name: &XorName) -> bool {
+        self.state.lock().await.stored_chunks.contains(name)
+    }
+
+    /// The recorded payment for a chunk, if its quotes have not expired.
+    pub(crate) async fn unexpired_payment(
+        &self,
+        name: &XorName,
+    ) -> Option<(ProofOfPayment, AttoTokens)> {
+        let state = self.state.lock().await;
+        state
+            .payments
+            .get(name)
+            .filter(|(proof, _)| !proof.has_expired())
+            .cloned()
+    }
+
+    pub(crate) async fn record_payments(&self, receipt: &Receipt) {
+        self.record(JournalEntry::Payments(receipt.clone())).await;
+    }
+
+    pub(crate) async fn record_stored(&self, names: impl IntoIterator<Item = XorName>) {
+        self.record(JournalEntry::Stored(names.into_iter().collect()))
+            .await;
+    }
+
+    pub(crate) async fn record_file(&self, path: PathBuf, file: JournaledFile) {
+        self.record(JournalEntry::File(path, Box::new(file))).await;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[tokio::test]
+    async fn journal_round_trip() {
+        let dir = tempfile::tempdir().unwrap();
+        let path = dir.path().join("journal");
+
+        let journal = UploadJournal::open(path.clone()).await.unwrap();
+        assert!(journal.is_empty().await);
+
+        let stored = XorName::random(&mut rand::thread_rng());
+        let paid = XorName::random(&mut rand::thread_rng());
+        let mut receipt = Receipt::new();
+        receipt.insert(
+            paid,
+            (
+                ProofOfPayment {
+                    peer_quotes: vec![],
+                },
+                AttoTokens::from_u64(10),
+            ),
+        );
+        receipt.insert(
+            stored,
+            (
+                ProofOfPayment {
+                    peer_quotes: vec![],
+                },
+                AttoTokens::from_u64(20),
+            ),
+        );
+        journal.record_payments(&receipt).await;
+        journal.record_stored([stored]).await;
+        journal
+            .record_file(
+                PathBuf::from("file"),
+                JournaledFile {
+                    size: 42,
+                    modified: 7,
+                    data_map_chunk: DataMapChunk::from_hex("1111").unwrap(),
+                    chunks: vec![],
+                    content_hash: None,
+                },
+            )
+            .await;
+        journal.save().await.unwrap();
+        // the next progress is appended to the journal
+        let stored_later = XorName::random(&mut rand::thread_rng());
+        journal.record_stored([stored_later]).await;
+        journal.save().await.unwrap();
+        drop(journal);
+        // an entry truncated by an interrupted save
+        let mut bytes = std::fs::read(&path).unwrap();
+        bytes.extend([0, 0, 0, 9, 1]);
+        std::fs::write(&path, bytes).unwrap();
+
+        let journal = UploadJournal::open(path.clone()).await.unwrap();
+        assert!(journal.is_stored(&stored).await);
+        assert!(journal.is_stored(&stored_later).await);
+        assert!(!journal.is_stored(&paid).await);
+        assert!(journal.unexpired_payment(&paid).await.is_some());
+        assert!(journal.unexpired_payment(&stored).await.is_none());
+        assert!(journal
+            .completed_file(Path::new("file"), 42, 7)
+            .await
+            .is_some());
+        // a modified file is not considered complete
+        assert!(journal
+            .completed_file(Path::new("file"), 42, 8)
+            .await
+            .is_none());
+
+        journal.remove().await.unwrap();
+        assert!(!path.exists());
+    }
+}
@@ -1,5 +1,6 @@
 use serde::{Deserialize, Serialize};
 use std::{
+    collections::BTreeMap,
     path::{Path, PathBuf},
     sync::LazyLock,
     time::{Duration, SystemTime, UNIX_EPOCH},
@@ -10,12 +11,21 @@ use crate::client::{quote::CostError, GetError, PutError};
 
 pub mod archive_private;
 pub mod archive_public;
+mod diff;
+pub mod folder;
 pub mod fs_private;
 pub mod fs_public;
 mod fs_shared;
+mod journal;
+mod payment_batch;
+mod report;
 
 pub use archive_private::PrivateArchive;
 pub use archive_public::PublicArchive;
+pub use diff::{ArchiveDiff, ConflictPolicy, MergeConflict, MergeSide};
+pub use folder::{FolderAddress, FolderArchive, FolderError};
+pub use journal::UploadJournal;
+pub use report::{FileUploadOutcome, SkipReason, UploadReport};
 
 /// Number of files to upload in parallel.
 ///
@@ -34,6 +44,20 @@ pub static FILE_UPLOAD_BATCH_SIZE: LazyLock<usize> = LazyLock::new(|| {
     batch_size
 });
 
+/// Number of chunks of a file that are encrypted, paid for and uploaded at a time.
+/// This bounds the memory used to upload a file, whatever its size.
+///
+/// Can be overridden by the `STREAM_UPLOAD_WINDOW_SIZE` environment variable.
+pub static STREAM_UPLOAD_WINDOW_SIZE: LazyLock<usize> = LazyLock::new(|| {
+    let window_size = std::env::var("STREAM_UPLOAD_WINDOW_SIZE")
+        .ok()
+        .and_then(|s| s.parse().ok())
+        .filter(|size| *size > 0)
+        .unwrap_or(32);
+    info!("Stream upload window size: {}", window_size);
+    window_size
+});
+
 /// Metadata for a file in an archive. Time values are UNIX timestamps.
 ///
 /// The recommended way to create a new [`Metadata`] is to use [`Metadata::new_with_size`].
@@ -87,6 +111,132 @@ impl Metadata {
     }
 }
 
+/// The type of an entry in an archive.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub enum NodeType {
+    /// A regular file, whose content is on the network
+    File,
+    /// A directory
+    Directory,
+    /// A symbolic link, pointing to the given target
+    Symlink(PathBuf),
+}
+
+/// POSIX attributes of an entry in an archive, when they were available on the system it was uploaded from.
+///
+/// Only the permission bits are kept, not the setuid, setgid and sticky bits.
+/// The owner is only recorded in private archives, and only restored on request.
+#[derive(Debug, Clone, Copy, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct UnixAttributes {
+    /// Permission bits, e.g. `0o755`
+    pub mode: Option<u32>,
+    /// Owner user ID
+    pub uid: Option<u32>,
+    /// Owner group ID
+    pub gid: Option<u32>,
+}
+
+impl UnixAttributes {
+    /// The bits of a mode that are recorded and restored.
+    pub const PERMISSION_BITS: u32 = 0o777;
+
+    /// The same attributes, without the owner.
+    pub fn without_owner(self) -> Self {
+        Self {
+            uid: None,
+            gid: None,
+            ..self
+        }
+    }
+
+    /// Read the attributes from the metadata of a file, only available on Unix.
+    pub fn from_fs_metadata(fs_metadata: &std::fs::Metadata) -> Option<Self> {
+        #[cfg(unix)]
+        {
+            use std::os::unix::fs::MetadataExt;
+            Some(Self {
+                mode: Some(fs_metadata.mode() & Self::PERMISSION_BITS),
+                uid: Some(fs_metadata.uid()),
+                gid: Some(fs_metadata.gid()),
+            })
+        }
+        #[cfg(not(unix))]
+        {
+            let _ = fs_metadata;
+            None
+        }
+    }
+}
+
+/// Hash of the plaintext content of a file.
+#[derive(Debug, Clone, Copy, Serialize, Deserialize, PartialEq, Eq, Hash)]
+pub enum ContentHash {
+    /// SHA-256 digest
+    Sha256([u8; 32]),
+}
+
+impl ContentHash {
+    /// Compute the SHA-256 hash of some content.
+    pub fn sha256(content: &[u8]) -> Self {
+        let mut hasher = ContentHasher::new();
+        hasher.update(content);
+        hasher.finalize()
+    }
+}
+
+impl std::fmt::Display for ContentHash {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            ContentHash::Sha256(digest) => write!(f, "sha256:{}", hex::encode(digest)),
+        }
+    }
+}
+
+/// Computes a [`ContentHash`] of content that is read in parts.
+pub(crate) struct ContentHasher(sha2::Sha256);
+
+impl ContentHasher {
+    pub(crate) fn new() -> Self {
+        Self(sha2::Digest::new())
+    }
+
+    pub(crate) fn update(&mut self, content: &[u8]) {
+        sha2::Digest::update(&mut self.0, content);
+    }
+
+    pub(crate) fn finalize(self) -> ContentHash {
+        ContentHash::Sha256(sha2::Digest::finalize(self.0).into())
+    }
+}
+
+/// Information about the plaintext content of a file in an archive, recorded when it was uploaded.
+#[derive(Debug, Clone, Default, Serialize, Deserialize, PartialEq, Eq)]
+pub struct ContentInfo {
+    /// Hash of the content, checked when the file is downloaded.
+    /// Only recorded if enabled with [`crate::Client::enable_content_hashes`].
+    pub hash: Option<ContentHash>,
+    /// MIME type of the content, e.g. `text/html`
+    pub content_type: Option<String>,
+}
+
+/// The entries of an archive, for the code shared by [`PublicArchive`] and [`PrivateArchive`],
+/// which only differ in how they refer to the content of their files.
+pub(crate) trait ArchiveEntries {
+    /// How the content of a file is referred to
+    type Content: Clone + PartialEq;
+
+    fn file_entries(&self) -> &BTreeMap<PathBuf, (Self::Content, Metadata)>;
+    fn file_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, (Self::Content, Metadata)>;
+    fn node_entries(&self) -> &BTreeMap<PathBuf, (NodeType, Metadata)>;
+    fn node_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, (NodeType, Metadata)>;
+    fn unix_attribute_entries(&self) -> &BTreeMap<PathBuf, UnixAttributes>;
+    fn unix_attribute_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, UnixAttributes>;
+    fn content_info_entries(&self) -> &BTreeMap<PathBuf, ContentInfo>;
+    fn content_info_entries_mut(&mut self) -> &mut BTreeMap<PathBuf, ContentInfo>;
+    /// Same as the inherent `set_unix_attributes` of the archive, which may not keep all the attributes.
+    fn set_unix_attributes(&mut self, path: PathBuf, attributes: UnixAttributes);
+}
+
 #[derive(Error, Debug, PartialEq, Eq)]
 pub enum RenameError {
     #[error("File not found in archive: {0}")]
@@ -108,6 +258,21 @@ pub enum UploadError {
     Serialization(#[from] rmp_serde::encode::Error),
     #[error("Failed to deserialize")]
     Deserialization(#[from] rmp_serde::decode::Error),
+    #[error("Upload incomplete: {0}")]
+    Incomplete(Box<UploadReport>),
+    #[error("File {0:?} differs from the archive that was paid for, the directory changed since it was paid for")]
+    PaidArchiveMismatch(PathBuf),
+}
+
+/// A downloaded file whose content does not match the hash recorded in its archive.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct HashMismatch {
+    /// Where the file was downloaded to, it is removed
+    pub path: PathBuf,
+    /// The hash recorded in the archive
+    pub expected: ContentHash,
+    /// The hash of the downloaded content
+    pub actual: ContentHash,
 }
 
 /// Errors that can occur during the download operation.
@@ -117,6 +282,17 @@ pub enum DownloadError {
     GetError(#[from] GetError),
     #[error("IO failure")]
     IoError(#[from] std::io::Error),
+    #[error(
+        "Content of {:?} does not match its recorded hash: expected {}, got {}",
+        .0.path,
+        .0.expected,
+        .0.actual
+    )]
+    HashMismatch(Box<HashMismatch>),
+    #[error("Archive entry {0:?} is not a relative path inside the download directory")]
+    UnsafePath(PathBuf),
+    #[error("Symlink {link:?} points outside of the download directory, to {target:?}")]
+    UnsafeSymlink { link: PathBuf, target: PathBuf },
 }
 
 /// Errors that can occur during the file cost calculation.
@@ -0,0 +1,210 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::sync::{Arc, Mutex, MutexGuard};
+
+use ant_protocol::storage::DataTypes;
+use tokio::sync::oneshot;
+use xor_name::XorName;
+
+use super::STREAM_UPLOAD_WINDOW_SIZE;
+use crate::client::payment::{PayError, PaymentOption, Receipt};
+use crate::Client;
+
+type BatchResult = Result<(Receipt, usize), Arc<PayError>>;
+
+/// Pays for the chunks of the files uploaded together in a single payment, rather than for each window
+/// of each file on its own, so that uploading many small files doesn't take as many transactions.
+///
+/// Chunks submitted by the files being uploaded are paid for once they add up to [`STREAM_UPLOAD_WINDOW_SIZE`],
+/// or once every file being uploaded is waiting for its chunks to be paid for.
+pub(crate) struct PaymentBatcher {
+    client: Client,
+    payment_option: PaymentOption,
+    state: Mutex<BatchState>,
+}
+
+#[derive(Default)]
+struct BatchState {
+    /// Number of files being uploaded, which may submit chunks
+    active_files: usize,
+    /// Names and sizes of the chunks waiting to be paid for
+    pending: Vec<(XorName, usize)>,
+    /// The files waiting for their chunks to be paid for
+    waiters: Vec<(Vec<XorName>, oneshot::Sender<BatchResult>)>,
+}
+
+impl BatchState {
+    fn take_ready_batch(&mut self) -> Option<BatchState> {
+        let ready = self.pending.len() >= *STREAM_UPLOAD_WINDOW_SIZE
+            || (!self.waiters.is_empty() && self.waiters.len() >= self.active_files);
+        ready.then(|| BatchState {
+            active_files: 0,
+            pending: std::mem::take(&mut self.pending),
+            waiters: std::mem::take(&mut self.waiters),
+        })
+    }
+}
+
+/// Registration of a file being uploaded with a [`PaymentBatcher`], for as long as it is alive.
+pub(crate) struct ActiveFile<'a> {
+    batcher: &'a PaymentBatcher,
+}
+
+impl Drop for ActiveFile<'_> {
+    fn drop(&mut self) {
+        let batch = {
+            let mut state = self.batcher.lock_state();
+            state.active_files = state.active_files.saturating_sub(1);
+            state.take_ready_batch()
+        };
+        // the files left may all be waiting for this one
+        if let Some(batch) = batch {
+            self.batcher.pay_batch(batch);
+        }
+    }
+}
+
+impl PaymentBatcher {
+    pub(crate) fn new(client: &Client, payment_option: PaymentOption) -> Self {
+        Self {
+            client: client.clone(),
+            payment_option,
+            state: Default::default(),
+        }
+    }
+
+    /// Register a file being uploaded, the batch isn't paid for without its chunks while it is alive.
+    pub(crate) fn start_file(&self) -> ActiveFile<'_> {
+        self.lock_state().active_files += 1;
+        ActiveFile { batcher: self }
+    }
+
+    /// Pay for the given chunks of a registered file, along with the chunks of the other files.
+    ///
+    /// Returns the receipt for the given chunks and how many of them were already paid for.
+    pub(crate) async fn pay(
+        &self,
+        content_addrs: Vec<(XorName, usize)>,
+    ) -> Result<(Receipt, usize), PayError> {
+        // nothing to batch, the receipt already covers the chunks
+        if let PaymentOption::Receipt(receipt) = &self.payment_option {
+            return Ok((receipt.clone(), 0));
+        }
+
+        let (sender, receiver) = oneshot::channel();
+        let batch = {
+            let mut state = self.lock_state();
+            let names = content_addrs.iter().map(|(name, _)| *name).collect();
+            state.pending.extend(content_addrs);
+            state.waiters.push((names, sender));
+            state.take_ready_batch()
+        };
+        if let Some(batch) = batch {
+            self.pay_batch(batch);
+        }
+
+        match receiver.await {
+            Ok(Ok(paid)) => Ok(paid),
+            Ok(Err(err)) => Err(Arc::try_unwrap(err).unwrap_or_else(PayError::Shared)),
+            Err(_) => {
+                error!("The payment of a batch of chunks was aborted");
+                Err(PayError::BatchAborted)
+            }
+        }
+    }
+
+    /// Pay for a batch in the background, so that it goes through even if the file that completed it
+    /// stops waiting for it.
+    fn pay_batch(&self, batch: BatchState) {
+        let client = self.client.clone();
+        let payment_option = self.payment_option.clone();
+        tokio::spawn(async move {
+            debug!(
+                "Paying for a batch of {} chunks of {} files",
+                batch.pending.len(),
+                batch.waiters.len()
+            );
+            let result = client
+                .pay_for_content_addrs(DataTypes::Chunk, batch.pending.into_iter(), payment_option)
+                .await
+                .inspect_err(|err| error!("Error paying for a batch of chunks: {err:?}"));
+            let result = result.map_err(Arc::new);
+            for (names, sender) in batch.waiters {
+                let file_result = match &result {
+                    Ok((receipt, _)) => {
+                        let paid: Receipt = names
+                            .iter()
+                            .filter_map(|name| Some((*name, receipt.get(name)?.clone())))
+                            .collect();
+                        let already_paid = names
+                            .iter()
+                            .filter(|name| !receipt.contains_key(*name))
+                            .count();
+                        Ok((paid, already_paid))
+                    }
+                    Err(err) => Err(Arc::clone(err)),
+                };
+                // the file may have stopped waiting
+                let _ = sender.send(file_result);
+            }
+        });
+    }
+
+    fn lock_state(&self) -> MutexGuard<'_, BatchState> {
+        self.state
+            .lock()
+            .unwrap_or_else(|poisoned| poisoned.into_inner())
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn submit(state: &mut BatchState, chunks: usize) -> oneshot::Receiver<BatchResult> {
+        let (sender, receiver) = oneshot::channel();
+        let addrs: Vec<_> = (0..chunks)
+            .map(|_| (XorName::random(&mut rand::thread_rng()), 1))
+            .collect();
+        let names = addrs.iter().map(|(name, _)| *name).collect();
+        state.pending.extend(addrs);
+        state.waiters.push((names, sender));
+        receiver
+    }
+
+    #[test]
+    fn batch_waits_for_every_active_file() {
+        let mut state = BatchState {
+            active_files: 3,
+            ..Default::default()
+        };
+        let _first = submit(&mut state, 2);
+        assert!(state.take_ready_batch().is_none());
+        let _second = submit(&mut state, 2);
+        assert!(state.take_ready_batch().is_none());
+
+        // the third file is done without anything left to pay for
+        state.active_files -= 1;
+        let batch = state.take_ready_batch().unwrap();
+        assert_eq!(batch.pending.len(), 4);
+        assert_eq!(batch.waiters.len(), 2);
+        assert!(state.pending.is_empty() && state.waiters.is_empty());
+    }
+
+    #[test]
+    fn full_batch_is_paid_right_away() {
+        let mut state = BatchState {
+            active_files: 2,
+            ..Default::default()
+        };
+        let _first = submit(&mut state, *STREAM_UPLOAD_WINDOW_SIZE);
+        let batch = state.take_ready_batch().unwrap();
+        assert_eq!(batch.pending.len(), *STREAM_UPLOAD_WINDOW_SIZE);
+    }
+}
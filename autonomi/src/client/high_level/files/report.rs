@@ -0,0 +1,228 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::collections::BTreeMap;
+use std::fmt;
+use std::path::{Path, PathBuf};
+
+use super::UploadError;
+use crate::client::data_types::chunk::ChunkAddress;
+
+/// Outcome of the upload of every file of a directory, by path of the file on the local file system.
+///
+/// Only the files reported as [`FileUploadOutcome::Uploaded`] made it into the archive of the directory.
+#[derive(Debug, Default)]
+pub struct UploadReport {
+    files: BTreeMap<PathBuf, FileUploadOutcome>,
+}
+
+/// Outcome of the upload of a single file.
+#[derive(Debug)]
+pub enum FileUploadOutcome {
+    /// The file was uploaded and added to the archive
+    Uploaded {
+        /// Number of chunks that were uploaded or already existed
+        chunks: usize,
+    },
+    /// The file did not change since the previous archive, its entry was carried over to the new archive
+    Unchanged,
+    /// The file was not uploaded
+    Skipped(SkipReason),
+    /// The file could not be read or self-encrypted
+    EncryptionFailed(UploadError),
+    /// Some chunks of the file could not be paid for or uploaded
+    UploadFailed {
+        error: UploadError,
+        /// The chunks that could not be paid for or uploaded
+        chunks: Vec<ChunkAddress>,
+    },
+}
+
+/// Why a file was not uploaded.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SkipReason {
+    /// The entry is neither a regular file nor a directory, e.g. a socket or a device
+    NotAFile,
+}
+
+impl fmt::Display for SkipReason {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            SkipReason::NotAFile => write!(f, "not a regular file"),
+        }
+    }
+}
+
+impl fmt::Display for FileUploadOutcome {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            FileUploadOutcome::Uploaded { chunks } => write!(f, "uploaded ({chunks} chunks)"),
+            FileUploadOutcome::Unchanged => write!(f, "unchanged"),
+            FileUploadOutcome::Skipped(reason) => write!(f, "skipped: {reason}"),
+            FileUploadOutcome::EncryptionFailed(err) => write!(f, "encryption failed: {err}"),
+            FileUploadOutcome::UploadFailed { error, chunks } => {
+                write!(f, "upload failed for {} chunks: {error}", chunks.len())
+            }
+        }
+    }
+}
+
+impl UploadReport {
+    /// Create a new empty report
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    pub(crate) fn insert(&mut self, path: PathBuf, outcome: FileUploadOutcome) {
+        self.files.insert(path, outcome);
+    }
+
+    /// The outcome of every file, by path.
+    pub fn files(&self) -> &BTreeMap<PathBuf, FileUploadOutcome> {
+        &self.files
+    }
+
+    /// Returns true if every file was uploaded, or carried over unchanged.
+    pub fn is_complete(&self) -> bool {
+        self.files.values().all(|outcome| {
+            matches!(
+                outcome,
+                FileUploadOutcome::Uploaded { .. } | FileUploadOutcome::Unchanged
+            )
+        })
+    }
+
+    /// Returns true if any file failed to be encrypted or uploaded.
+    /// Skipped files are not failures.
+    pub fn has_failures(&self) -> bool {
+        self.failed().next().is_some()
+    }
+
+    /// The files that were uploaded.
+    pub fn uploaded(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, FileUploadOutcome::Uploaded { .. }))
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// The files that were carried over from the previous archive as they did not change.
+    pub fn unchanged(&self) -> impl Iterator<Item = &Path> {
+        self.files
+            .iter()
+            .filter(|(_, outcome)| matches!(outcome, FileUploadOutcome::Unchanged))
+            .map(|(path, _)| path.as_path())
+    }
+
+    /// The files that were skipped, with the reason why.
+    pub fn skipped(&self) -> impl Iterator<Item = (&Path, &SkipReason)> {
+        self.files
+            .iter()
+            .filter_map(|(path, outcome)| match outcome {
+                FileUploadOutcome::Skipped(reason) => Some((path.as_path(), reason)),
+                _ => None,
+            })
+    }
+
+    /// The files that failed to be encrypted or uploaded, with their outcome.
+    pub fn failed(&self) -> impl Iterator<Item = (&Path, &FileUploadOutcome)> {
+        self.files
+            .iter()
+            .filter(|(_, outcome)| {
+                matches!(
+                    outcome,
+                    FileUploadOutcome::EncryptionFailed(_) | FileUploadOutcome::UploadFailed { .. }
+                )
+            })
+            .map(|(path, outcome)| (path.as_path(), outcome))
+    }
+
+    /// Total number of chunks of the uploaded files.
+    pub fn chunks_uploaded(&self) -> usize {
+        self.files
+            .values()
+            .map(|outcome| match outcome {
+                FileUploadOutcome::Uploaded { chunks } => *chunks,
+                _ => 0,
+            })
+            .sum()
+    }
+}
+
+impl fmt::Display for UploadReport {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(
+            f,
+            "{} of {} files uploaded, ",
+            self.uploaded().count(),
+            self.files.len()
+        )?;
+        let unchanged = self.unchanged().count();
+        if unchanged > 0 {
+            write!(f, "{unchanged} unchanged, ")?;
+        }
+        write!(
+            f,
+            "{} skipped, {} failed",
+            self.skipped().count(),
+            self.failed().count()
+        )
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn report_outcomes() {
+        let mut report = UploadReport::new();
+        assert!(report.is_complete());
+
+        report.insert(
+            PathBuf::from("a"),
+            FileUploadOutcome::Uploaded { chunks: 3 },
+        );
+        report.insert(
+            PathBuf::from("b"),
+            FileUploadOutcome::Uploaded { chunks: 4 },
+        );
+        report.insert(PathBuf::from("e"), FileUploadOutcome::Unchanged);
+        assert!(report.is_complete());
+        assert_eq!(report.chunks_uploaded(), 7);
+        assert_eq!(report.unchanged().collect::<Vec<_>>(), [Path::new("e")]);
+
+        report.insert(
+            PathBuf::from("c"),
+            FileUploadOutcome::Skipped(SkipReason::NotAFile),
+        );
+        assert!(!report.is_complete());
+        assert!(!report.has_failures());
+
+        report.insert(
+            PathBuf::from("d"),
+            FileUploadOutcome::EncryptionFailed(UploadError::IoError(std::io::Error::other(
+                "unreadable",
+            ))),
+        );
+        assert!(report.has_failures());
+        assert_eq!(
+            report.uploaded().collect::<Vec<_>>(),
+            [Path::new("a"), Path::new("b")]
+        );
+        assert_eq!(
+            report.skipped().collect::<Vec<_>>(),
+            [(Path::new("c"), &SkipReason::NotAFile)]
+        );
+        assert_eq!(report.failed().count(), 1);
+        assert_eq!(
+            report.to_string(),
+            "2 of 5 files uploaded, 1 unchanged, 1 skipped, 1 failed"
+        );
+    }
+}
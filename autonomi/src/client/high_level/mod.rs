@@ -10,6 +10,15 @@ pub mod data;
 pub mod files;
 pub mod vault;
 
+/// Encrypted data of any size that can be overwritten or appended to, see [`crate::Client::mutable_blob_create`].
+pub mod mutable_blob;
+
+/// Scratchpads readable by a list of readers on top of their owner, see [`crate::Client::shared_scratchpad_create`].
+pub mod shared_scratchpad;
+
+/// Subscriptions to changes of pointers, scratchpads and registers, see [`crate::Client::watch`].
+pub mod watch;
+
 /// Registers are a mutable piece of data on the Network.
 /// They can be read by anyone and updated only by the register owner.
 /// Each entry is signed by the owner and all value history is kept on the Network.
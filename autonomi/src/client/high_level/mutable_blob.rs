@@ -0,0 +1,603 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Mutable blobs hold encrypted data of any size that can be overwritten or appended to.
+//!
+//! The data is split over as many [`Scratchpad`]s as needed, each one encrypted with its own derived key.
+//! The scratchpads are claimed in batches by a chain of [`GraphEntry`]s starting at a root entry derived
+//! from the blob key: the first descendant of each entry points to the next entry in the chain, and the
+//! others to the scratchpads. Scratchpads are only paid for when first written to.
+//!
+//! ```ignore
+//! [root GraphEntry] -> [GraphEntry] -> [GraphEntry] -> ...
+//!    |  |  |             |  |  |
+//!   [Scratchpads]       [Scratchpads]
+//! ```
+//!
+//! The content ends at the first scratchpad that is not full. Vaults are mutable blobs keyed by the vault key.
+
+use crate::client::data_types::scratchpad::ScratchpadError;
+use crate::client::high_level::files::FILE_UPLOAD_BATCH_SIZE;
+use crate::client::key_derivation::{DerivationIndex, MainPubkey, MainSecretKey};
+use crate::client::payment::PaymentOption;
+use crate::client::quote::CostError;
+use crate::client::utils::process_tasks_with_max_concurrency;
+use crate::client::Client;
+use crate::graph::GraphError;
+use ant_evm::{AttoTokens, U256};
+use ant_networking::{GetRecordError, NetworkError};
+use ant_protocol::storage::{
+    GraphContent, GraphEntry, GraphEntryAddress, Scratchpad, ScratchpadAddress,
+};
+use ant_protocol::Bytes;
+use bls::{PublicKey, SecretKey};
+use tracing::info;
+use xor_name::XorName;
+
+/// Defines the max size of content can be written into per ScratchPad
+pub(crate) const MAX_CONTENT_PER_SCRATCHPAD: usize = Scratchpad::MAX_SIZE - 1024;
+
+/// Defines the max number of Scratchpads that one GraphEntry can point to
+/// The current value is assuming GraphEntry max_size to be 100KB.
+const NUM_OF_SCRATCHPADS_PER_GRAPHENTRY: usize = 1_000;
+
+/// Hard coded derivation index for the blob's root GraphEntry.
+/// Derive the blob's main secret/public key by it to get the root GraphEntry owner/address
+const BLOB_HEAD_DERIVATION_INDEX: [u8; 32] = [0; 32];
+
+/// Data encoding of the scratchpads of mutable blobs, vaults use their [`crate::vault::VaultContentType`] instead
+const MUTABLE_BLOB_DATA_ENCODING: u64 = 0x626c6f62;
+
+#[derive(Debug, thiserror::Error)]
+pub enum MutableBlobError {
+    #[error("Mutable blob Scratchpad related error: {0}")]
+    Scratchpad(#[from] ScratchpadError),
+    #[error("Mutable blob GraphEntry related error: {0}")]
+    GraphEntry(#[from] GraphError),
+    #[error("Mutable blob Cost related error: {0}")]
+    Cost(#[from] CostError),
+    #[error("Protocol: {0}")]
+    Protocol(#[from] ant_protocol::Error),
+    #[error("Mutable blob doesn't have enough graph descendants: {0}")]
+    NotEnoughGraphDescendants(String),
+}
+
+impl Client {
+    /// Create the key of a mutable blob from a SecretKey and a name.
+    ///
+    /// This allows an app to keep any number of independent blobs under a single main key.
+    pub fn mutable_blob_key_from_name(owner: &SecretKey, name: &str) -> SecretKey {
+        let main_key = MainSecretKey::new(owner.clone());
+        let derivation_index = DerivationIndex::from_bytes(
+            XorName::from_content(format!("mutable_blob/{name}").as_bytes()).0,
+        );
+        main_key.derive_key(&derivation_index).into()
+    }
+
+    /// Get the cost of creating a new mutable blob of at most `max_size` bytes.
+    ///
+    /// Returns zero if the blob already exists, even if growing it would require more space.
+    pub async fn mutable_blob_cost(
+        &self,
+        owner: &PublicKey,
+        max_size: u64,
+    ) -> Result<AttoTokens, MutableBlobError> {
+        self.blob_cost(&MainPubkey::new(*owner), max_size).await
+    }
+
+    /// Check if a mutable blob exists on the Network.
+    pub async fn mutable_blob_check_existance(
+        &self,
+        owner: &PublicKey,
+    ) -> Result<bool, MutableBlobError> {
+        let public_key = MainPubkey::new(*owner)
+            .derive_key(&DerivationIndex::from_bytes(BLOB_HEAD_DERIVATION_INDEX));
+        let root_addr = GraphEntryAddress::new(public_key.into());
+        Ok(self.graph_entry_check_existance(&root_addr).await?)
+    }
+
+    /// Create a new mutable blob with the given content.
+    ///
+    /// Fails with [`GraphError::AlreadyExists`] if the blob already exists, use
+    /// [`Client::mutable_blob_overwrite`] to change its content.
+    pub async fn mutable_blob_create(
+        &self,
+        owner: &SecretKey,
+        data: Bytes,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, MutableBlobError> {
+        let main_secret_key = MainSecretKey::new(owner.clone());
+        let root_addr = blob_root_address(&main_secret_key);
+        if self.graph_entry_check_existance(&root_addr).await? {
+            return Err(GraphError::AlreadyExists(root_addr).into());
+        }
+        info!("Creating mutable blob at {root_addr:?}");
+        self.blob_write(
+            &main_secret_key,
+            data,
+            MUTABLE_BLOB_DATA_ENCODING,
+            0,
+            payment_option,
+        )
+        .await
+    }
+
+    /// Fetch and decrypt the content of a mutable blob.
+    pub async fn mutable_blob_get(&self, owner: &SecretKey) -> Result<Bytes, MutableBlobError> {
+        let (data, _) = self.blob_fetch(&MainSecretKey::new(owner.clone())).await?;
+        Ok(data)
+    }
+
+    /// Replace the content of a mutable blob, creating it if it does not exist.
+    ///
+    /// Space is claimed and paid for as the blob grows, it is never released when the blob shrinks.
+    pub async fn mutable_blob_overwrite(
+        &self,
+        owner: &SecretKey,
+        data: Bytes,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, MutableBlobError> {
+        self.blob_write(
+            &MainSecretKey::new(owner.clone()),
+            data,
+            MUTABLE_BLOB_DATA_ENCODING,
+            0,
+            payment_option,
+        )
+        .await
+    }
+
+    /// Append data to the content of an existing mutable blob.
+    ///
+    /// Only the last scratchpad of the current content is decrypted, it is rewritten along with the new ones.
+    pub async fn mutable_blob_append(
+        &self,
+        owner: &SecretKey,
+        data: Bytes,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, MutableBlobError> {
+        let main_secret_key = MainSecretKey::new(owner.clone());
+        let (_, scratchpad_derivations) = self
+            .blob_claimed_capacity(
+                &main_secret_key,
+                DerivationIndex::from_bytes(BLOB_HEAD_DERIVATION_INDEX),
+            )
+            .await?;
+        if scratchpad_derivations.is_empty() {
+            return Err(GraphError::Network(NetworkError::GetRecordError(
+                GetRecordError::RecordNotFound,
+            ))
+            .into());
+        }
+        let (last_part, last_content) = self
+            .blob_last_part(&main_secret_key, &scratchpad_derivations)
+            .await?;
+        let content = Bytes::from([last_content, data].concat());
+        self.blob_write(
+            &main_secret_key,
+            content,
+            MUTABLE_BLOB_DATA_ENCODING,
+            last_part,
+            payment_option,
+        )
+        .await
+    }
+
+    /// Fetch and decrypt a blob, returning its content along with the data encoding of its scratchpads.
+    pub(crate) async fn blob_fetch(
+        &self,
+        main_secret_key: &MainSecretKey,
+    ) -> Result<(Bytes, u64), MutableBlobError> {
+        let mut cur_graph_entry_addr = blob_root_address(main_secret_key);
+        let mut decrypted_full_text = vec![];
+        let mut content_type = 0;
+        let mut has_end_reached = false;
+
+        while !has_end_reached {
+            let graph_entry = self.graph_entry_get(&cur_graph_entry_addr).await?;
+
+            // The first descendant is reserved for `expand GraphEntry`.
+            match graph_entry.descendants.split_first() {
+                Some((&(first, _), rest)) => {
+                    cur_graph_entry_addr = GraphEntryAddress::new(first);
+                    let scratchpad_addresses = rest.to_vec();
+
+                    let (decrypt_data, cur_content_type, is_end_reached) = self
+                        .fetch_scratchpads_of_one_graph_entry_and_decrypt(
+                            main_secret_key,
+                            scratchpad_addresses,
+                        )
+                        .await?;
+                    decrypted_full_text.push(decrypt_data);
+                    content_type = cur_content_type;
+                    has_end_reached = is_end_reached;
+                }
+                None => {
+                    let msg = format!(
+                        "GraphEntry at {cur_graph_entry_addr:?} only has {} descendants.",
+                        graph_entry.descendants.len()
+                    );
+                    return Err(MutableBlobError::NotEnoughGraphDescendants(msg));
+                }
+            }
+        }
+
+        debug!("blob data is successfully fetched and decrypted");
+        Ok((Bytes::from(decrypted_full_text.concat()), content_type))
+    }
+
+    /// A quick estimation of cost:
+    ///   num_of_graph_entry * graph_entry_cost + num_of_scratchpad * scratchpad_cost
+    pub(crate) async fn blob_cost(
+        &self,
+        main_pubkey: &MainPubkey,
+        max_size: u64,
+    ) -> Result<AttoTokens, MutableBlobError> {
+        let public_key =
+            main_pubkey.derive_key(&DerivationIndex::from_bytes(BLOB_HEAD_DERIVATION_INDEX));
+        let graph_entry_cost = self.graph_entry_cost(&public_key.into()).await?;
+        if graph_entry_cost.is_zero() {
+            // Has been created, assuming all Scratchpads have been created and paid
+            Ok(graph_entry_cost)
+        } else {
+            let scratchpad_cost = self.scratchpad_cost(&public_key.into()).await?;
+
+            let num_of_scratchpads = max_size / MAX_CONTENT_PER_SCRATCHPAD as u64 + 1;
+            let num_of_graph_entry =
+                num_of_scratchpads / NUM_OF_SCRATCHPADS_PER_GRAPHENTRY as u64 + 1;
+
+            let total_cost = U256::from(num_of_graph_entry) * graph_entry_cost.as_atto()
+                + U256::from(num_of_scratchpads) * scratchpad_cost.as_atto();
+            Ok(AttoTokens::from_atto(total_cost))
+        }
+    }
+
+    /// Write the content of a blob from its `first_part` scratchpad on, the scratchpads before it are left unchanged.
+    ///
+    /// Dynamically expand the blob capacity by paying for more space (Scratchpad) when needed.
+    pub(crate) async fn blob_write(
+        &self,
+        main_secret_key: &MainSecretKey,
+        data: Bytes,
+        content_type: u64,
+        first_part: usize,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, MutableBlobError> {
+        info!("Writing {} bytes to blob ...", data.len());
+        let mut total_cost = AttoTokens::zero();
+
+        // scratchpad_derivations ordered by the collection order
+        let (mut cur_free_graphentry_derivation, mut scratchpad_derivations) = self
+            .blob_claimed_capacity(
+                main_secret_key,
+                DerivationIndex::from_bytes(BLOB_HEAD_DERIVATION_INDEX),
+            )
+            .await?;
+
+        let contents = split_bytes(data);
+
+        let required = first_part + contents.len();
+
+        info!(
+            "Current capacity is {}, meanwhile requiring {required}",
+            scratchpad_derivations.len(),
+        );
+
+        // claim more capacity if short of.
+        // Note: as the Scratchpad is `created on use`, hence during the `claim stage`,
+        //       NUM_OF_SCRATCHPADS_PER_GRAPHENTRY to be claimed in one newly created GraphEntry.
+        while scratchpad_derivations.len() < required {
+            let (new_free_graphentry_derivation, new_scratchpad_derivations, graph_cost) = self
+                .expand_capacity(
+                    main_secret_key,
+                    &cur_free_graphentry_derivation,
+                    payment_option.clone(),
+                )
+                .await?;
+            cur_free_graphentry_derivation = new_free_graphentry_derivation;
+            scratchpad_derivations.extend(&new_scratchpad_derivations);
+            total_cost = AttoTokens::from_atto(total_cost.as_atto() + graph_cost.as_atto());
+        }
+
+        // Convert to Vec of futures
+        let update_futures: Vec<_> = contents
+            .into_iter()
+            .enumerate()
+            .map(|(i, content)| {
+                let sp_secret_key = main_secret_key.derive_key(&DerivationIndex::from_bytes(
+                    scratchpad_derivations[first_part + i].1,
+                ));
+                let client = self.clone();
+                let payment_option_clone = payment_option.clone();
+
+                async move {
+                    let target_addr = ScratchpadAddress::new(sp_secret_key.public_key().into());
+                    let already_exists = self.scratchpad_check_existance(&target_addr).await?;
+
+                    if already_exists {
+                        info!(
+                            "Updating Scratchpad at {target_addr:?} with content of {} bytes",
+                            content.len()
+                        );
+                        match client
+                            .scratchpad_update(&sp_secret_key.clone().into(), content_type, &content)
+                            .await
+                        {
+                            Ok(()) => {
+                                info!(
+                                    "Updated Scratchpad at {target_addr:?} with content of {} bytes",
+                                    content.len()
+                                );
+                                Ok(None)
+                            }
+                            Err(err) => Err(err.into()),
+                        }
+                    } else {
+                        info!("Creating Scratchpad at {target_addr:?}");
+                        let (price, addr) = client
+                            .scratchpad_create(
+                                &sp_secret_key.into(),
+                                content_type,
+                                &content,
+                                payment_option_clone,
+                            )
+                            .await?;
+                        info!("Created Scratchpad at {addr:?} with cost of {price:?}");
+                        Ok(Some(price))
+                    }
+                }
+            })
+            .collect();
+
+        let update_results =
+            process_tasks_with_max_concurrency(update_futures, *FILE_UPLOAD_BATCH_SIZE).await;
+
+        // Process results
+        for result in update_results {
+            match result {
+                Ok(Some(price)) => {
+                    total_cost = AttoTokens::from_atto(total_cost.as_atto() + price.as_atto());
+                }
+                Ok(None) => (),
+                Err(e) => return Err(e),
+            }
+        }
+
+        Ok(total_cost)
+    }
+
+    // Expand the capacity, i.e. upload one GraphEntry
+    // The returned value is:
+    //   * cur_free_graphentry_derivation: the output[0] of the tail of the linked GraphEntry
+    //   * scratchpad_derivations: ordered by the creating order
+    //   * graph_cost: cost paid to upload the GraphEntry
+    async fn expand_capacity(
+        &self,
+        main_secret_key: &MainSecretKey,
+        cur_graphentry_derivation: &DerivationIndex,
+        payment_option: PaymentOption,
+    ) -> Result<(DerivationIndex, Vec<(PublicKey, GraphContent)>, AttoTokens), MutableBlobError>
+    {
+        let own_secret_key = main_secret_key.derive_key(cur_graphentry_derivation);
+
+        // For blobs, doesn't need the backward poining. i.e. one-direction link shall be enough.
+        let parents = vec![];
+        // For blobs, doesn't need this field to be populated.
+        let initial_value = [0u8; 32];
+
+        // Poining to the next GraphEntry
+        let new_graphentry_derivation = DerivationIndex::random(&mut rand::thread_rng());
+        let public_key: PublicKey = main_secret_key
+            .derive_key(&new_graphentry_derivation)
+            .public_key()
+            .into();
+        let mut descendants = vec![(public_key, new_graphentry_derivation.into_bytes())];
+
+        // Pointing to other future Scrachpads
+        descendants.extend((0..NUM_OF_SCRATCHPADS_PER_GRAPHENTRY).map(|_| {
+            let derivation_index = DerivationIndex::random(&mut rand::thread_rng());
+            let public_key: PublicKey = main_secret_key
+                .derive_key(&derivation_index)
+                .public_key()
+                .into();
+            (public_key, derivation_index.into_bytes())
+        }));
+
+        let graph_entry = GraphEntry::new(
+            &own_secret_key.into(),
+            parents,
+            initial_value,
+            descendants.clone(),
+        );
+
+        // Upload the GraphEntry
+        let (graph_cost, _addr) = self.graph_entry_put(graph_entry, payment_option).await?;
+
+        let scratchpad_derivations = descendants.split_off(1);
+        Ok((
+            new_graphentry_derivation,
+            scratchpad_derivations,
+            graph_cost,
+        ))
+    }
+
+    // Collects the current claimed capacity (i.e. the uploaded `GrapthEntry`s)
+    // The returned value is:
+    //   * cur_free_graphentry_derivation: i.e. the root if no graph_entry uploaded,
+    //       otherwise, the first un-used one (the output[0] of the tail of the linked GraphEntry)
+    //   * scratchpad_derivations: ordered by the collection order
+    async fn blob_claimed_capacity(
+        &self,
+        main_secret_key: &MainSecretKey,
+        mut cur_free_graphentry_derivation: DerivationIndex,
+    ) -> Result<(DerivationIndex, Vec<(PublicKey, GraphContent)>), MutableBlobError> {
+        let mut scratchpad_derivations = vec![];
+        loop {
+            let public_key = main_secret_key
+                .derive_key(&cur_free_graphentry_derivation)
+                .public_key();
+            let cur_graph_entry_addr = GraphEntryAddress::new(public_key.into());
+
+            match self.graph_entry_get(&cur_graph_entry_addr).await {
+                Ok(entry) => {
+                    // A GraphEntry was created with all NUM_OF_SCRATCHPADS_PER_GRAPHENTRY
+                    // scratchpad claimed:
+                    //   * the first descendant pointing to next GraphEntry.
+                    //   * other descendants pointing to Scratchpads for content.
+                    if entry.descendants.len() <= NUM_OF_SCRATCHPADS_PER_GRAPHENTRY {
+                        let msg = format!(
+                            "GraphEntry at {cur_graph_entry_addr:?} only has {} descendants.",
+                            entry.descendants.len()
+                        );
+                        return Err(MutableBlobError::NotEnoughGraphDescendants(msg));
+                    }
+                    cur_free_graphentry_derivation =
+                        DerivationIndex::from_bytes(entry.descendants[0].1);
+                    scratchpad_derivations.extend(&entry.descendants[1..]);
+                }
+                Err(GraphError::Network(NetworkError::GetRecordError(
+                    GetRecordError::RecordNotFound,
+                ))) => {
+                    // GraphEntry not existed, return the current snapshot.
+                    info!(
+                        "blob capacity is successfully fetched, with {} scratchpads",
+                        scratchpad_derivations.len()
+                    );
+                    return Ok((cur_free_graphentry_derivation, scratchpad_derivations));
+                }
+                Err(err) => {
+                    return Err(err.into());
+                }
+            }
+        }
+    }
+
+    // Find the last part of the blob content, i.e. its first scratchpad that is not full.
+    // The returned value is the index of that scratchpad along with its decrypted content.
+    async fn blob_last_part(
+        &self,
+        main_secret_key: &MainSecretKey,
+        scratchpad_derivations: &[(PublicKey, GraphContent)],
+    ) -> Result<(usize, Bytes), MutableBlobError> {
+        // The encryption adds a fixed size overhead, full scratchpads are told apart by their size
+        // without decrypting them.
+        let full_encrypted_size = SecretKey::random()
+            .public_key()
+            .encrypt(b"")
+            .to_bytes()
+            .len()
+            + MAX_CONTENT_PER_SCRATCHPAD;
+        for (i, (pub_key, derive_bytes)) in scratchpad_derivations.iter().enumerate() {
+            let sp = self
+                .scratchpad_get(&ScratchpadAddress::new(*pub_key))
+                .await?;
+            if sp.encrypted_data().len() < full_encrypted_size {
+                let secret_key =
+                    main_secret_key.derive_key(&DerivationIndex::from_bytes(*derive_bytes));
+                return Ok((i, sp.decrypt_data(&secret_key.into())?));
+            }
+        }
+        let msg = format!(
+            "None of the {} scratchpads of the blob ends its content.",
+            scratchpad_derivations.len()
+        );
+        Err(MutableBlobError::NotEnoughGraphDescendants(msg))
+    }
+
+    async fn fetch_scratchpads_of_one_graph_entry_and_decrypt(
+        &self,
+        main_secret_key: &MainSecretKey,
+        scratchpad_addresses: Vec<(PublicKey, [u8; 32])>,
+    ) -> Result<(Bytes, u64, bool), MutableBlobError> {
+        let mut decrypted_full_text = vec![];
+        let mut content_type = 0;
+        let mut has_end_reached = false;
+        // Any non-max-sized ScratchPad indicates the end-of-blob-content.
+        for (pub_key, derive_bytes) in scratchpad_addresses {
+            let addr = ScratchpadAddress::new(pub_key);
+            let secret_key = main_secret_key.derive_key(&DerivationIndex::from_bytes(derive_bytes));
+
+            let sp = self.scratchpad_get(&addr).await?;
+            content_type = sp.data_encoding();
+            let decrypt_data = sp.decrypt_data(&secret_key.into())?;
+            // the encrypted data is larger than the content, only the content size tells a full scratchpad
+            let is_full = decrypt_data.len() >= MAX_CONTENT_PER_SCRATCHPAD;
+            decrypted_full_text.push(decrypt_data);
+            if !is_full {
+                has_end_reached = true;
+                break;
+            }
+        }
+
+        Ok((
+            Bytes::from(decrypted_full_text.concat()),
+            content_type,
+            has_end_reached,
+        ))
+    }
+}
+
+fn blob_root_address(main_secret_key: &MainSecretKey) -> GraphEntryAddress {
+    let public_key = main_secret_key
+        .derive_key(&DerivationIndex::from_bytes(BLOB_HEAD_DERIVATION_INDEX))
+        .public_key();
+    GraphEntryAddress::new(public_key.into())
+}
+
+/// Split the content in scratchpad sized parts.
+///
+/// The last part is never full so that readers know where the content ends: when the content
+/// fills its last part, an empty part is added after it.
+fn split_bytes(input: Bytes) -> Vec<Bytes> {
+    let mut contents = Vec::new();
+    let mut offset = 0;
+
+    while offset < input.len() {
+        let end = (offset + MAX_CONTENT_PER_SCRATCHPAD).min(input.len());
+        contents.push(input.slice(offset..end));
+        offset = end;
+    }
+    match contents.last() {
+        Some(last) if last.len() < MAX_CONTENT_PER_SCRATCHPAD => {}
+        _ => contents.push(Bytes::new()),
+    }
+
+    contents
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn split_bytes_always_ends_with_a_partial_part() {
+        let sizes = |len: usize| -> Vec<usize> {
+            split_bytes(Bytes::from(vec![7; len]))
+                .iter()
+                .map(|part| part.len())
+                .collect()
+        };
+        let max = MAX_CONTENT_PER_SCRATCHPAD;
+        assert_eq!(sizes(0), vec![0]);
+        assert_eq!(sizes(10), vec![10]);
+        assert_eq!(sizes(max), vec![max, 0]);
+        assert_eq!(sizes(2 * max + 1), vec![max, max, 1]);
+
+        let input = Bytes::from((0..3 * max).map(|i| i as u8).collect::<Vec<_>>());
+        assert_eq!(split_bytes(input.clone()).concat(), input.to_vec());
+    }
+
+    #[test]
+    fn blob_keys_from_names() {
+        let owner = SecretKey::random();
+        let key = Client::mutable_blob_key_from_name(&owner, "state");
+        assert_eq!(key, Client::mutable_blob_key_from_name(&owner, "state"));
+        assert_ne!(key, Client::mutable_blob_key_from_name(&owner, "other"));
+        assert_ne!(key, Client::register_key_from_name(&owner, "state"));
+    }
+}
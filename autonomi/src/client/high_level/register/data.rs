@@ -0,0 +1,254 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+
+use crate::client::data_types::chunk::{ChunkAddress, DataMapChunk};
+use crate::client::high_level::data::DataAddress;
+use crate::client::high_level::register::{
+    RegisterAddress, RegisterError, RegisterValue, SecretKey,
+};
+use crate::client::key_derivation::{DerivationIndex, MainSecretKey};
+use crate::client::payment::PaymentOption;
+use crate::client::Client;
+use crate::{AttoTokens, Chunk};
+
+/// Hard coded derivation index for the key encrypting the private data of a register
+const REGISTER_DATA_KEY_DERIVATION_INDEX: [u8; 32] = [1; 32];
+
+/// Prefix of the encrypted data maps, checked on decryption to detect a wrong data key,
+/// which would otherwise decrypt to garbage
+const DATA_MAP_MAGIC: &[u8] = b"autonomi register data map v0";
+
+/// How the data stored in a register with [`Client::register_update_data`] is uploaded.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum RegisterDataVisibility {
+    /// The data is uploaded as public data, readable by anyone knowing the register address
+    Public,
+    /// The data is uploaded as private data, whose data map is encrypted with the register data key,
+    /// see [`Client::register_data_key`]
+    Private,
+}
+
+/// The reference to the data of a register entry, stored in a chunk whose address is the value of the entry.
+#[derive(Debug, Clone, Serialize, Deserialize)]
+enum RegisterDataRef {
+    /// Public data at this address
+    Public(DataAddress),
+    /// Private data, whose data map chunk is encrypted with the register data key
+    Private(Bytes),
+}
+
+/// This type essentially wraps the reference in a version marker, so the format can evolve.
+#[derive(Serialize, Deserialize)]
+enum RegisterDataRefVersioned {
+    V0(RegisterDataRef),
+}
+
+impl Client {
+    /// The key encrypting the private data of the register owned by `owner`.
+    ///
+    /// This key only gives read access to the private data of the register: share it with readers
+    /// instead of the owner key, which also allows updating the register.
+    pub fn register_data_key(owner: &SecretKey) -> SecretKey {
+        MainSecretKey::new(owner.clone())
+            .derive_key(&DerivationIndex::from_bytes(
+                REGISTER_DATA_KEY_DERIVATION_INDEX,
+            ))
+            .into()
+    }
+
+    /// Create a new register holding data of any size.
+    ///
+    /// The data is uploaded as self-encrypted data, and the register entry records a reference to it.
+    /// Registers holding data should only be read with [`Client::register_get_data`] or [`super::RegisterHistory::next_data`].
+    pub async fn register_create_data(
+        &self,
+        owner: &SecretKey,
+        data: Bytes,
+        visibility: RegisterDataVisibility,
+        payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, RegisterAddress), RegisterError> {
+        let (data_cost, value) = self
+            .register_put_data(owner, data, visibility, payment_option.clone())
+            .await?;
+        let (register_cost, addr) = self.register_create(owner, value, payment_option).await?;
+        let total_cost = data_cost
+            .checked_add(register_cost)
+            .ok_or(RegisterError::InvalidCost)?;
+        Ok((total_cost, addr))
+    }
+
+    /// Update a register created with [`Client::register_create_data`] with data of any size.
+    pub async fn register_update_data(
+        &self,
+        owner: &SecretKey,
+        data: Bytes,
+        visibility: RegisterDataVisibility,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, RegisterError> {
+        let (data_cost, value) = self
+            .register_put_data(owner, data, visibility, payment_option.clone())
+            .await?;
+        let register_cost = self.register_update(owner, value, payment_option).await?;
+        data_cost
+            .checked_add(register_cost)
+            .ok_or(RegisterError::InvalidCost)
+    }
+
+    /// Get the current data of a register created with [`Client::register_create_data`].
+    ///
+    /// The `data_key` is needed to read private data, see [`Client::register_data_key`].
+    pub async fn register_get_data(
+        &self,
+        addr: &RegisterAddress,
+        data_key: Option<&SecretKey>,
+    ) -> Result<Bytes, RegisterError> {
+        let value = self.register_get(addr).await?;
+        self.register_resolve_data(&value, data_key).await
+    }
+
+    /// Fetch the data referred to by the value of a register entry.
+    pub(crate) async fn register_resolve_data(
+        &self,
+        value: &RegisterValue,
+        data_key: Option<&SecretKey>,
+    ) -> Result<Bytes, RegisterError> {
+        let (_, data) = self
+            .register_resolve_data_and_address(value, data_key)
+            .await?;
+        Ok(data)
+    }
+
+    /// Fetch the data referred to by the value of a register entry, along with its address if the data is public.
+    pub(crate) async fn register_resolve_data_and_address(
+        &self,
+        value: &RegisterValue,
+        data_key: Option<&SecretKey>,
+    ) -> Result<(Option<DataAddress>, Bytes), RegisterError> {
+        match self.register_get_data_ref(value).await? {
+            RegisterDataRef::Public(data_addr) => {
+                let data = self.data_get_public(&data_addr).await?;
+                Ok((Some(data_addr), data))
+            }
+            RegisterDataRef::Private(encrypted_data_map) => {
+                let data_key = data_key.ok_or(RegisterError::MissingDataKey)?;
+                let data_map = decrypt_data_map(data_key, &encrypted_data_map)?;
+                Ok((None, self.data_get(&data_map).await?))
+            }
+        }
+    }
+
+    /// The address of the data referred to by the value of a register entry, `None` if the data is private.
+    pub(crate) async fn register_data_address(
+        &self,
+        value: &RegisterValue,
+    ) -> Result<Option<DataAddress>, RegisterError> {
+        match self.register_get_data_ref(value).await? {
+            RegisterDataRef::Public(data_addr) => Ok(Some(data_addr)),
+            RegisterDataRef::Private(_) => Ok(None),
+        }
+    }
+
+    /// Fetch the reference stored in the chunk whose address is the value of a register entry.
+    async fn register_get_data_ref(
+        &self,
+        value: &RegisterValue,
+    ) -> Result<RegisterDataRef, RegisterError> {
+        let reference_addr = ChunkAddress::new(xor_name::XorName(*value));
+        debug!("Getting register data reference at {reference_addr:?}");
+        let chunk = self.chunk_get(&reference_addr).await?;
+        let RegisterDataRefVersioned::V0(reference) = rmp_serde::from_slice(chunk.value())
+            .map_err(|err| {
+                RegisterError::InvalidDataReference(format!(
+                    "Failed to deserialize reference at {reference_addr:?}: {err}"
+                ))
+            })?;
+        Ok(reference)
+    }
+
+    /// Upload the data and its reference, returning the register value pointing to the reference.
+    async fn register_put_data(
+        &self,
+        owner: &SecretKey,
+        data: Bytes,
+        visibility: RegisterDataVisibility,
+        payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, RegisterValue), RegisterError> {
+        let (data_cost, reference) = match visibility {
+            RegisterDataVisibility::Public => {
+                let (cost, data_addr) = self.data_put_public(data, payment_option.clone()).await?;
+                (cost, RegisterDataRef::Public(data_addr))
+            }
+            RegisterDataVisibility::Private => {
+                let (cost, data_map) = self.data_put(data, payment_option.clone()).await?;
+                let data_key = Self::register_data_key(owner);
+                let encrypted = encrypt_data_map(&data_key, &data_map);
+                (cost, RegisterDataRef::Private(encrypted))
+            }
+        };
+
+        let bytes = rmp_serde::to_vec_named(&RegisterDataRefVersioned::V0(reference))
+            .map_err(|err| RegisterError::InvalidDataReference(err.to_string()))?;
+        let (reference_cost, reference_addr) = self
+            .chunk_put(&Chunk::new(Bytes::from(bytes)), payment_option)
+            .await?;
+        debug!("Uploaded register data reference at {reference_addr:?}");
+
+        let total_cost = data_cost
+            .checked_add(reference_cost)
+            .ok_or(RegisterError::InvalidCost)?;
+        Ok((total_cost, reference_addr.xorname().0))
+    }
+}
+
+fn encrypt_data_map(data_key: &SecretKey, data_map: &DataMapChunk) -> Bytes {
+    let plaintext = [DATA_MAP_MAGIC, data_map.0.value()].concat();
+    Bytes::from(data_key.public_key().encrypt(plaintext).to_bytes())
+}
+
+fn decrypt_data_map(
+    data_key: &SecretKey,
+    encrypted_data_map: &[u8],
+) -> Result<DataMapChunk, RegisterError> {
+    let ciphertext = bls::Ciphertext::from_bytes(encrypted_data_map).map_err(|_| {
+        RegisterError::InvalidDataReference("Invalid encrypted data map".to_string())
+    })?;
+    let plaintext = data_key.decrypt(&ciphertext).ok_or_else(|| {
+        RegisterError::InvalidDataReference("Corrupt encrypted data map".to_string())
+    })?;
+    let data_map = plaintext
+        .strip_prefix(DATA_MAP_MAGIC)
+        .ok_or(RegisterError::WrongDataKey)?;
+    Ok(DataMapChunk::from(Chunk::new(Bytes::copy_from_slice(
+        data_map,
+    ))))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn data_map_encryption() {
+        let owner = SecretKey::random();
+        let data_key = Client::register_data_key(&owner);
+        assert_ne!(data_key.public_key(), owner.public_key());
+
+        let data_map = DataMapChunk::from(Chunk::new(Bytes::from_static(b"data map")));
+        let encrypted = encrypt_data_map(&data_key, &data_map);
+        let decrypted = decrypt_data_map(&data_key, &encrypted).unwrap();
+        assert_eq!(decrypted, data_map);
+
+        assert!(matches!(
+            decrypt_data_map(&owner, &encrypted),
+            Err(RegisterError::WrongDataKey)
+        ));
+    }
+}
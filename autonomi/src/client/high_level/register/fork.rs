@@ -0,0 +1,222 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::fmt;
+use std::sync::Arc;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use rand::RngCore;
+
+use crate::client::data_types::graph::{GraphEntry, GraphEntryAddress};
+use crate::client::high_level::register::{get_derivation_from_graph_entry, RegisterError};
+use crate::client::key_derivation::DerivationIndex;
+
+/// Marker in the first byte of a derivation index holding the time its entry was written
+const TIMESTAMP_MARKER: u8 = 0x7a;
+
+/// Bytes of the derivation index holding the timestamp, after the marker
+const TIMESTAMP_BYTES: std::ops::Range<usize> = 1..8;
+
+/// Callback choosing one of the forked heads of a register, returning its index in the given slice.
+pub type ForkResolver = dyn Fn(&[GraphEntry]) -> usize + Send + Sync;
+
+/// How to deal with a forked register, i.e. a register updated concurrently whose head has multiple entries.
+///
+/// On reads, the policy chooses the value returned. On updates, it chooses the head the new entry follows.
+/// The history continues from that head only, but the new entry records the losing heads by listing their
+/// address as parent, and their values can still be read at the forked address.
+#[derive(Clone, Default)]
+pub enum ForkResolution {
+    /// Return a [`RegisterError::Fork`] with the values of all the forked heads
+    Fail,
+    /// Choose the head with the smallest derivation index for its next entry
+    #[default]
+    LowestIndex,
+    /// Choose the head written last, according to the timestamp embedded in its entry, see [`entry_timestamp`].
+    /// Heads without a timestamp are considered older than any other, ties are resolved by [`ForkResolution::LowestIndex`]
+    LastWriterWins,
+    /// Let the caller choose among the forked heads, sorted by derivation index
+    Callback(Arc<ForkResolver>),
+}
+
+impl ForkResolution {
+    /// Create a policy letting `resolver` choose among the forked heads.
+    pub fn callback(resolver: impl Fn(&[GraphEntry]) -> usize + Send + Sync + 'static) -> Self {
+        Self::Callback(Arc::new(resolver))
+    }
+
+    /// Choose one of the forked `heads` found at `addr`.
+    pub(crate) fn resolve(
+        &self,
+        addr: &GraphEntryAddress,
+        mut heads: Vec<GraphEntry>,
+    ) -> Result<GraphEntry, RegisterError> {
+        if heads.len() == 1 {
+            return Ok(heads.remove(0));
+        }
+        let mut heads: Vec<(GraphEntry, DerivationIndex)> = heads
+            .into_iter()
+            .filter_map(|e| {
+                get_derivation_from_graph_entry(&e)
+                    .ok()
+                    .map(|derivation| (e, derivation))
+            })
+            .collect();
+        if heads.is_empty() {
+            return Err(RegisterError::Corrupt(format!(
+                "No valid descendants found for FORKED entry at {addr:?}"
+            )));
+        }
+        heads.sort_by_key(|(_, derivation)| *derivation);
+
+        let chosen = match self {
+            Self::Fail => {
+                let values = heads.iter().map(|(e, _)| e.content).collect();
+                return Err(RegisterError::Fork(values));
+            }
+            Self::LowestIndex => 0,
+            Self::LastWriterWins => {
+                // the first of the latest heads, max_by_key would return the last one
+                let latest = heads.iter().filter_map(|(e, _)| entry_timestamp(e)).max();
+                heads
+                    .iter()
+                    .position(|(e, _)| entry_timestamp(e) == latest)
+                    .unwrap_or(0)
+            }
+            Self::Callback(resolver) => {
+                let entries: Vec<GraphEntry> = heads.iter().map(|(e, _)| e.clone()).collect();
+                let chosen = resolver(&entries);
+                if chosen >= entries.len() {
+                    return Err(RegisterError::Corrupt(format!(
+                        "Fork resolver chose head {chosen} out of {} at {addr:?}",
+                        entries.len()
+                    )));
+                }
+                chosen
+            }
+        };
+        debug!("Resolved fork at {addr:?} with {self:?}, chose head {chosen}");
+        Ok(heads.swap_remove(chosen).0)
+    }
+}
+
+impl fmt::Debug for ForkResolution {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Self::Fail => write!(f, "Fail"),
+            Self::LowestIndex => write!(f, "LowestIndex"),
+            Self::LastWriterWins => write!(f, "LastWriterWins"),
+            Self::Callback(_) => write!(f, "Callback"),
+        }
+    }
+}
+
+/// Create the derivation index of the next entry of a register, embedding the current time.
+///
+/// The index is random except for its first bytes: a marker followed by the milliseconds since the
+/// UNIX epoch as a 56 bits big endian integer, so that indexes also sort by time.
+pub(crate) fn new_derivation_index() -> DerivationIndex {
+    let millis = SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0);
+    derivation_index_at(millis)
+}
+
+/// Create a random derivation index embedding the given time, see [`new_derivation_index`].
+pub(crate) fn derivation_index_at(millis: u64) -> DerivationIndex {
+    let mut bytes = [0u8; 32];
+    rand::thread_rng().fill_bytes(&mut bytes[TIMESTAMP_BYTES.end..]);
+    bytes[0] = TIMESTAMP_MARKER;
+    bytes[TIMESTAMP_BYTES].copy_from_slice(&millis.to_be_bytes()[1..]);
+    DerivationIndex::from_bytes(bytes)
+}
+
+/// The time a register entry was written, in milliseconds since the UNIX epoch.
+///
+/// Returns `None` for entries written by clients that did not embed a timestamp in their descendant.
+pub fn entry_timestamp(entry: &GraphEntry) -> Option<u64> {
+    let derivation = get_derivation_from_graph_entry(entry).ok()?;
+    let bytes = derivation.as_bytes();
+    if bytes[0] != TIMESTAMP_MARKER {
+        return None;
+    }
+    let mut millis = [0u8; 8];
+    millis[1..].copy_from_slice(&bytes[TIMESTAMP_BYTES]);
+    Some(u64::from_be_bytes(millis))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::key_derivation::MainSecretKey;
+    use crate::SecretKey;
+
+    fn head(owner: &SecretKey, value: u8, derivation: DerivationIndex) -> GraphEntry {
+        let next = MainSecretKey::new(owner.clone())
+            .public_key()
+            .derive_key(&derivation);
+        GraphEntry::new(
+            owner,
+            vec![],
+            [value; 32],
+            vec![(next.into(), derivation.into_bytes())],
+        )
+    }
+
+    fn timestamped(millis: u64, random: u8) -> DerivationIndex {
+        let mut bytes = [random; 32];
+        bytes[0] = TIMESTAMP_MARKER;
+        bytes[TIMESTAMP_BYTES].copy_from_slice(&millis.to_be_bytes()[1..]);
+        DerivationIndex::from_bytes(bytes)
+    }
+
+    #[test]
+    fn resolve_forks() {
+        let owner = SecretKey::random();
+        let addr = GraphEntryAddress::new(owner.public_key());
+        let heads = vec![
+            head(&owner, 1, timestamped(2000, 9)),
+            head(&owner, 2, DerivationIndex::from_bytes([0; 32])),
+            head(&owner, 3, timestamped(3000, 1)),
+            head(&owner, 4, timestamped(3000, 5)),
+        ];
+
+        let err = ForkResolution::Fail
+            .resolve(&addr, heads.clone())
+            .unwrap_err();
+        assert!(matches!(err, RegisterError::Fork(values) if values.len() == 4));
+
+        let lowest = ForkResolution::LowestIndex.resolve(&addr, heads.clone());
+        assert_eq!(lowest.unwrap().content, [2; 32]);
+
+        let latest = ForkResolution::LastWriterWins.resolve(&addr, heads.clone());
+        assert_eq!(latest.unwrap().content, [3; 32]);
+
+        let last = ForkResolution::callback(|heads| heads.len() - 1).resolve(&addr, heads.clone());
+        assert_eq!(last.unwrap().content, [4; 32]);
+
+        let out_of_range = ForkResolution::callback(|heads| heads.len()).resolve(&addr, heads);
+        assert!(matches!(out_of_range, Err(RegisterError::Corrupt(_))));
+    }
+
+    #[test]
+    fn timestamp_in_derivation_index() {
+        let owner = SecretKey::random();
+        let before = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .unwrap()
+            .as_millis() as u64;
+        let entry = head(&owner, 0, new_derivation_index());
+        let millis = entry_timestamp(&entry).unwrap();
+        assert!(millis >= before && millis < before + 60_000);
+
+        let untimed = head(&owner, 0, DerivationIndex::from_bytes([1; 32]));
+        assert_eq!(entry_timestamp(&untimed), None);
+    }
+}
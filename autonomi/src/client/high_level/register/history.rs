@@ -7,11 +7,14 @@
 // permissions and limitations relating to use of the SAFE Network Software.
 
 use ant_networking::{GetRecordError, NetworkError};
+use bytes::Bytes;
 
-use crate::client::data_types::graph::{GraphEntryAddress, GraphError};
+use crate::client::data_types::graph::{GraphEntry, GraphEntryAddress, GraphError};
 use crate::client::high_level::register::{
-    PublicKey, RegisterAddress, RegisterError, RegisterValue,
+    get_derivation_from_graph_entry, ForkResolution, PublicKey, RegisterAddress, RegisterError,
+    RegisterValue, SecretKey,
 };
+use crate::client::key_derivation::DerivationIndex;
 use crate::client::key_derivation::MainPubkey;
 use crate::client::Client;
 
@@ -21,6 +24,7 @@ pub struct RegisterHistory {
     client: Client,
     register_owner: PublicKey,
     current_iter: GraphEntryAddress,
+    data_key: Option<SecretKey>,
 }
 
 impl RegisterHistory {
@@ -29,29 +33,98 @@ impl RegisterHistory {
             client,
             register_owner,
             current_iter: root,
+            data_key: None,
         }
     }
 
+    /// Set the key used to read private data with [`RegisterHistory::next_data`], see [`Client::register_data_key`].
+    pub fn with_data_key(mut self, data_key: SecretKey) -> Self {
+        self.data_key = Some(data_key);
+        self
+    }
+
     /// Fetch and go to the next register value from the history.
     ///
+    /// When the register forked, the history follows the head the fork was resolved with, i.e. the one
+    /// the next (merge) entry follows, or the head with the smallest derivation index if the fork is not resolved yet.
+    ///
     /// Returns `Ok(None)` when we reached the end.
     pub async fn next(&mut self) -> Result<Option<RegisterValue>, RegisterError> {
-        let (entry, next_derivation) = match self
-            .client
-            .register_get_graph_entry_and_next_derivation_index(&self.current_iter)
-            .await
-        {
-            Ok(res) => res,
+        let entry = self.next_entry().await?;
+        Ok(entry.map(|(_, entry)| entry.content))
+    }
+
+    /// Fetch and go to the next register entry from the history, along with its address.
+    pub(crate) async fn next_entry(
+        &mut self,
+    ) -> Result<Option<(GraphEntryAddress, GraphEntry)>, RegisterError> {
+        let heads = match self.client.register_get_heads(&self.current_iter).await {
+            Ok(heads) => heads,
             Err(RegisterError::GraphError(GraphError::Network(NetworkError::GetRecordError(
                 GetRecordError::RecordNotFound,
             )))) => return Ok(None),
             Err(e) => return Err(e),
         };
-        let next_entry_pk: PublicKey = MainPubkey::from(self.register_owner)
-            .derive_key(&next_derivation)
+        let (entry, next_derivation) = self.followed_head(heads).await?;
+        let entry_addr = self.current_iter;
+        self.current_iter = self.entry_address(&next_derivation);
+        Ok(Some((entry_addr, entry)))
+    }
+
+    /// Among the heads at the current entry, find the one the history continues from
+    async fn followed_head(
+        &self,
+        heads: Vec<GraphEntry>,
+    ) -> Result<(GraphEntry, DerivationIndex), RegisterError> {
+        if heads.len() > 1 {
+            for head in &heads {
+                let Ok(derivation) = get_derivation_from_graph_entry(head) else {
+                    continue;
+                };
+                let next = self.entry_address(&derivation);
+                if self.client.graph_entry_check_existance(&next).await? {
+                    return Ok((head.clone(), derivation));
+                }
+            }
+        }
+        let head = ForkResolution::LowestIndex.resolve(&self.current_iter, heads)?;
+        let derivation = get_derivation_from_graph_entry(&head)?;
+        Ok((head, derivation))
+    }
+
+    /// The address of the register entry derived with the given index
+    fn entry_address(&self, derivation: &DerivationIndex) -> GraphEntryAddress {
+        let entry_pk: PublicKey = MainPubkey::from(self.register_owner)
+            .derive_key(derivation)
             .into();
-        self.current_iter = GraphEntryAddress::new(next_entry_pk);
-        Ok(Some(entry.content))
+        GraphEntryAddress::new(entry_pk)
+    }
+
+    /// Fetch and go to the next register value from the history, along with the data it refers to,
+    /// for registers created with [`Client::register_create_data`].
+    ///
+    /// Returns `Ok(None)` when we reached the end.
+    pub async fn next_data(&mut self) -> Result<Option<(RegisterValue, Bytes)>, RegisterError> {
+        let Some(value) = self.next().await? else {
+            return Ok(None);
+        };
+        let data = self
+            .client
+            .register_resolve_data(&value, self.data_key.as_ref())
+            .await?;
+        Ok(Some((value, data)))
+    }
+
+    /// Get all the register values from the history along with the data they refer to,
+    /// starting from the first to the latest entry, see [`RegisterHistory::next_data`].
+    pub async fn collect_data(&mut self) -> Result<Vec<(RegisterValue, Bytes)>, RegisterError> {
+        let mut history_from_first = self.clone();
+        history_from_first.current_iter = GraphEntryAddress::new(self.register_owner);
+        let mut values = Vec::new();
+        while let Some(value) = history_from_first.next_data().await? {
+            values.push(value);
+        }
+        Ok(values)
     }
 
     /// Get all the register values from the history, starting from the first to the latest entry
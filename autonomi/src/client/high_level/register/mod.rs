@@ -11,17 +11,23 @@ use crate::client::data_types::pointer::{PointerAddress, PointerError, PointerTa
 use crate::client::key_derivation::{DerivationIndex, MainPubkey, MainSecretKey};
 use crate::client::payment::PaymentOption;
 use crate::client::quote::CostError;
-use crate::client::Client;
+use crate::client::{Client, GetError, PutError};
 use crate::AttoTokens;
 use ant_networking::{GetRecordError, NetworkError};
 use serde::{Deserialize, Serialize};
 use thiserror::Error;
 use xor_name::XorName;
 
+mod data;
+mod fork;
 mod history;
+mod writers;
 
 pub use crate::{PublicKey, SecretKey};
+pub use data::RegisterDataVisibility;
+pub use fork::{entry_timestamp, ForkResolution, ForkResolver};
 pub use history::RegisterHistory;
+pub use writers::{DelegatedEntry, WriterList, WriterStatus};
 
 /// A Register is addressed at a [`RegisterAddress`] which is in fact the owner's [`PublicKey`].
 /// There can only be one register stored at [`PublicKey`].
@@ -88,7 +94,7 @@ pub enum RegisterError {
     InvalidCost,
     #[error("Invalid head pointer, was expecting a GraphEntryAddress but got: {0:?}")]
     InvalidHeadPointer(PointerTarget),
-    #[error("Forked register, this can happen if the register has been updated concurrently, you can solve this by updating the register again with a new value. Concurrent entries: {0:?}")]
+    #[error("Forked register, this can happen if the register has been updated concurrently, you can solve this by updating the register again with a new value, or by reading it with another ForkResolution. Concurrent entries: {0:?}")]
     Fork(Vec<[u8; 32]>),
     #[error("Corrupt register: {0}")]
     Corrupt(String),
@@ -98,6 +104,20 @@ pub enum RegisterError {
         "Invalid register value length: {0}, expected something within {REGISTER_VALUE_SIZE} bytes"
     )]
     InvalidRegisterValueLength(usize),
+    #[error("Failed to get register data: {0}")]
+    GetError(#[from] GetError),
+    #[error("Failed to put register data: {0}")]
+    PutError(#[from] PutError),
+    #[error("Invalid register data reference: {0}")]
+    InvalidDataReference(String),
+    #[error("The register data is private, its data key is needed to read it")]
+    MissingDataKey,
+    #[error("Wrong data key for the private register data")]
+    WrongDataKey,
+    #[error("{0:?} is not an authorized writer of the register")]
+    UnauthorizedWriter(PublicKey),
+    #[error("Invalid register writer list: {0}")]
+    InvalidWriterList(String),
 }
 
 /// Hard coded derivation index for the register head pointer
@@ -134,14 +154,25 @@ impl Client {
         owner: &SecretKey,
         initial_value: RegisterValue,
         payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, RegisterAddress), RegisterError> {
+        self.register_create_with_parents(owner, initial_value, vec![], payment_option)
+            .await
+    }
+
+    /// Create a new register whose first entry refers to the given parents
+    async fn register_create_with_parents(
+        &self,
+        owner: &SecretKey,
+        initial_value: RegisterValue,
+        parents: Vec<PublicKey>,
+        payment_option: PaymentOption,
     ) -> Result<(AttoTokens, RegisterAddress), RegisterError> {
         let main_key = MainSecretKey::new(owner.clone());
         let public_key = main_key.public_key();
 
         // create the first entry and decide on the next key
-        let index = DerivationIndex::random(&mut rand::thread_rng());
+        let index = fork::new_derivation_index();
         let next_key = main_key.public_key().derive_key(&index);
-        let parents = vec![];
         let descendants = vec![(next_key.into(), index.into_bytes())];
         let root_entry = GraphEntry::new(
             &main_key.clone().into(),
@@ -170,11 +201,34 @@ impl Client {
     /// Update the value of a register.
     ///
     /// The register needs to be created first with [`Client::register_create`]
+    /// If the register is forked, the new entry follows the head with the smallest derivation index,
+    /// see [`Client::register_update_with_resolution`].
     pub async fn register_update(
         &self,
         owner: &SecretKey,
         new_value: RegisterValue,
         payment_option: PaymentOption,
+    ) -> Result<AttoTokens, RegisterError> {
+        self.register_update_with_resolution(
+            owner,
+            new_value,
+            &ForkResolution::LowestIndex,
+            payment_option,
+        )
+        .await
+    }
+
+    /// Update the value of a register, resolving a fork with the given policy.
+    ///
+    /// When the register is forked, the new entry follows the head chosen by `resolution`, and is a merge entry:
+    /// its parent is the forked address, which resolves to all the forked heads, the losing ones included.
+    /// [`ForkResolution::Fail`] refuses to update a forked register.
+    pub async fn register_update_with_resolution(
+        &self,
+        owner: &SecretKey,
+        new_value: RegisterValue,
+        resolution: &ForkResolution,
+        payment_option: PaymentOption,
     ) -> Result<AttoTokens, RegisterError> {
         // get the pointer of the register head
         let addr = RegisterAddress(owner.public_key());
@@ -194,15 +248,15 @@ impl Client {
 
         // get the next derivation index from the current head entry
         debug!("Getting register head graph entry at {graph_entry_addr:?}");
-        let (parent_entry, new_derivation) = self
-            .register_get_graph_entry_and_next_derivation_index(graph_entry_addr)
-            .await?;
+        let heads = self.register_get_heads(graph_entry_addr).await?;
+        let parent_entry = resolution.resolve(graph_entry_addr, heads.clone())?;
+        let parents = merge_parents(&parent_entry, &heads);
+        let new_derivation = get_derivation_from_graph_entry(&parent_entry)?;
 
         // create a new entry with the new value
         let main_key = MainSecretKey::new(owner.clone());
         let new_key = main_key.derive_key(&new_derivation);
-        let parents = vec![parent_entry.owner];
-        let next_derivation = DerivationIndex::random(&mut rand::thread_rng());
+        let next_derivation = fork::new_derivation_index();
         let next_pk = main_key.public_key().derive_key(&next_derivation);
         let descendants = vec![(next_pk.into(), next_derivation.into_bytes())];
         let new_entry = GraphEntry::new(&new_key.into(), parents, new_value, descendants);
@@ -234,9 +288,21 @@ impl Client {
     }
 
     /// Get the current value of the register
+    ///
+    /// Returns [`RegisterError::Fork`] if the register is forked, see [`Client::register_get_with_resolution`].
     pub async fn register_get(
         &self,
         addr: &RegisterAddress,
+    ) -> Result<RegisterValue, RegisterError> {
+        self.register_get_with_resolution(addr, &ForkResolution::Fail)
+            .await
+    }
+
+    /// Get the current value of the register, resolving a fork with the given policy.
+    pub async fn register_get_with_resolution(
+        &self,
+        addr: &RegisterAddress,
+        resolution: &ForkResolution,
     ) -> Result<RegisterValue, RegisterError> {
         // get the pointer of the register head
         let pointer_addr = register_head_pointer_address(addr);
@@ -249,14 +315,8 @@ impl Client {
 
         // get the entry from the graph
         debug!("Getting register head graph entry at {graph_entry_addr:?}");
-        let entry = match self.graph_entry_get(graph_entry_addr).await {
-            Ok(entry) => entry,
-            Err(GraphError::Fork(entries)) => {
-                let values = entries.iter().map(|e| e.content).collect::<Vec<_>>();
-                return Err(RegisterError::Fork(values));
-            }
-            Err(err) => return Err(err.into()),
-        };
+        let heads = self.register_get_heads(graph_entry_addr).await?;
+        let entry = resolution.resolve(graph_entry_addr, heads)?;
 
         // get the content of the entry
         let content = entry.content;
@@ -276,36 +336,40 @@ impl Client {
             .ok_or(CostError::InvalidCost)
     }
 
-    /// Get underlying register graph entry and next derivation index
-    /// In normal circumstances, there is only one entry with one descendant, yielding ONE entry and ONE derivation index
-    /// In the case of a fork or a corrupt register, the smallest derivation index among all the entries descendants is chosen
-    /// We chose here to deal with the errors instead of erroring out to allow users to solve Fork and Corrupt issues by updating the register
-    async fn register_get_graph_entry_and_next_derivation_index(
+    /// Get the underlying register graph entries at an address
+    /// In normal circumstances, there is only one entry, but there are several in the case of a fork
+    /// The caller decides how to deal with a fork with a [`ForkResolution`]
+    async fn register_get_heads(
         &self,
         graph_entry_addr: &GraphEntryAddress,
-    ) -> Result<(GraphEntry, DerivationIndex), RegisterError> {
-        let entry = match self.graph_entry_get(graph_entry_addr).await {
-            Ok(e) => e,
+    ) -> Result<Vec<GraphEntry>, RegisterError> {
+        match self.graph_entry_get(graph_entry_addr).await {
+            Ok(entry) => Ok(vec![entry]),
             Err(GraphError::Fork(entries)) => {
-                warn!("Forked register, multiple entries found: {entries:?}, choosing the one with the smallest derivation index for the next entry");
-                let (entry_by_smallest_derivation, _) = entries
-                    .into_iter()
-                    .filter_map(|e| {
-                        get_derivation_from_graph_entry(&e)
-                            .ok()
-                            .map(|derivation| (e, derivation))
-                    })
-                    .min_by(|a, b| a.1.cmp(&b.1))
-                    .ok_or(RegisterError::Corrupt(format!(
-                        "No valid descendants found for FORKED entry at {graph_entry_addr:?}"
-                    )))?;
-                entry_by_smallest_derivation
+                warn!(
+                    "Forked register, multiple entries found at {graph_entry_addr:?}: {entries:?}"
+                );
+                Ok(entries)
             }
-            Err(err) => return Err(err.into()),
-        };
-        let new_derivation = get_derivation_from_graph_entry(&entry)?;
-        Ok((entry, new_derivation))
+            Err(err) => Err(err.into()),
+        }
+    }
+}
+
+/// The parents of the entry following the `chosen` head: the address of the chosen head, followed by the
+/// address of the other, losing, heads when they are not at the same address.
+///
+/// Forked heads usually share the same address, as they were written concurrently from the same parent,
+/// so that single parent resolves to all of them and records which heads the merge entry resolved,
+/// their values can still be read at the forked address.
+fn merge_parents(chosen: &GraphEntry, heads: &[GraphEntry]) -> Vec<PublicKey> {
+    let mut parents = vec![chosen.owner];
+    for head in heads {
+        if !parents.contains(&head.owner) {
+            parents.push(head.owner);
+        }
     }
+    parents
 }
 
 /// Get the address of the register's head pointer
@@ -381,4 +445,36 @@ mod tests {
         .unwrap_err();
         assert!(matches!(err, super::RegisterError::InvalidRegisterValueLength(v) if v == 33));
     }
+
+    #[test]
+    fn test_merge_parents_records_losing_heads() {
+        let owner = bls::SecretKey::random();
+        let head = |owner: &bls::SecretKey, value: u8| {
+            let descendant = bls::SecretKey::random().public_key();
+            super::GraphEntry::new(owner, vec![], [value; 32], vec![(descendant, [value; 32])])
+        };
+        // every parent resolves to one of the heads, and every head is resolved by a parent
+        let resolves = |parents: &[bls::PublicKey], heads: &[super::GraphEntry]| {
+            parents
+                .iter()
+                .all(|parent| heads.iter().any(|head| head.owner == *parent))
+                && heads.iter().all(|head| parents.contains(&head.owner))
+        };
+
+        let heads = vec![head(&owner, 1), head(&owner, 2), head(&owner, 3)];
+        let parents = super::merge_parents(&heads[0], &heads[..1]);
+        assert_eq!(parents, vec![owner.public_key()]);
+
+        // forked heads at the same address are all resolved by that address
+        let parents = super::merge_parents(&heads[1], &heads);
+        assert_eq!(parents, vec![owner.public_key()]);
+        assert!(resolves(&parents, &heads));
+
+        // heads at other addresses are listed after the chosen one
+        let other = bls::SecretKey::random();
+        let heads = vec![head(&owner, 1), head(&other, 2), head(&owner, 3)];
+        let parents = super::merge_parents(&heads[1], &heads);
+        assert_eq!(parents, vec![other.public_key(), owner.public_key()]);
+        assert!(resolves(&parents, &heads));
+    }
 }
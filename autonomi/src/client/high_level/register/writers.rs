@@ -0,0 +1,519 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Delegated writers of a register.
+//!
+//! Only the owner of a register can update it, as each entry is signed by a key derived from the owner's key.
+//! Instead, each writer authorized by the owner appends to its own lane: a register whose key is derived from
+//! the writer's key and the register address, and whose first entry refers to the register root as parent.
+//! As lane addresses are derived from the writers' public keys, only the writers can write to their lanes.
+//!
+//! The owner keeps the list of writers on the Network, in a chunk referred to by a pointer signed by the owner.
+//! Readers combine the register and the lanes of the writers in the list, the latest entry winning.
+//! When a writer is revoked, the head of its lane is recorded in the list and later entries are ignored.
+//!
+//! Writers choose the timestamps of their entries, so timestamps too far in the future are not trusted:
+//! such entries are considered older than any other, see [`DelegatedEntry::timestamp`].
+
+use std::collections::BTreeMap;
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use ant_networking::{GetRecordError, NetworkError};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use xor_name::XorName;
+
+use crate::client::data_types::graph::GraphEntryAddress;
+use crate::client::data_types::pointer::{PointerAddress, PointerError, PointerTarget};
+use crate::client::high_level::register::{
+    entry_timestamp, ForkResolution, PublicKey, RegisterAddress, RegisterError, RegisterValue,
+    SecretKey,
+};
+use crate::client::key_derivation::{DerivationIndex, MainPubkey, MainSecretKey};
+use crate::client::payment::PaymentOption;
+use crate::client::Client;
+use crate::{AttoTokens, Chunk, GraphEntry};
+
+/// Hard coded derivation index for the pointer to the writer list of a register
+const REGISTER_WRITERS_DERIVATION_INDEX: [u8; 32] = [2; 32];
+
+/// How far in the future an entry timestamp is accepted, to allow for clock differences between writers
+const MAX_CLOCK_SKEW_MILLIS: u64 = 5 * 60 * 1000;
+
+/// Whether a writer can append to a register.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+pub enum WriterStatus {
+    /// The writer can append to the register
+    Active,
+    /// The writer was revoked, only the entries of its lane up to `last_entry` are accepted
+    Revoked {
+        last_entry: Option<GraphEntryAddress>,
+        /// When the writer was revoked, in milliseconds since the UNIX epoch,
+        /// later timestamps of its entries are not trusted
+        #[serde(default)]
+        revoked_at: Option<u64>,
+    },
+}
+
+/// The writers authorized by the owner of a register, see [`Client::register_add_writer`].
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct WriterList {
+    writers: BTreeMap<PublicKey, WriterStatus>,
+}
+
+/// This type essentially wraps the writer list in a version marker, so the format can evolve.
+#[derive(Serialize, Deserialize)]
+enum WriterListVersioned {
+    V0(WriterList),
+}
+
+impl WriterList {
+    /// All the writers ever authorized, along with their status
+    pub fn iter(&self) -> impl Iterator<Item = (&PublicKey, &WriterStatus)> {
+        self.writers.iter()
+    }
+
+    /// The status of a writer, `None` if it was never authorized
+    pub fn status(&self, writer: &PublicKey) -> Option<&WriterStatus> {
+        self.writers.get(writer)
+    }
+
+    /// Returns true if the writer can currently append to the register
+    pub fn is_active(&self, writer: &PublicKey) -> bool {
+        matches!(self.status(writer), Some(WriterStatus::Active))
+    }
+}
+
+/// An entry of a register with delegated writers.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DelegatedEntry {
+    /// The owner or the writer who wrote the entry
+    pub writer: PublicKey,
+    pub value: RegisterValue,
+    /// When the entry was written, see [`entry_timestamp`]
+    ///
+    /// `None` if the entry has no timestamp, or if its timestamp cannot be trusted: later than a few minutes
+    /// from now, or later than the revocation of its writer. Otherwise a writer could win over all later entries.
+    pub timestamp: Option<u64>,
+}
+
+impl DelegatedEntry {
+    fn new(writer: PublicKey, entry: &GraphEntry, max_timestamp: u64) -> Self {
+        Self {
+            writer,
+            value: entry.content,
+            timestamp: entry_timestamp(entry).filter(|timestamp| *timestamp <= max_timestamp),
+        }
+    }
+}
+
+impl WriterStatus {
+    /// The latest timestamp trusted for the entries of a writer with this status
+    fn max_timestamp(&self, now: u64) -> u64 {
+        let max = now.saturating_add(MAX_CLOCK_SKEW_MILLIS);
+        match self {
+            Self::Revoked {
+                revoked_at: Some(revoked_at),
+                ..
+            } => max.min(*revoked_at),
+            _ => max,
+        }
+    }
+}
+
+/// The current time in milliseconds since the UNIX epoch, as in [`entry_timestamp`]
+fn now_millis() -> u64 {
+    SystemTime::now()
+        .duration_since(UNIX_EPOCH)
+        .map(|d| d.as_millis() as u64)
+        .unwrap_or(0)
+}
+
+impl Client {
+    /// The address of the lane a writer appends to for the given register.
+    pub fn register_writer_lane(writer: &PublicKey, addr: &RegisterAddress) -> RegisterAddress {
+        let lane_key = MainPubkey::from(*writer).derive_key(&lane_derivation_index(addr));
+        RegisterAddress::new(lane_key.into())
+    }
+
+    /// The key of the lane a writer appends to for the given register.
+    pub fn register_writer_key(writer: &SecretKey, addr: &RegisterAddress) -> SecretKey {
+        MainSecretKey::new(writer.clone())
+            .derive_key(&lane_derivation_index(addr))
+            .into()
+    }
+
+    /// Get the writers of a register, empty if the owner never authorized any.
+    pub async fn register_writers(
+        &self,
+        addr: &RegisterAddress,
+    ) -> Result<WriterList, RegisterError> {
+        let pointer_addr = writers_pointer_address(addr);
+        debug!("Getting register writer list pointer at {pointer_addr:?}");
+        let pointer = match self.pointer_get(&pointer_addr).await {
+            Ok(pointer) => pointer,
+            Err(PointerError::Network(NetworkError::GetRecordError(
+                GetRecordError::RecordNotFound,
+            ))) => return Ok(WriterList::default()),
+            Err(err) => return Err(err.into()),
+        };
+        let chunk_addr = match pointer.target() {
+            PointerTarget::ChunkAddress(chunk_addr) => chunk_addr,
+            other => {
+                return Err(RegisterError::InvalidWriterList(format!(
+                    "Expected a chunk address but got {other:?}"
+                )))
+            }
+        };
+        let chunk = self.chunk_get(chunk_addr).await?;
+        let WriterListVersioned::V0(list) = rmp_serde::from_slice(chunk.value())
+            .map_err(|err| RegisterError::InvalidWriterList(err.to_string()))?;
+        Ok(list)
+    }
+
+    /// Authorize a writer to append to a register, see [`Client::register_update_as_writer`].
+    ///
+    /// A revoked writer can be authorized again, its lane entries are accepted again.
+    pub async fn register_add_writer(
+        &self,
+        owner: &SecretKey,
+        writer: PublicKey,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, RegisterError> {
+        let addr = RegisterAddress::new(owner.public_key());
+        let mut list = self.register_writers(&addr).await?;
+        list.writers.insert(writer, WriterStatus::Active);
+        self.register_put_writers(owner, &list, payment_option)
+            .await
+    }
+
+    /// Revoke a writer of a register: the entries it appends from now on are ignored.
+    pub async fn register_revoke_writer(
+        &self,
+        owner: &SecretKey,
+        writer: PublicKey,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, RegisterError> {
+        let addr = RegisterAddress::new(owner.public_key());
+        let mut list = self.register_writers(&addr).await?;
+        if !list.is_active(&writer) {
+            return Err(RegisterError::UnauthorizedWriter(writer));
+        }
+        let lane = Self::register_writer_lane(&writer, &addr);
+        let last_entry = self.register_head_entry(&lane).await?.map(|(head, _)| head);
+        info!("Revoking writer {writer:?} of register {addr}, last accepted entry: {last_entry:?}");
+        let revoked_at = Some(now_millis());
+        list.writers.insert(
+            writer,
+            WriterStatus::Revoked {
+                last_entry,
+                revoked_at,
+            },
+        );
+        self.register_put_writers(owner, &list, payment_option)
+            .await
+    }
+
+    /// Append a value to a register as a writer authorized by its owner.
+    ///
+    /// The value is written to the writer's lane, created on the first update.
+    pub async fn register_update_as_writer(
+        &self,
+        writer: &SecretKey,
+        addr: &RegisterAddress,
+        new_value: RegisterValue,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, RegisterError> {
+        let list = self.register_writers(addr).await?;
+        if !list.is_active(&writer.public_key()) {
+            return Err(RegisterError::UnauthorizedWriter(writer.public_key()));
+        }
+        let lane_key = Self::register_writer_key(writer, addr);
+        match self
+            .register_update(&lane_key, new_value, payment_option.clone())
+            .await
+        {
+            Err(RegisterError::CannotUpdateNewRegister) => {
+                debug!(
+                    "Creating the lane of writer {:?} for register {addr}",
+                    writer.public_key()
+                );
+                let parents = vec![*addr.to_underlying_graph_root().owner()];
+                let (cost, _) = self
+                    .register_create_with_parents(&lane_key, new_value, parents, payment_option)
+                    .await?;
+                Ok(cost)
+            }
+            res => res,
+        }
+    }
+
+    /// Get the latest value of a register with delegated writers, among the owner's and the writers' entries.
+    pub async fn register_get_delegated(
+        &self,
+        addr: &RegisterAddress,
+    ) -> Result<DelegatedEntry, RegisterError> {
+        let list = self.register_writers(addr).await?;
+        let now = now_millis();
+        let owner_max_timestamp = WriterStatus::Active.max_timestamp(now);
+        let mut latest = match self.register_head_entry(addr).await? {
+            Some((_, entry)) => DelegatedEntry::new(addr.owner(), &entry, owner_max_timestamp),
+            None => {
+                return Err(PointerError::Network(NetworkError::GetRecordError(
+                    GetRecordError::RecordNotFound,
+                ))
+                .into())
+            }
+        };
+        for (writer, status) in list.iter() {
+            let lane = Self::register_writer_lane(writer, addr);
+            let head = match status {
+                WriterStatus::Active => self.register_head_entry(&lane).await?,
+                WriterStatus::Revoked {
+                    last_entry: None, ..
+                } => None,
+                WriterStatus::Revoked {
+                    last_entry: Some(last_entry),
+                    ..
+                } => Some((*last_entry, self.register_entry_at(last_entry).await?)),
+            };
+            if let Some((_, entry)) = head {
+                let candidate = DelegatedEntry::new(*writer, &entry, status.max_timestamp(now));
+                if candidate.timestamp > latest.timestamp {
+                    latest = candidate;
+                }
+            }
+        }
+        Ok(latest)
+    }
+
+    /// Get all the entries of a register with delegated writers, sorted by time of writing.
+    pub async fn register_history_delegated(
+        &self,
+        addr: &RegisterAddress,
+    ) -> Result<Vec<DelegatedEntry>, RegisterError> {
+        let list = self.register_writers(addr).await?;
+        let now = now_millis();
+        let owner_status = WriterStatus::Active;
+        let mut entries = self
+            .register_lane_entries(addr, addr.owner(), &owner_status, now)
+            .await?;
+        for (writer, status) in list.iter() {
+            let lane = Self::register_writer_lane(writer, addr);
+            let lane_entries = self
+                .register_lane_entries(&lane, *writer, status, now)
+                .await?;
+            entries.extend(lane_entries);
+        }
+        // entries without timestamp come first, the sort is stable to keep each lane in order
+        entries.sort_by_key(|entry| entry.timestamp);
+        Ok(entries)
+    }
+
+    /// The entries of a lane accepted for a writer with the given status
+    async fn register_lane_entries(
+        &self,
+        lane: &RegisterAddress,
+        writer: PublicKey,
+        status: &WriterStatus,
+        now: u64,
+    ) -> Result<Vec<DelegatedEntry>, RegisterError> {
+        let last_entry = match status {
+            WriterStatus::Active => None,
+            WriterStatus::Revoked {
+                last_entry: None, ..
+            } => return Ok(vec![]),
+            WriterStatus::Revoked {
+                last_entry: Some(last_entry),
+                ..
+            } => Some(last_entry),
+        };
+        let max_timestamp = status.max_timestamp(now);
+        let mut history = self.register_history(lane);
+        let mut entries = vec![];
+        while let Some((entry_addr, entry)) = history.next_entry().await? {
+            entries.push(DelegatedEntry::new(writer, &entry, max_timestamp));
+            if Some(&entry_addr) == last_entry {
+                break;
+            }
+        }
+        Ok(entries)
+    }
+
+    /// The head entry of a register, `None` if it does not exist
+    async fn register_head_entry(
+        &self,
+        addr: &RegisterAddress,
+    ) -> Result<Option<(GraphEntryAddress, GraphEntry)>, RegisterError> {
+        let pointer = match self.pointer_get(&addr.to_underlying_head_pointer()).await {
+            Ok(pointer) => pointer,
+            Err(PointerError::Network(NetworkError::GetRecordError(
+                GetRecordError::RecordNotFound,
+            ))) => return Ok(None),
+            Err(err) => return Err(err.into()),
+        };
+        let head = match pointer.target() {
+            PointerTarget::GraphEntryAddress(head) => *head,
+            other => return Err(RegisterError::InvalidHeadPointer(other.clone())),
+        };
+        let entry = self.register_entry_at(&head).await?;
+        Ok(Some((head, entry)))
+    }
+
+    /// The register entry at an address, resolving forks by the smallest derivation index
+    async fn register_entry_at(
+        &self,
+        entry_addr: &GraphEntryAddress,
+    ) -> Result<GraphEntry, RegisterError> {
+        let heads = self.register_get_heads(entry_addr).await?;
+        ForkResolution::LowestIndex.resolve(entry_addr, heads)
+    }
+
+    /// Upload the writer list and point the register writers pointer to it
+    async fn register_put_writers(
+        &self,
+        owner: &SecretKey,
+        list: &WriterList,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, RegisterError> {
+        let bytes = rmp_serde::to_vec_named(&WriterListVersioned::V0(list.clone()))
+            .map_err(|err| RegisterError::InvalidWriterList(err.to_string()))?;
+        let (chunk_cost, chunk_addr) = self
+            .chunk_put(&Chunk::new(Bytes::from(bytes)), payment_option.clone())
+            .await?;
+
+        let pointer_key: SecretKey = MainSecretKey::new(owner.clone())
+            .derive_key(&DerivationIndex::from_bytes(
+                REGISTER_WRITERS_DERIVATION_INDEX,
+            ))
+            .into();
+        let target = PointerTarget::ChunkAddress(chunk_addr);
+        let pointer_addr = PointerAddress::new(pointer_key.public_key());
+        let pointer_cost = if self.pointer_check_existance(&pointer_addr).await? {
+            self.pointer_update(&pointer_key, target).await?;
+            AttoTokens::zero()
+        } else {
+            let (cost, _) = self
+                .pointer_create(&pointer_key, target, payment_option)
+                .await?;
+            cost
+        };
+        chunk_cost
+            .checked_add(pointer_cost)
+            .ok_or(RegisterError::InvalidCost)
+    }
+}
+
+/// The address of the pointer to the writer list of a register
+fn writers_pointer_address(addr: &RegisterAddress) -> PointerAddress {
+    let pointer_pk = MainPubkey::from(addr.owner()).derive_key(&DerivationIndex::from_bytes(
+        REGISTER_WRITERS_DERIVATION_INDEX,
+    ));
+    PointerAddress::new(pointer_pk.into())
+}
+
+/// The derivation index of the writers lanes of a register
+fn lane_derivation_index(addr: &RegisterAddress) -> DerivationIndex {
+    let mut name = b"register-writer-lane".to_vec();
+    name.extend_from_slice(&addr.owner().to_bytes());
+    DerivationIndex::from_bytes(XorName::from_content(&name).0)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::high_level::register::fork::derivation_index_at;
+
+    fn entry_at(writer: &SecretKey, millis: u64) -> GraphEntry {
+        let derivation = derivation_index_at(millis);
+        let next = MainSecretKey::new(writer.clone())
+            .public_key()
+            .derive_key(&derivation);
+        GraphEntry::new(
+            writer,
+            vec![],
+            [1; 32],
+            vec![(next.into(), derivation.into_bytes())],
+        )
+    }
+
+    #[test]
+    fn future_timestamps_are_not_trusted() {
+        let writer = SecretKey::random();
+        let now = 1_700_000_000_000;
+        let active = WriterStatus::Active;
+        let revoked = WriterStatus::Revoked {
+            last_entry: None,
+            revoked_at: Some(now - 60_000),
+        };
+
+        // an active writer's entry is trusted up to a few minutes ahead of now
+        let recent = entry_at(&writer, now + 1000);
+        let entry = DelegatedEntry::new(writer.public_key(), &recent, active.max_timestamp(now));
+        assert_eq!(entry.timestamp, Some(now + 1000));
+
+        // but an entry far in the future does not win over the entries written until then
+        let future = entry_at(&writer, now + 365 * 24 * 3600 * 1000);
+        let entry = DelegatedEntry::new(writer.public_key(), &future, active.max_timestamp(now));
+        assert_eq!(entry.timestamp, None);
+
+        // a revoked writer's entries are only trusted up to its revocation
+        let before = entry_at(&writer, now - 120_000);
+        let entry = DelegatedEntry::new(writer.public_key(), &before, revoked.max_timestamp(now));
+        assert_eq!(entry.timestamp, Some(now - 120_000));
+        let entry = DelegatedEntry::new(writer.public_key(), &recent, revoked.max_timestamp(now));
+        assert_eq!(entry.timestamp, None);
+
+        // lists written before revocation times were recorded keep the clock skew bound
+        let legacy = WriterStatus::Revoked {
+            last_entry: None,
+            revoked_at: None,
+        };
+        assert_eq!(legacy.max_timestamp(now), now + MAX_CLOCK_SKEW_MILLIS);
+    }
+
+    #[test]
+    fn writer_lanes() {
+        let owner = SecretKey::random();
+        let writer = SecretKey::random();
+        let addr = RegisterAddress::new(owner.public_key());
+
+        let lane = Client::register_writer_lane(&writer.public_key(), &addr);
+        let lane_key = Client::register_writer_key(&writer, &addr);
+        assert_eq!(lane.owner(), lane_key.public_key());
+        assert_ne!(lane.owner(), writer.public_key());
+
+        let other_addr = RegisterAddress::new(SecretKey::random().public_key());
+        assert_ne!(
+            Client::register_writer_lane(&writer.public_key(), &other_addr),
+            lane
+        );
+    }
+
+    #[test]
+    fn writer_list_serialization() {
+        let active = SecretKey::random().public_key();
+        let revoked = SecretKey::random().public_key();
+        let mut list = WriterList::default();
+        list.writers.insert(active, WriterStatus::Active);
+        list.writers.insert(
+            revoked,
+            WriterStatus::Revoked {
+                last_entry: Some(GraphEntryAddress::new(revoked)),
+                revoked_at: Some(1_700_000_000_000),
+            },
+        );
+
+        let bytes = rmp_serde::to_vec_named(&WriterListVersioned::V0(list.clone())).unwrap();
+        let WriterListVersioned::V0(deserialized) = rmp_serde::from_slice(&bytes).unwrap();
+        assert_eq!(deserialized, list);
+        assert!(deserialized.is_active(&active));
+        assert!(!deserialized.is_active(&revoked));
+        assert_eq!(deserialized.status(&SecretKey::random().public_key()), None);
+    }
+}
@@ -0,0 +1,339 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+//! Shared scratchpads are scratchpads that can be read by a list of readers on top of their owner.
+//!
+//! A regular [`Scratchpad`] is encrypted to its owner's public key. The data of a shared scratchpad is
+//! encrypted once with a random content key instead, and that content key is encrypted for each reader.
+//! The readers' public keys are stored in clear alongside, so that each reader can find its copy of the key.
+//!
+//! Only the owner can update a shared scratchpad. Adding a reader only adds a copy of the content key,
+//! removing one rotates the content key so that the removed reader can't read the next versions.
+
+use crate::client::data_types::scratchpad::{
+    Bytes, PublicKey, Scratchpad, ScratchpadAddress, ScratchpadError, SecretKey,
+};
+use crate::client::payment::PaymentOption;
+use crate::client::Client;
+use crate::AttoTokens;
+use bls::Ciphertext;
+use serde::{Deserialize, Serialize};
+use thiserror::Error;
+
+/// Errors that can occur when dealing with shared scratchpads.
+#[derive(Error, Debug)]
+#[allow(clippy::large_enum_variant)]
+pub enum SharedScratchpadError {
+    #[error("Underlying scratchpad error: {0}")]
+    Scratchpad(#[from] ScratchpadError),
+    #[error("Failed to serialize the shared scratchpad content: {0}")]
+    Serialization(#[from] rmp_serde::encode::Error),
+    #[error("The scratchpad is not a shared scratchpad: {0}")]
+    Deserialization(#[from] rmp_serde::decode::Error),
+    #[error("{0:?} is not a reader of the scratchpad")]
+    NotAReader(PublicKey),
+    #[error("Failed to decrypt the shared scratchpad content")]
+    Decryption,
+}
+
+/// The encrypted content of a shared scratchpad, stored as the scratchpad's encrypted data.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+struct SharedContent {
+    /// The content key encrypted for each reader, the owner being the first reader
+    readers: Vec<(PublicKey, Bytes)>,
+    /// The data encrypted with the content key
+    encrypted_data: Bytes,
+}
+
+#[derive(Serialize, Deserialize)]
+enum SharedContentVersioned {
+    V0(SharedContent),
+}
+
+impl SharedContent {
+    /// Encrypt the data with the content key, and the content key for each reader
+    fn seal(content_key: &SecretKey, data: &Bytes, readers: &[PublicKey]) -> Self {
+        let mut content = Self {
+            readers: vec![],
+            encrypted_data: Bytes::from(content_key.public_key().encrypt(data).to_bytes()),
+        };
+        for reader in readers {
+            content.add_reader(content_key, *reader);
+        }
+        content
+    }
+
+    /// Encrypt the content key for a new reader, does nothing if it is already a reader
+    fn add_reader(&mut self, content_key: &SecretKey, reader: PublicKey) {
+        if self.readers.iter().any(|(pk, _)| *pk == reader) {
+            return;
+        }
+        let wrapped_key = reader.encrypt(content_key.to_bytes()).to_bytes();
+        self.readers.push((reader, Bytes::from(wrapped_key)));
+    }
+
+    fn reader_keys(&self) -> Vec<PublicKey> {
+        self.readers.iter().map(|(pk, _)| *pk).collect()
+    }
+
+    /// Decrypt the content key with a reader's key
+    fn content_key(&self, reader: &SecretKey) -> Result<SecretKey, SharedScratchpadError> {
+        let reader_pk = reader.public_key();
+        let (_, wrapped_key) = self
+            .readers
+            .iter()
+            .find(|(pk, _)| *pk == reader_pk)
+            .ok_or(SharedScratchpadError::NotAReader(reader_pk))?;
+        let key_bytes = decrypt(reader, wrapped_key)?;
+        let key_bytes: [u8; 32] = key_bytes
+            .try_into()
+            .map_err(|_| SharedScratchpadError::Decryption)?;
+        SecretKey::from_bytes(key_bytes).map_err(|_| SharedScratchpadError::Decryption)
+    }
+
+    /// Decrypt the data with a reader's key
+    fn open(&self, reader: &SecretKey) -> Result<Bytes, SharedScratchpadError> {
+        let content_key = self.content_key(reader)?;
+        Ok(Bytes::from(decrypt(&content_key, &self.encrypted_data)?))
+    }
+
+    fn to_bytes(&self) -> Result<Bytes, SharedScratchpadError> {
+        let versioned = SharedContentVersioned::V0(self.clone());
+        Ok(Bytes::from(rmp_serde::to_vec_named(&versioned)?))
+    }
+
+    fn from_bytes(bytes: &Bytes) -> Result<Self, SharedScratchpadError> {
+        match rmp_serde::from_slice(bytes)? {
+            SharedContentVersioned::V0(content) => Ok(content),
+        }
+    }
+}
+
+fn decrypt(sk: &SecretKey, bytes: &[u8]) -> Result<Vec<u8>, SharedScratchpadError> {
+    let cipher = Ciphertext::from_bytes(bytes).map_err(|_| SharedScratchpadError::Decryption)?;
+    sk.decrypt(&cipher).ok_or(SharedScratchpadError::Decryption)
+}
+
+/// Sign the shared content as the encrypted data of the owner's scratchpad
+fn shared_scratchpad(
+    owner: &SecretKey,
+    content_type: u64,
+    content: &SharedContent,
+    counter: u64,
+) -> Result<Scratchpad, SharedScratchpadError> {
+    let address = ScratchpadAddress::new(owner.public_key());
+    let encrypted_data = content.to_bytes()?;
+    let signature = owner.sign(Scratchpad::bytes_for_signature(
+        address,
+        content_type,
+        &encrypted_data,
+        counter,
+    ));
+    Ok(Scratchpad::new_with_signature(
+        owner.public_key(),
+        content_type,
+        encrypted_data,
+        counter,
+        signature,
+    ))
+}
+
+impl Client {
+    /// Create a new shared scratchpad, readable by its owner and the given readers.
+    ///
+    /// Make sure that the owner key is not already used for another scratchpad as each key is associated with one scratchpad.
+    /// The content type is used to identify the type of data stored in the scratchpad, the choice is up to the caller.
+    pub async fn shared_scratchpad_create(
+        &self,
+        owner: &SecretKey,
+        content_type: u64,
+        initial_data: &Bytes,
+        readers: &[PublicKey],
+        payment_option: PaymentOption,
+    ) -> Result<(AttoTokens, ScratchpadAddress), SharedScratchpadError> {
+        let address = ScratchpadAddress::new(owner.public_key());
+        if self.scratchpad_check_existance(&address).await? {
+            return Err(ScratchpadError::ScratchpadAlreadyExists(address).into());
+        }
+
+        let content_key = SecretKey::random();
+        let all_readers: Vec<_> = std::iter::once(owner.public_key())
+            .chain(readers.iter().copied())
+            .collect();
+        let content = SharedContent::seal(&content_key, initial_data, &all_readers);
+        let scratchpad = shared_scratchpad(owner, content_type, &content, 0)?;
+        let (cost, addr) = self.scratchpad_put(scratchpad, payment_option).await?;
+        debug!(
+            "Created shared scratchpad at {addr:?} with {} readers",
+            content.readers.len()
+        );
+        Ok((cost, addr))
+    }
+
+    /// Fetch and decrypt a shared scratchpad with the key of one of its readers, or its owner.
+    pub async fn shared_scratchpad_get(
+        &self,
+        address: &ScratchpadAddress,
+        reader: &SecretKey,
+    ) -> Result<Bytes, SharedScratchpadError> {
+        let (_, content) = self.shared_scratchpad_fetch(address).await?;
+        content.open(reader)
+    }
+
+    /// Get the public keys of the readers of a shared scratchpad, starting with its owner.
+    pub async fn shared_scratchpad_readers(
+        &self,
+        address: &ScratchpadAddress,
+    ) -> Result<Vec<PublicKey>, SharedScratchpadError> {
+        let (_, content) = self.shared_scratchpad_fetch(address).await?;
+        Ok(content.reader_keys())
+    }
+
+    /// Update the data of a shared scratchpad, keeping its readers and content key.
+    ///
+    /// Like [`Client::scratchpad_update`], this operation is free.
+    pub async fn shared_scratchpad_update(
+        &self,
+        owner: &SecretKey,
+        content_type: u64,
+        data: &Bytes,
+    ) -> Result<(), SharedScratchpadError> {
+        let address = ScratchpadAddress::new(owner.public_key());
+        let (current, content) = self.shared_scratchpad_fetch(&address).await?;
+        let content_key = content.content_key(owner)?;
+        let new_content = SharedContent::seal(&content_key, data, &content.reader_keys());
+        let scratchpad =
+            shared_scratchpad(owner, content_type, &new_content, current.counter() + 1)?;
+        self.scratchpad_put_update(scratchpad).await?;
+        debug!("Updated shared scratchpad at {address:?}");
+        Ok(())
+    }
+
+    /// Give a new reader access to a shared scratchpad, including to its current data.
+    pub async fn shared_scratchpad_add_reader(
+        &self,
+        owner: &SecretKey,
+        reader: PublicKey,
+    ) -> Result<(), SharedScratchpadError> {
+        let address = ScratchpadAddress::new(owner.public_key());
+        let (current, mut content) = self.shared_scratchpad_fetch(&address).await?;
+        let content_key = content.content_key(owner)?;
+        content.add_reader(&content_key, reader);
+        let scratchpad = shared_scratchpad(
+            owner,
+            current.data_encoding(),
+            &content,
+            current.counter() + 1,
+        )?;
+        self.scratchpad_put_update(scratchpad).await?;
+        debug!("Added reader {reader:?} to shared scratchpad at {address:?}");
+        Ok(())
+    }
+
+    /// Remove a reader from a shared scratchpad, rotating its content key.
+    ///
+    /// The removed reader can't read the next versions of the scratchpad, but might have kept the
+    /// versions it could read.
+    pub async fn shared_scratchpad_remove_reader(
+        &self,
+        owner: &SecretKey,
+        reader: &PublicKey,
+    ) -> Result<(), SharedScratchpadError> {
+        self.shared_scratchpad_rotate(owner, Some(reader)).await
+    }
+
+    /// Rotate the content key of a shared scratchpad, re-encrypting its data for the same readers.
+    ///
+    /// This is useful if the content key or a reader key might have been compromised.
+    pub async fn shared_scratchpad_rotate_key(
+        &self,
+        owner: &SecretKey,
+    ) -> Result<(), SharedScratchpadError> {
+        self.shared_scratchpad_rotate(owner, None).await
+    }
+
+    async fn shared_scratchpad_rotate(
+        &self,
+        owner: &SecretKey,
+        removed_reader: Option<&PublicKey>,
+    ) -> Result<(), SharedScratchpadError> {
+        let address = ScratchpadAddress::new(owner.public_key());
+        let (current, content) = self.shared_scratchpad_fetch(&address).await?;
+        let data = content.open(owner)?;
+        let readers: Vec<_> = content
+            .reader_keys()
+            .into_iter()
+            .filter(|pk| Some(pk) != removed_reader || *pk == owner.public_key())
+            .collect();
+
+        let new_content = SharedContent::seal(&SecretKey::random(), &data, &readers);
+        let scratchpad = shared_scratchpad(
+            owner,
+            current.data_encoding(),
+            &new_content,
+            current.counter() + 1,
+        )?;
+        self.scratchpad_put_update(scratchpad).await?;
+        debug!("Rotated the content key of shared scratchpad at {address:?}");
+        Ok(())
+    }
+
+    async fn shared_scratchpad_fetch(
+        &self,
+        address: &ScratchpadAddress,
+    ) -> Result<(Scratchpad, SharedContent), SharedScratchpadError> {
+        let scratchpad = self.scratchpad_get(address).await?;
+        let content = SharedContent::from_bytes(scratchpad.encrypted_data())?;
+        Ok((scratchpad, content))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn readers_decrypt_shared_content() {
+        let owner = SecretKey::random();
+        let (alice, bob) = (SecretKey::random(), SecretKey::random());
+        let content_key = SecretKey::random();
+        let data = Bytes::from("shared state");
+
+        let content = SharedContent::seal(
+            &content_key,
+            &data,
+            &[owner.public_key(), alice.public_key()],
+        );
+        let content = SharedContent::from_bytes(&content.to_bytes().unwrap()).unwrap();
+        assert_eq!(content.open(&owner).unwrap(), data);
+        assert_eq!(content.open(&alice).unwrap(), data);
+        assert!(matches!(
+            content.open(&bob),
+            Err(SharedScratchpadError::NotAReader(pk)) if pk == bob.public_key()
+        ));
+
+        let mut content = content;
+        content.add_reader(&content_key, bob.public_key());
+        content.add_reader(&content_key, bob.public_key());
+        assert_eq!(content.readers.len(), 3);
+        assert_eq!(content.open(&bob).unwrap(), data);
+
+        // the signed scratchpad carries the shared content
+        let scratchpad = shared_scratchpad(&owner, 7, &content, 1).unwrap();
+        assert!(scratchpad.verify_signature());
+        let stored = SharedContent::from_bytes(scratchpad.encrypted_data()).unwrap();
+        assert_eq!(stored, content);
+    }
+
+    #[test]
+    fn regular_scratchpads_are_not_shared() {
+        let owner = SecretKey::random();
+        let scratchpad = Scratchpad::new(&owner, 0, &Bytes::from("private"), 0);
+        assert!(SharedContent::from_bytes(scratchpad.encrypted_data()).is_err());
+    }
+}
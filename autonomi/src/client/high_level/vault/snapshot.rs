@@ -0,0 +1,236 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::time::{SystemTime, UNIX_EPOCH};
+
+use crate::client::high_level::mutable_blob::MutableBlobError;
+use crate::client::key_derivation::MainSecretKey;
+use crate::client::payment::PaymentOption;
+use crate::client::Client;
+use crate::graph::GraphError;
+use crate::register::{RegisterAddress, RegisterDataVisibility, RegisterError, SecretKey};
+use crate::scratchpad::ScratchpadError;
+use ant_evm::AttoTokens;
+use ant_networking::{GetRecordError, NetworkError};
+use ant_protocol::{Bytes, Error as ProtocolError};
+use serde::{Deserialize, Serialize};
+
+use super::{VaultContentType, VaultError, VaultSecretKey};
+
+/// Name of the register keeping the snapshots of a vault, derived from the vault key
+const VAULT_SNAPSHOTS_REGISTER_NAME: &str = "vault-snapshots";
+
+/// A previous content of a vault.
+///
+/// Before a vault is overwritten, its content is uploaded as private data and recorded in a register
+/// owned by a key derived from the vault key, so every previous content can be listed and restored.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VaultSnapshot {
+    /// When the content was replaced in the vault, in seconds since the unix epoch
+    pub timestamp: u64,
+    /// The content type of the vault at the time
+    pub content_type: VaultContentType,
+    /// The content of the vault
+    pub data: Bytes,
+}
+
+#[derive(Serialize, Deserialize)]
+enum VaultSnapshotVersioned {
+    V0(VaultSnapshot),
+}
+
+impl VaultSnapshot {
+    fn to_bytes(&self) -> Result<Bytes, VaultError> {
+        let bytes = rmp_serde::to_vec_named(&VaultSnapshotVersioned::V0(self.clone()))
+            .map_err(|e| VaultError::InvalidSnapshot(e.to_string()))?;
+        Ok(Bytes::from(bytes))
+    }
+
+    fn from_bytes(bytes: &Bytes) -> Result<Self, VaultError> {
+        match rmp_serde::from_slice(bytes) {
+            Ok(VaultSnapshotVersioned::V0(snapshot)) => Ok(snapshot),
+            Err(e) => Err(VaultError::InvalidSnapshot(e.to_string())),
+        }
+    }
+}
+
+/// The owner key of the register keeping the snapshots of a vault
+fn snapshots_register_key(secret_key: &VaultSecretKey) -> SecretKey {
+    Client::register_key_from_name(secret_key, VAULT_SNAPSHOTS_REGISTER_NAME)
+}
+
+impl Client {
+    /// Get the previous contents of a vault, from the oldest to the latest.
+    pub async fn vault_snapshots(
+        &self,
+        secret_key: &VaultSecretKey,
+    ) -> Result<Vec<VaultSnapshot>, VaultError> {
+        let owner = snapshots_register_key(secret_key);
+        let addr = RegisterAddress::new(owner.public_key());
+        if !self
+            .graph_entry_check_existance(&addr.to_underlying_graph_root())
+            .await?
+        {
+            return Ok(vec![]);
+        }
+
+        let versions = self
+            .register_history(&addr)
+            .with_data_key(Client::register_data_key(&owner))
+            .collect_data()
+            .await?;
+        versions
+            .iter()
+            .map(|(_, bytes)| VaultSnapshot::from_bytes(bytes))
+            .collect()
+    }
+
+    /// Restore the content of a vault from one of its snapshots, see [`Client::vault_snapshots`].
+    ///
+    /// Like any write, the current content of the vault is snapshotted first, so a restore can be undone.
+    /// Returns the total cost of the write and of the snapshot.
+    pub async fn vault_restore_snapshot(
+        &self,
+        secret_key: &VaultSecretKey,
+        index: usize,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, VaultError> {
+        let owner = snapshots_register_key(secret_key);
+        let addr = RegisterAddress::new(owner.public_key());
+        let mut snapshots = self.vault_snapshots(secret_key).await?;
+        if index >= snapshots.len() {
+            return Err(VaultError::SnapshotNotFound(index, snapshots.len()));
+        }
+        let snapshot = snapshots.swap_remove(index);
+        info!("Restoring vault from snapshot {index} of {addr}");
+        self.write_bytes_to_vault(
+            snapshot.data,
+            payment_option,
+            secret_key,
+            snapshot.content_type,
+        )
+        .await
+    }
+
+    /// Snapshot the current content of the vault before it is replaced by `new_data`.
+    ///
+    /// Nothing is done if the vault does not exist yet or if its content does not change.
+    /// If the current content cannot be decrypted or deserialized, e.g. because the vault is damaged,
+    /// no snapshot is taken so that the vault can still be overwritten.
+    /// Any other error, e.g. a network timeout, is returned so that the vault isn't overwritten without a snapshot.
+    pub(crate) async fn vault_snapshot_current(
+        &self,
+        main_secret_key: &MainSecretKey,
+        secret_key: &VaultSecretKey,
+        new_data: &Bytes,
+        new_content_type: VaultContentType,
+        payment_option: PaymentOption,
+    ) -> Result<AttoTokens, VaultError> {
+        let (data, content_type) = match self.blob_fetch(main_secret_key).await {
+            Ok(current) => current,
+            Err(MutableBlobError::GraphEntry(GraphError::Network(
+                NetworkError::GetRecordError(GetRecordError::RecordNotFound),
+            ))) => return Ok(AttoTokens::zero()),
+            Err(err) if is_unreadable_content(&err) => {
+                warn!("Could not read the current vault content, it will be overwritten without a snapshot: {err}");
+                return Ok(AttoTokens::zero());
+            }
+            Err(err) => return Err(err.into()),
+        };
+        if data == new_data && content_type == new_content_type {
+            return Ok(AttoTokens::zero());
+        }
+
+        let timestamp = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|d| d.as_secs())
+            .unwrap_or(0);
+        let snapshot = VaultSnapshot {
+            timestamp,
+            content_type,
+            data,
+        };
+        let bytes = snapshot.to_bytes()?;
+
+        let owner = snapshots_register_key(secret_key);
+        let addr = RegisterAddress::new(owner.public_key());
+        let visibility = RegisterDataVisibility::Private;
+        let cost = if self
+            .graph_entry_check_existance(&addr.to_underlying_graph_root())
+            .await?
+        {
+            self.register_update_data(&owner, bytes, visibility, payment_option)
+                .await?
+        } else {
+            let (cost, _) = self
+                .register_create_data(&owner, bytes, visibility, payment_option)
+                .await?;
+            cost
+        };
+        debug!("Snapshotted the previous vault content to {addr}");
+        Ok(cost)
+    }
+}
+
+/// Whether the content of a vault was fetched but cannot be decrypted or deserialized
+fn is_unreadable_content(err: &MutableBlobError) -> bool {
+    matches!(
+        err,
+        MutableBlobError::Protocol(
+            ProtocolError::ScratchpadCipherTextFailed | ProtocolError::ScratchpadCipherTextInvalid
+        ) | MutableBlobError::Scratchpad(
+            ScratchpadError::CouldNotDeserializeScratchPad(_) | ScratchpadError::Serialization
+        ) | MutableBlobError::GraphEntry(GraphError::Serialization)
+            | MutableBlobError::NotEnoughGraphDescendants(_)
+    )
+}
+
+impl From<RegisterError> for VaultError {
+    fn from(err: RegisterError) -> Self {
+        Self::Snapshot(Box::new(err))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn snapshot_serialization() {
+        let snapshot = VaultSnapshot {
+            timestamp: 1_700_000_000,
+            content_type: 42,
+            data: Bytes::from("previous user data"),
+        };
+        let bytes = snapshot.to_bytes().unwrap();
+        assert_eq!(VaultSnapshot::from_bytes(&bytes).unwrap(), snapshot);
+        assert!(VaultSnapshot::from_bytes(&Bytes::from("not a snapshot")).is_err());
+
+        let vault_key = VaultSecretKey::random();
+        assert_ne!(
+            snapshots_register_key(&vault_key),
+            snapshots_register_key(&VaultSecretKey::random())
+        );
+    }
+
+    #[test]
+    fn only_unreadable_content_skips_the_snapshot() {
+        assert!(is_unreadable_content(&MutableBlobError::Protocol(
+            ProtocolError::ScratchpadCipherTextInvalid
+        )));
+        assert!(is_unreadable_content(&MutableBlobError::GraphEntry(
+            GraphError::Serialization
+        )));
+        assert!(!is_unreadable_content(&MutableBlobError::GraphEntry(
+            GraphError::Network(NetworkError::GetRecordError(GetRecordError::RecordNotFound))
+        )));
+        assert!(!is_unreadable_content(&MutableBlobError::Scratchpad(
+            ScratchpadError::Missing
+        )));
+    }
+}
@@ -6,8 +6,11 @@
 // KIND, either express or implied. Please review the Licences for the specific language governing
 // permissions and limitations relating to use of the SAFE Network Software.
 
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 
+use crate::client::data_types::graph::GraphEntryAddress;
+use crate::client::data_types::pointer::PointerAddress;
+use crate::client::data_types::scratchpad::ScratchpadAddress;
 use crate::client::high_level::files::archive_private::PrivateArchiveDataMap;
 use crate::client::high_level::files::archive_public::ArchiveAddress;
 use crate::client::payment::PaymentOption;
@@ -18,6 +21,7 @@ use ant_evm::AttoTokens;
 use ant_protocol::Bytes;
 use serde::{Deserialize, Serialize};
 use std::sync::LazyLock;
+use std::time::{SystemTime, UNIX_EPOCH};
 
 use super::{app_name_to_vault_content_type, VaultContentType, VaultError, VaultSecretKey};
 
@@ -37,6 +41,65 @@ pub struct UserData {
     pub private_file_archives: HashMap<PrivateArchiveDataMap, String>,
     /// Owned register addresses, along with their names (can be empty)
     pub register_addresses: HashMap<RegisterAddress, String>,
+    /// Owned pointer addresses, along with their names (can be empty)
+    #[serde(default)]
+    pub pointers: HashMap<PointerAddress, String>,
+    /// Owned scratchpad addresses, along with their names (can be empty)
+    #[serde(default)]
+    pub scratchpads: HashMap<ScratchpadAddress, String>,
+    /// Addresses of the roots of owned graphs, along with their names (can be empty)
+    #[serde(default)]
+    pub graph_entries: HashMap<GraphEntryAddress, String>,
+    /// Data kept by apps, by app name. Each app is free to choose the format of its data,
+    /// it is recommended to version it so that it can evolve.
+    #[serde(default)]
+    pub app_data: BTreeMap<String, AppData>,
+}
+
+/// The data kept by an app in [`UserData`], along with when it was last updated.
+#[derive(Debug, Clone, Serialize, Deserialize, PartialEq, Eq)]
+pub struct AppData {
+    /// The data, in the format chosen by the app
+    pub data: Bytes,
+    /// When the data was last updated, as a UNIX timestamp in milliseconds.
+    /// Used to keep the most recent data when merging the user data of several devices.
+    pub updated: u64,
+}
+
+impl AppData {
+    /// Data updated now
+    pub fn new(data: Bytes) -> Self {
+        let updated = SystemTime::now()
+            .duration_since(UNIX_EPOCH)
+            .map(|elapsed| elapsed.as_millis() as u64)
+            .unwrap_or(0);
+        Self { data, updated }
+    }
+}
+
+/// UserData as stored in vaults
+#[derive(Serialize, Deserialize)]
+enum UserDataVersioned {
+    V1(UserData),
+}
+
+/// UserData as stored in vaults before it was versioned, only with archives and registers
+#[derive(Deserialize)]
+struct UserDataV0 {
+    file_archives: HashMap<ArchiveAddress, String>,
+    private_file_archives: HashMap<PrivateArchiveDataMap, String>,
+    register_addresses: HashMap<RegisterAddress, String>,
+}
+
+impl From<UserDataV0> for UserData {
+    fn from(v0: UserDataV0) -> Self {
+        Self {
+            file_archives: v0.file_archives,
+            private_file_archives: v0.private_file_archives,
+            register_addresses: v0.register_addresses,
+            ..Default::default()
+        }
+    }
 }
 
 /// Errors that can occur during the get operation.
@@ -104,16 +167,94 @@ impl UserData {
         self.private_file_archives.remove(&archive)
     }
 
+    /// Add a pointer. Returning `Option::Some` with the old name if the pointer was already in the set.
+    pub fn add_pointer(&mut self, pointer: PointerAddress, name: String) -> Option<String> {
+        self.pointers.insert(pointer, name)
+    }
+
+    /// Add a scratchpad. Returning `Option::Some` with the old name if the scratchpad was already in the set.
+    pub fn add_scratchpad(
+        &mut self,
+        scratchpad: ScratchpadAddress,
+        name: String,
+    ) -> Option<String> {
+        self.scratchpads.insert(scratchpad, name)
+    }
+
+    /// Add the root of a graph. Returning `Option::Some` with the old name if the graph entry was already in the set.
+    pub fn add_graph_entry(
+        &mut self,
+        graph_entry: GraphEntryAddress,
+        name: String,
+    ) -> Option<String> {
+        self.graph_entries.insert(graph_entry, name)
+    }
+
+    /// Get the data of an app.
+    pub fn app_data(&self, app: &str) -> Option<&Bytes> {
+        self.app_data.get(app).map(|app_data| &app_data.data)
+    }
+
+    /// Set the data of an app, marking it as updated now. Returning `Option::Some` with the previous data of the app if it had any.
+    pub fn set_app_data(&mut self, app: &str, data: Bytes) -> Option<Bytes> {
+        self.app_data
+            .insert(app.to_string(), AppData::new(data))
+            .map(|app_data| app_data.data)
+    }
+
+    /// Remove the data of an app. Returning `Option::Some` with the data of the app if it had any.
+    pub fn remove_app_data(&mut self, app: &str) -> Option<Bytes> {
+        self.app_data.remove(app).map(|app_data| app_data.data)
+    }
+
+    /// Merge with another UserData
+    ///
+    /// Note that for entries in both, the names and app data from the other UserData are the ones that are kept.
+    pub fn merge(&mut self, other: UserData) {
+        self.file_archives.extend(other.file_archives);
+        self.private_file_archives
+            .extend(other.private_file_archives);
+        self.register_addresses.extend(other.register_addresses);
+        self.pointers.extend(other.pointers);
+        self.scratchpads.extend(other.scratchpads);
+        self.graph_entries.extend(other.graph_entries);
+        self.app_data.extend(other.app_data);
+    }
+
+    /// Merge the UserData fetched from the vault into this local UserData
+    ///
+    /// Like [`UserData::merge`] the names from the vault are kept, but for the data of each app the most
+    /// recently updated one is kept, as it may have been updated locally or from another device since the last sync.
+    pub fn merge_from_vault(&mut self, vault: UserData) {
+        let mut app_data = std::mem::take(&mut self.app_data);
+        for (app, vault_app_data) in vault.app_data.iter() {
+            let local_is_newer = matches!(
+                app_data.get(app),
+                Some(local) if local.updated >= vault_app_data.updated
+            );
+            if !local_is_newer {
+                app_data.insert(app.clone(), vault_app_data.clone());
+            }
+        }
+        self.merge(vault);
+        self.app_data = app_data;
+    }
+
     /// To bytes
     pub fn to_bytes(&self) -> Result<Bytes, rmp_serde::encode::Error> {
-        let bytes = rmp_serde::to_vec(&self)?;
+        let bytes = rmp_serde::to_vec_named(&UserDataVersioned::V1(self.clone()))?;
         Ok(Bytes::from(bytes))
     }
 
-    /// From bytes
+    /// From bytes, also reads the UserData stored by previous versions
     pub fn from_bytes(bytes: Bytes) -> Result<Self, rmp_serde::decode::Error> {
-        let vault_content = rmp_serde::from_slice(&bytes)?;
-        Ok(vault_content)
+        match rmp_serde::from_slice(&bytes) {
+            Ok(UserDataVersioned::V1(user_data)) => Ok(user_data),
+            Err(err) => match rmp_serde::from_slice::<UserDataV0>(&bytes) {
+                Ok(v0) => Ok(v0.into()),
+                Err(_) => Err(err),
+            },
+        }
     }
 }
 
@@ -138,9 +279,9 @@ impl Client {
         Ok(vault)
     }
 
-    /// Put the user data to the vault
+    /// Put the user data to the vault, keeping the previous content of the vault as a snapshot.
     ///
-    /// Returns the total cost of the put operation
+    /// Returns the total cost of the put operation, including the snapshot
     pub async fn put_user_data_to_vault(
         &self,
         secret_key: &VaultSecretKey,
@@ -161,3 +302,94 @@ impl Client {
         Ok(total_cost)
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::data::DataAddress;
+    use bls::SecretKey;
+    use xor_name::XorName;
+
+    #[test]
+    fn reads_previous_user_data() {
+        let archive = DataAddress::new(XorName::random(&mut rand::thread_rng()));
+        let register = RegisterAddress::new(SecretKey::random().public_key());
+        let v0 = (
+            HashMap::from([(archive, "archive".to_string())]),
+            HashMap::<PrivateArchiveDataMap, String>::new(),
+            HashMap::from([(register, "register".to_string())]),
+        );
+        let bytes = Bytes::from(rmp_serde::to_vec(&v0).unwrap());
+
+        let user_data = UserData::from_bytes(bytes).unwrap();
+        assert_eq!(user_data.file_archives, v0.0);
+        assert_eq!(user_data.register_addresses, v0.2);
+        assert!(user_data.pointers.is_empty() && user_data.app_data.is_empty());
+    }
+
+    #[test]
+    fn new_fields_roundtrip_and_merge() {
+        let pk = SecretKey::random().public_key();
+        let mut user_data = UserData::new();
+        user_data.add_pointer(PointerAddress::new(pk), "pointer".into());
+        user_data.add_scratchpad(ScratchpadAddress::new(pk), "scratchpad".into());
+        user_data.add_graph_entry(GraphEntryAddress::new(pk), "graph".into());
+        user_data.set_app_data("app", Bytes::from("state v1"));
+
+        let bytes = user_data.to_bytes().unwrap();
+        assert_eq!(UserData::from_bytes(bytes).unwrap(), user_data);
+
+        let mut other = UserData::new();
+        other.set_app_data("app", Bytes::from("state v2"));
+        other.set_app_data("other app", Bytes::from("other state"));
+        user_data.merge(other);
+        assert_eq!(user_data.app_data("app"), Some(&Bytes::from("state v2")));
+        assert_eq!(user_data.app_data.len(), 2);
+        assert_eq!(user_data.pointers.len(), 1);
+    }
+
+    #[test]
+    fn merge_from_vault_keeps_newest_app_data() {
+        let app_data = |data: &'static str, updated| AppData {
+            data: Bytes::from(data),
+            updated,
+        };
+        let pk = SecretKey::random().public_key();
+        let mut local = UserData::new();
+        local.add_pointer(PointerAddress::new(pk), "local name".into());
+        local
+            .app_data
+            .insert("app".into(), app_data("local state", 20));
+        local
+            .app_data
+            .insert("synced app".into(), app_data("stale local state", 10));
+
+        let mut vault = UserData::new();
+        vault.add_pointer(PointerAddress::new(pk), "vault name".into());
+        vault
+            .app_data
+            .insert("app".into(), app_data("stale vault state", 10));
+        // updated from another device since the last sync
+        vault
+            .app_data
+            .insert("synced app".into(), app_data("vault state", 30));
+        vault
+            .app_data
+            .insert("other app".into(), app_data("other state", 5));
+
+        local.merge_from_vault(vault);
+        assert_eq!(local.app_data("app"), Some(&Bytes::from("local state")));
+        assert_eq!(
+            local.app_data("synced app"),
+            Some(&Bytes::from("vault state"))
+        );
+        assert_eq!(
+            local.app_data("other app"),
+            Some(&Bytes::from("other state"))
+        );
+        assert_eq!(
+            local.pointers.get(&PointerAddress::new(pk)),
+            Some(&"vault name".to_string())
+        );
+    }
+}
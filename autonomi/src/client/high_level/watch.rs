@@ -0,0 +1,285 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::time::Duration;
+
+use ant_networking::{GetRecordError, NetworkError};
+use futures::Stream;
+use tokio::sync::mpsc;
+
+use crate::client::data_types::graph::{GraphEntryAddress, GraphError};
+use crate::client::data_types::pointer::{Pointer, PointerAddress, PointerError, PointerTarget};
+use crate::client::data_types::scratchpad::{Scratchpad, ScratchpadAddress, ScratchpadError};
+use crate::client::high_level::register::{
+    ForkResolution, RegisterAddress, RegisterError, RegisterValue,
+};
+use crate::client::{Client, ClientEvent};
+
+/// Mutable data that can be watched for changes with [`Client::watch`].
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum WatchAddress {
+    Pointer(PointerAddress),
+    Scratchpad(ScratchpadAddress),
+    Register(RegisterAddress),
+}
+
+impl From<PointerAddress> for WatchAddress {
+    fn from(addr: PointerAddress) -> Self {
+        Self::Pointer(addr)
+    }
+}
+
+impl From<ScratchpadAddress> for WatchAddress {
+    fn from(addr: ScratchpadAddress) -> Self {
+        Self::Scratchpad(addr)
+    }
+}
+
+impl From<RegisterAddress> for WatchAddress {
+    fn from(addr: RegisterAddress) -> Self {
+        Self::Register(addr)
+    }
+}
+
+/// A new version of watched data.
+#[derive(Debug, Clone)]
+pub enum WatchUpdate {
+    Pointer(Pointer),
+    Scratchpad(Scratchpad),
+    Register {
+        address: RegisterAddress,
+        /// The head entry of the register
+        head: GraphEntryAddress,
+        value: RegisterValue,
+    },
+}
+
+/// Errors yielded by a [`Client::watch`] stream. The stream keeps polling after an error.
+#[derive(Debug, thiserror::Error)]
+pub enum WatchError {
+    #[error("Failed to get pointer: {0}")]
+    Pointer(#[from] PointerError),
+    #[error("Failed to get scratchpad: {0}")]
+    Scratchpad(#[from] ScratchpadError),
+    #[error("Failed to get register: {0}")]
+    Register(#[from] RegisterError),
+}
+
+/// How often watched data is polled.
+///
+/// Polling starts at `initial_interval`, and the interval is multiplied by `backoff_factor` each time
+/// nothing changed, up to `max_interval`. It goes back to `initial_interval` when a new version is found.
+#[derive(Debug, Clone)]
+pub struct WatchConfig {
+    pub initial_interval: Duration,
+    pub max_interval: Duration,
+    pub backoff_factor: u32,
+    /// How to resolve a forked register head
+    pub fork_resolution: ForkResolution,
+}
+
+impl Default for WatchConfig {
+    fn default() -> Self {
+        Self {
+            initial_interval: Duration::from_secs(5),
+            max_interval: Duration::from_secs(120),
+            backoff_factor: 2,
+            fork_resolution: ForkResolution::default(),
+        }
+    }
+}
+
+impl WatchConfig {
+    fn next_interval(&self, interval: Duration) -> Duration {
+        interval
+            .saturating_mul(self.backoff_factor)
+            .min(self.max_interval)
+    }
+}
+
+/// What identifies a version of watched data, newer versions being greater:
+/// the counter of pointers and scratchpads, and the counter of the head pointer of registers
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+struct Version(u64);
+
+impl Version {
+    fn is_newer_than(&self, last: Option<&Version>) -> bool {
+        last.map(|last| self > last).unwrap_or(true)
+    }
+}
+
+struct WatchState {
+    client: Client,
+    address: WatchAddress,
+    config: WatchConfig,
+    last_version: Option<Version>,
+    interval: Duration,
+    first_poll: bool,
+}
+
+impl Client {
+    /// Watch mutable data for changes, see [`Client::watch_with_config`].
+    pub fn watch(
+        &self,
+        address: impl Into<WatchAddress>,
+    ) -> impl Stream<Item = Result<WatchUpdate, WatchError>> + Send + 'static {
+        self.watch_with_config(address, WatchConfig::default())
+    }
+
+    /// Watch a pointer, a scratchpad or a register for changes, polling the network as set in `config`.
+    ///
+    /// The stream first yields the current version, if the data exists, and then every newer version found:
+    /// pointers and scratchpads by their counter, registers by the counter of their head pointer.
+    /// Older versions returned by lagging nodes are ignored. Errors are yielded and the stream keeps polling.
+    /// Every update is also sent as a [`ClientEvent::DataChanged`] if client events are enabled.
+    pub fn watch_with_config(
+        &self,
+        address: impl Into<WatchAddress>,
+        config: WatchConfig,
+    ) -> impl Stream<Item = Result<WatchUpdate, WatchError>> + Send + 'static {
+        let state = WatchState {
+            client: self.clone(),
+            address: address.into(),
+            interval: config.initial_interval,
+            config,
+            last_version: None,
+            first_poll: true,
+        };
+        futures::stream::unfold(state, |mut state| async move {
+            loop {
+                if !state.first_poll {
+                    tokio::time::sleep(state.interval).await;
+                }
+                state.first_poll = false;
+
+                match state.client.watch_poll(&state.address, &state.config).await {
+                    Ok(Some((version, update)))
+                        if version.is_newer_than(state.last_version.as_ref()) =>
+                    {
+                        debug!("New version of watched {:?}: {version:?}", state.address);
+                        state.last_version = Some(version);
+                        state.interval = state.config.initial_interval;
+                        state.client.send_data_changed_event(&update);
+                        return Some((Ok(update), state));
+                    }
+                    Ok(_) => {
+                        state.interval = state.config.next_interval(state.interval);
+                    }
+                    Err(err) => {
+                        warn!("Failed to poll watched {:?}: {err}", state.address);
+                        state.interval = state.config.next_interval(state.interval);
+                        return Some((Err(err), state));
+                    }
+                }
+            }
+        })
+    }
+
+    /// Get the current version of watched data, `None` if it does not exist yet
+    async fn watch_poll(
+        &self,
+        address: &WatchAddress,
+        config: &WatchConfig,
+    ) -> Result<Option<(Version, WatchUpdate)>, WatchError> {
+        match address {
+            WatchAddress::Pointer(addr) => {
+                let Some(pointer) = not_found_as_none(self.pointer_get(addr).await)? else {
+                    return Ok(None);
+                };
+                let version = Version(pointer.counter() as u64);
+                Ok(Some((version, WatchUpdate::Pointer(pointer))))
+            }
+            WatchAddress::Scratchpad(addr) => {
+                let pad = match self.scratchpad_get(addr).await {
+                    Ok(pad) => pad,
+                    Err(ScratchpadError::Network(NetworkError::GetRecordError(
+                        GetRecordError::RecordNotFound,
+                    ))) => return Ok(None),
+                    Err(err) => return Err(err.into()),
+                };
+                let version = Version(pad.counter());
+                Ok(Some((version, WatchUpdate::Scratchpad(pad))))
+            }
+            WatchAddress::Register(addr) => {
+                let pointer_addr = addr.to_underlying_head_pointer();
+                let Some(pointer) = not_found_as_none(self.pointer_get(&pointer_addr).await)?
+                else {
+                    return Ok(None);
+                };
+                // the head pointer is updated along with the head, its counter orders the heads
+                let version = Version(pointer.counter() as u64);
+                let head = match pointer.target() {
+                    PointerTarget::GraphEntryAddress(head) => *head,
+                    other => {
+                        return Err(RegisterError::InvalidHeadPointer(other.clone()).into());
+                    }
+                };
+                let heads = match self.graph_entry_get(&head).await {
+                    Ok(entry) => vec![entry],
+                    Err(GraphError::Fork(entries)) => entries,
+                    Err(err) => return Err(RegisterError::from(err).into()),
+                };
+                let entry = config.fork_resolution.resolve(&head, heads)?;
+                let update = WatchUpdate::Register {
+                    address: *addr,
+                    head,
+                    value: entry.content,
+                };
+                Ok(Some((version, update)))
+            }
+        }
+    }
+
+    /// Send the update without waiting, so that a full event channel doesn't stall the watch stream
+    fn send_data_changed_event(&self, update: &WatchUpdate) {
+        if let Some(channel) = self.client_event_sender.as_ref() {
+            let event = ClientEvent::DataChanged(Box::new(update.clone()));
+            match channel.try_send(event) {
+                Ok(()) => {}
+                Err(mpsc::error::TrySendError::Full(_)) => {
+                    warn!("Client event channel is full, dropping the data changed event");
+                }
+                Err(err) => error!("Failed to send client event: {err:?}"),
+            }
+        }
+    }
+}
+
+fn not_found_as_none(res: Result<Pointer, PointerError>) -> Result<Option<Pointer>, WatchError> {
+    match res {
+        Ok(pointer) => Ok(Some(pointer)),
+        Err(PointerError::Network(NetworkError::GetRecordError(
+            GetRecordError::RecordNotFound,
+        ))) => Ok(None),
+        Err(err) => Err(err.into()),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn versions_and_backoff() {
+        assert!(Version(0).is_newer_than(None));
+        assert!(Version(2).is_newer_than(Some(&Version(1))));
+        assert!(!Version(1).is_newer_than(Some(&Version(1))));
+        // an older version returned by a lagging node
+        assert!(!Version(0).is_newer_than(Some(&Version(1))));
+
+        let config = WatchConfig {
+            initial_interval: Duration::from_secs(1),
+            max_interval: Duration::from_secs(5),
+            backoff_factor: 3,
+            ..Default::default()
+        };
+        let interval = config.next_interval(config.initial_interval);
+        assert_eq!(interval, Duration::from_secs(3));
+        assert_eq!(config.next_interval(interval), Duration::from_secs(5));
+    }
+}
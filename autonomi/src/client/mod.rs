@@ -25,8 +25,11 @@ pub use data_types::scratchpad;
 mod high_level;
 pub use high_level::data;
 pub use high_level::files;
+pub use high_level::mutable_blob;
 pub use high_level::register;
+pub use high_level::shared_scratchpad;
 pub use high_level::vault;
+pub use high_level::watch;
 
 pub mod analyze;
 pub mod config;
@@ -39,22 +42,28 @@ pub mod quote;
 pub mod external_signer;
 
 // private module with utility functions
+mod chunk_cache;
 mod network;
+mod payment_ledger;
+mod payment_policy;
 mod utils;
 
 use ant_bootstrap::{BootstrapCacheStore, InitialPeersConfig};
 pub use ant_evm::Amount;
-use ant_evm::EvmNetwork;
+use ant_evm::{AttoTokens, EvmNetwork};
 use ant_networking::{
     interval, multiaddr_is_global, Network, NetworkBuilder, NetworkError, NetworkEvent,
 };
 use ant_protocol::{version::IDENTIFY_PROTOCOL_STR, NetworkAddress};
-use config::{ClientConfig, ClientOperatingStrategy};
+use chunk_cache::ChunkCache;
+use config::{ChunkCacheStats, ClientConfig, ClientOperatingStrategy, PaymentPolicy};
 use libp2p::{identity::Keypair, Multiaddr};
 use payment::PayError;
+use payment_ledger::PaymentLedger;
+use payment_policy::SessionSpending;
 use quote::CostError;
-use std::{collections::HashSet, time::Duration};
-use tokio::sync::{mpsc, watch};
+use std::{collections::HashSet, sync::Arc, time::Duration};
+use tokio::sync::{mpsc, watch as shutdown_watch};
 
 /// Time before considering the connection timed out.
 pub const CONNECT_TIMEOUT_SECS: u64 = 10;
@@ -86,8 +95,18 @@ pub struct Client {
     evm_network: EvmNetwork,
     /// The configuration for operations on the client.
     config: ClientOperatingStrategy,
+    /// The on-disk cache of fetched chunks, if enabled.
+    pub(crate) chunk_cache: Option<Arc<ChunkCache>>,
+    /// The limits on what the client may spend.
+    pub(crate) payment_policy: PaymentPolicy,
+    /// What the client spent since it was initialized.
+    pub(crate) session_spending: Arc<SessionSpending>,
+    /// The local record of the payments made, if enabled.
+    pub(crate) payment_ledger: Option<Arc<PaymentLedger>>,
+    /// Whether the hash of the content of uploaded files is recorded in their archive.
+    pub(crate) content_hashes: bool,
     // Shutdown signal for child tasks. Sends signal when dropped.
-    _shutdown_tx: watch::Sender<bool>,
+    _shutdown_tx: shutdown_watch::Sender<bool>,
 }
 
 /// Error returned by [`Client::init`].
@@ -156,15 +175,13 @@ impl Client {
     ///
     /// See [`Client::init_with_config`].
     pub async fn init_local() -> Result<Self, ConnectError> {
-        Self::init_with_config(ClientConfig {
-            init_peers_config: InitialPeersConfig {
+        let config = ClientConfig::default()
+            .with_init_peers_config(InitialPeersConfig {
                 local: true,
                 ..Default::default()
-            },
-            evm_network: EvmNetwork::new(true).unwrap_or_default(),
-            strategy: Default::default(),
-        })
-        .await
+            })
+            .with_evm_network(EvmNetwork::new(true).unwrap_or_default());
+        Self::init_with_config(config).await
     }
 
     /// Initialize a client that bootstraps from a list of peers.
@@ -184,16 +201,14 @@ impl Client {
         // Any global address makes the client non-local
         let local = !peers.iter().any(multiaddr_is_global);
 
-        Self::init_with_config(ClientConfig {
-            init_peers_config: InitialPeersConfig {
+        let config = ClientConfig::default()
+            .with_init_peers_config(InitialPeersConfig {
                 local,
                 addrs: peers,
                 ..Default::default()
-            },
-            evm_network: EvmNetwork::new(local).unwrap_or_default(),
-            strategy: Default::default(),
-        })
-        .await
+            })
+            .with_evm_network(EvmNetwork::new(local).unwrap_or_default());
+        Self::init_with_config(config).await
     }
 
     /// Initialize the client with the given configuration.
@@ -229,15 +244,43 @@ impl Client {
         receiver.await.expect("sender should not close")?;
         debug!("Enough peers were added to our routing table, initialization complete");
 
+        let chunk_cache = config.chunk_cache.as_ref().and_then(|cache_config| {
+            ChunkCache::open(cache_config)
+                .inspect_err(|err| {
+                    warn!(
+                        "Failed to open the chunk cache at {:?}, continuing without it: {err}",
+                        cache_config.dir
+                    )
+                })
+                .ok()
+                .map(Arc::new)
+        });
+
         Ok(Self {
             network,
             client_event_sender: None,
             evm_network: config.evm_network,
             config: config.strategy,
+            chunk_cache,
+            payment_policy: config.payment_policy,
+            session_spending: Default::default(),
+            payment_ledger: config
+                .payment_ledger
+                .map(|path| Arc::new(PaymentLedger::new(path))),
+            content_hashes: false,
             _shutdown_tx: shutdown_tx,
         })
     }
 
+    /// Record the SHA-256 hash of the content of the files uploaded from a directory in their archive,
+    /// see [`crate::files::ContentInfo::hash`], so that downloads can be checked against it.
+    ///
+    /// The content is hashed as it is encrypted, without reading the files again.
+    pub fn enable_content_hashes(&mut self) {
+        self.content_hashes = true;
+        debug!("Content hashes of uploaded files are enabled");
+    }
+
     /// Receive events from the client.
     pub fn enable_client_events(&mut self) -> mpsc::Receiver<ClientEvent> {
         let (client_event_sender, client_event_receiver) =
@@ -251,12 +294,26 @@ impl Client {
     pub fn evm_network(&self) -> &EvmNetwork {
         &self.evm_network
     }
+
+    /// Statistics of the chunk cache, `None` if it is disabled, see [`ClientConfig::chunk_cache`].
+    pub fn chunk_cache_stats(&self) -> Option<ChunkCacheStats> {
+        self.chunk_cache.as_ref().map(|cache| cache.stats())
+    }
+
+    /// Total amount paid since the client was initialized, see [`ClientConfig::payment_policy`].
+    pub fn session_spent(&self) -> AttoTokens {
+        self.session_spending.spent()
+    }
 }
 
 fn build_client_and_run_swarm(
     init_peers_config: &InitialPeersConfig,
     initial_peers: Vec<Multiaddr>,
-) -> (watch::Sender<bool>, Network, mpsc::Receiver<NetworkEvent>) {
+) -> (
+    shutdown_watch::Sender<bool>,
+    Network,
+    mpsc::Receiver<NetworkEvent>,
+) {
     let mut network_builder = NetworkBuilder::new(
         Keypair::generate_ed25519(),
         init_peers_config.local,
@@ -278,7 +335,7 @@ fn build_client_and_run_swarm(
 
     // TODO: Implement graceful SwarmDriver shutdown for client.
     // Create a shutdown signal channel
-    let (shutdown_tx, shutdown_rx) = watch::channel(false);
+    let (shutdown_tx, shutdown_rx) = shutdown_watch::channel(false);
 
     let _swarm_driver = ant_networking::time::spawn(swarm_driver.run(shutdown_rx));
 
@@ -290,7 +347,7 @@ fn build_client_and_run_swarm(
 async fn handle_event_receiver(
     mut event_receiver: mpsc::Receiver<NetworkEvent>,
     sender: futures::channel::oneshot::Sender<Result<(), ConnectError>>,
-    mut shutdown_rx: watch::Receiver<bool>,
+    mut shutdown_rx: shutdown_watch::Receiver<bool>,
 ) {
     // We switch this to `None` when we've sent the oneshot 'connect' result.
     let mut sender = Some(sender);
@@ -358,9 +415,14 @@ async fn handle_event_receiver(
 }
 
 /// Events that can be broadcasted by the client.
+///
+/// New events may be added, so matches on them need a wildcard arm.
 #[derive(Debug, Clone)]
+#[non_exhaustive]
 pub enum ClientEvent {
     UploadComplete(UploadSummary),
+    /// A new version of data watched with [`Client::watch`]
+    DataChanged(Box<watch::WatchUpdate>),
 }
 
 /// Summary of an upload operation.
@@ -8,12 +8,22 @@
 
 use crate::client::quote::{DataTypes, StoreQuote};
 use crate::Client;
-use ant_evm::{EncodedPeerId, EvmWallet, EvmWalletError, ProofOfPayment};
-use std::collections::HashMap;
+use ant_evm::{
+    EncodedPeerId, EvmWallet, EvmWalletError, PayForQuotesError, ProofOfPayment, QuoteHash, TxHash,
+};
+use bytes::Bytes;
+use serde::{Deserialize, Serialize};
+use std::collections::{BTreeMap, HashMap};
+use std::sync::Arc;
 use xor_name::XorName;
 
+use super::payment_ledger::payment_records;
+use super::payment_policy::PaymentPolicyError;
 use super::quote::CostError;
 
+pub use super::payment_ledger::{
+    PaymentFilter, PaymentLedger, PaymentLedgerError, PaymentRecord, QuotePaymentRecord,
+};
 pub use crate::{Amount, AttoTokens};
 
 /// Contains the proof of payments for each XOR address and the amount paid
@@ -21,6 +31,29 @@ pub type Receipt = HashMap<XorName, (ProofOfPayment, AttoTokens)>;
 
 pub type AlreadyPaidAddressesCount = usize;
 
+/// How many times the quotes left unpaid by a partially failed payment are paid again.
+const PARTIAL_PAYMENT_RETRIES: usize = 2;
+
+/// This type essentially wraps a [`Receipt`] in a version marker, so that its encoding can evolve.
+/// Serialize it with [`receipt_to_bytes`] and deserialize it with [`receipt_from_bytes`].
+#[derive(Serialize, Deserialize)]
+pub enum ReceiptVersioned {
+    V0(Receipt),
+}
+
+/// Serialize a [`Receipt`], e.g. to pay for an upload on one machine and do the upload on another.
+pub fn receipt_to_bytes(receipt: &Receipt) -> Result<Bytes, rmp_serde::encode::Error> {
+    let versioned = ReceiptVersioned::V0(receipt.clone());
+    Ok(Bytes::from(rmp_serde::to_vec_named(&versioned)?))
+}
+
+/// Deserialize a [`Receipt`] serialized with [`receipt_to_bytes`].
+pub fn receipt_from_bytes(bytes: &[u8]) -> Result<Receipt, rmp_serde::decode::Error> {
+    // Currently we have only `V0`. If we add `V1`, then we need an upgrade/migration path here.
+    let ReceiptVersioned::V0(receipt) = rmp_serde::from_slice(bytes)?;
+    Ok(receipt)
+}
+
 /// Errors that can occur during the pay operation.
 #[derive(Debug, thiserror::Error)]
 pub enum PayError {
@@ -34,6 +67,32 @@ pub enum PayError {
     SelfEncryption(#[from] crate::self_encryption::Error),
     #[error("Cost error: {0:?}")]
     Cost(#[from] CostError),
+    #[error("Payment refused by the payment policy: {0}")]
+    Policy(#[from] PaymentPolicyError),
+    /// Some of the payments went through before the error occurred.
+    /// The receipt covers the addresses that were paid for, so they can be skipped when retrying.
+    #[error("Payment failed after paying for {} addresses: {error}", .receipt.len())]
+    PartialPayment {
+        error: Box<PayError>,
+        receipt: Receipt,
+    },
+    /// The addresses were paid for along with those of other uploads, and that payment failed.
+    #[error("Payment shared with other uploads failed: {0}")]
+    Shared(Arc<PayError>),
+    #[error("The payment was aborted before it completed")]
+    BatchAborted,
+}
+
+impl PayError {
+    /// The receipt for the addresses that were paid for before the error occurred, if any.
+    /// Pass it back with [`PaymentOption::ReceiptAndWallet`] to only pay for the remaining addresses.
+    pub fn partial_receipt(&self) -> Option<&Receipt> {
+        match self {
+            PayError::PartialPayment { receipt, .. } => Some(receipt),
+            PayError::Shared(error) => error.partial_receipt(),
+            _ => None,
+        }
+    }
 }
 
 pub fn receipt_from_store_quotes(quotes: StoreQuote) -> Receipt {
@@ -63,6 +122,31 @@ pub fn receipt_from_store_quotes(quotes: StoreQuote) -> Receipt {
     receipt
 }
 
+/// Build a receipt for the addresses whose quotes were all paid in the given transactions.
+/// Quotes with a zero price don't need a transaction and count as paid.
+fn receipt_from_partial_payment(
+    quotes: StoreQuote,
+    tx_hashes_by_quote: &BTreeMap<QuoteHash, TxHash>,
+) -> Receipt {
+    let paid = quotes
+        .0
+        .into_iter()
+        .filter(|(content_addr, quote_for_address)| {
+            let (paid, unpaid): (Vec<_>, Vec<_>) = quote_for_address
+                .0
+                .iter()
+                .filter(|(_, _, price)| !price.is_zero())
+                .partition(|(_, quote, _)| tx_hashes_by_quote.contains_key(&quote.hash()));
+            if !paid.is_empty() && !unpaid.is_empty() {
+                warn!("Payment for {content_addr:?} only partially went through");
+            }
+            unpaid.is_empty()
+        })
+        .collect();
+
+    receipt_from_store_quotes(StoreQuote(paid))
+}
+
 /// Payment options for data payments.
 #[derive(Clone)]
 pub enum PaymentOption {
@@ -70,6 +154,9 @@ pub enum PaymentOption {
     Wallet(EvmWallet),
     /// When data was already paid for, use the receipt
     Receipt(Receipt),
+    /// Use the receipt for the addresses it covers and pay for the rest using the evm wallet,
+    /// e.g. to resume after a [`PayError::PartialPayment`]
+    ReceiptAndWallet(Receipt, EvmWallet),
 }
 
 impl From<EvmWallet> for PaymentOption {
@@ -99,14 +186,60 @@ impl Client {
     ) -> Result<(Receipt, AlreadyPaidAddressesCount), PayError> {
         match payment_option {
             PaymentOption::Wallet(wallet) => {
-                let (receipt, skipped) = self.pay(data_type, content_addrs, &wallet).await?;
-                Ok((receipt, skipped))
+                self.pay_with_receipt(data_type, content_addrs, &wallet, Receipt::new())
+                    .await
             }
             PaymentOption::Receipt(receipt) => Ok((receipt, 0)),
+            PaymentOption::ReceiptAndWallet(receipt, wallet) => {
+                self.pay_with_receipt(data_type, content_addrs, &wallet, receipt)
+                    .await
+            }
+        }
+    }
+
+    /// Pay for the content addrs not covered by the given receipt yet.
+    ///
+    /// If the payment fails after some of it went through, the error carries the receipt
+    /// for everything that was paid for, including the given receipt.
+    pub(crate) async fn pay_with_receipt(
+        &self,
+        data_type: DataTypes,
+        content_addrs: impl Iterator<Item = (XorName, usize)>,
+        wallet: &EvmWallet,
+        mut receipt: Receipt,
+    ) -> Result<(Receipt, AlreadyPaidAddressesCount), PayError> {
+        let to_pay: Vec<_> = content_addrs
+            .filter(|(content_addr, _)| !receipt.contains_key(content_addr))
+            .collect();
+        if to_pay.is_empty() {
+            return Ok((receipt, 0));
+        }
+
+        match self.pay(data_type, to_pay.into_iter(), wallet).await {
+            Ok((paid, skipped)) => {
+                receipt.extend(paid);
+                Ok((receipt, skipped))
+            }
+            Err(PayError::PartialPayment {
+                error,
+                receipt: paid,
+            }) => {
+                receipt.extend(paid);
+                Err(PayError::PartialPayment { error, receipt })
+            }
+            Err(err) if receipt.is_empty() => Err(err),
+            Err(err) => Err(PayError::PartialPayment {
+                error: Box::new(err),
+                receipt,
+            }),
         }
     }
 
     /// Pay for the content addrs and get the proof of payment.
+    ///
+    /// If some of the payment transactions went through before one failed, the quotes left unpaid are
+    /// paid again, keeping the quotes that were paid. If that keeps failing, a [`PayError::PartialPayment`]
+    /// is returned with the receipt for the addresses whose quotes were all paid for.
     pub(crate) async fn pay(
         &self,
         data_type: DataTypes,
@@ -122,22 +255,37 @@ impl Client {
         let quotes = self.get_store_quotes(data_type, content_addrs).await?;
 
         if !quotes.is_empty() {
-            // Make sure nobody else can use the wallet while we are paying
-            debug!("Waiting for wallet lock");
-            let lock_guard = wallet.lock().await;
-            debug!("Locked wallet");
-
-            // TODO: the error might contain some succeeded quote payments as well. These should be returned on err, so that they can be skipped when retrying.
-            // TODO: retry when it fails?
-            // Execute payments
-            let _payments = wallet
-                .pay_for_quotes(quotes.payments())
-                .await
-                .map_err(|err| PayError::from(err.0))?;
-
-            // payment is done, unlock the wallet for other threads
-            drop(lock_guard);
-            debug!("Unlocked wallet");
+            self.payment_policy.check(&quotes).await?;
+            let total = quotes.price();
+            self.session_spending
+                .reserve(total, self.payment_policy.max_per_session)?;
+
+            let (tx_hashes_by_quote, result) = pay_for_quotes_with_retries(&quotes, wallet).await;
+            self.record_payments(&quotes, &tx_hashes_by_quote, data_type, wallet)
+                .await;
+
+            if let Err(err) = result {
+                // the quotes paid for addresses left partially paid were spent as well
+                let paid = quotes
+                    .payments()
+                    .iter()
+                    .filter(|(quote_hash, _, _)| tx_hashes_by_quote.contains_key(quote_hash))
+                    .map(|(_, _, amount)| *amount)
+                    .sum::<Amount>();
+                self.session_spending.release(total.saturating_sub(paid));
+                let receipt = receipt_from_partial_payment(quotes, &tx_hashes_by_quote);
+                if receipt.is_empty() {
+                    return Err(PayError::from(err));
+                }
+                error!(
+                    "Payment failed after paying for {} addresses: {err:?}",
+                    receipt.len()
+                );
+                return Err(PayError::PartialPayment {
+                    error: Box::new(PayError::from(err)),
+                    receipt,
+                });
+            }
         }
 
         let skipped_chunks = number_of_content_addrs - quotes.len();
@@ -151,4 +299,158 @@ impl Client {
 
         Ok((receipt, skipped_chunks))
     }
+
+    /// Record the payments in the payment ledger, if enabled.
+    /// Failing to do so is logged, the payment itself went through.
+    async fn record_payments(
+        &self,
+        quotes: &StoreQuote,
+        tx_hashes_by_quote: &BTreeMap<QuoteHash, TxHash>,
+        data_type: DataTypes,
+        wallet: &EvmWallet,
+    ) {
+        let Some(ledger) = self.payment_ledger.clone() else {
+            return;
+        };
+        let records = payment_records(quotes, tx_hashes_by_quote, data_type, wallet.address());
+        let recording = tokio::task::spawn_blocking(move || {
+            if let Err(err) = ledger.record(&records) {
+                error!(
+                    "Failed to record {} payments in the ledger at {:?}: {err}",
+                    records.len(),
+                    ledger.path()
+                );
+            }
+        });
+        if let Err(err) = recording.await {
+            error!("Failed to record payments in the ledger: {err}");
+        }
+    }
+}
+
+/// Pay for the quotes, paying again for the quotes left unpaid when only some of the transactions went through.
+///
+/// Returns the transactions of all the quotes that were paid, and the last error if some were left unpaid.
+async fn pay_for_quotes_with_retries(
+    quotes: &StoreQuote,
+    wallet: &EvmWallet,
+) -> (BTreeMap<QuoteHash, TxHash>, Result<(), EvmWalletError>) {
+    let mut tx_hashes_by_quote = BTreeMap::new();
+    let mut retries = 0;
+    loop {
+        let unpaid: Vec<_> = quotes
+            .payments()
+            .into_iter()
+            .filter(|(quote_hash, _, amount)| {
+                !amount.is_zero() && !tx_hashes_by_quote.contains_key(quote_hash)
+            })
+            .collect();
+
+        // Make sure nobody else can use the wallet while we are paying
+        debug!("Waiting for wallet lock");
+        let lock_guard = wallet.lock().await;
+        debug!("Locked wallet");
+
+        // Execute payments
+        let result = wallet.pay_for_quotes(unpaid).await;
+
+        // payment is done, unlock the wallet for other threads
+        drop(lock_guard);
+        debug!("Unlocked wallet");
+
+        match result {
+            Ok(paid) => {
+                tx_hashes_by_quote.extend(paid);
+                return (tx_hashes_by_quote, Ok(()));
+            }
+            Err(PayForQuotesError(err, paid)) => {
+                tx_hashes_by_quote.extend(paid);
+                // only retry when some of the payments went through, otherwise the wallet is not usable
+                if tx_hashes_by_quote.is_empty() || retries >= PARTIAL_PAYMENT_RETRIES {
+                    return (tx_hashes_by_quote, Err(err));
+                }
+                retries += 1;
+                warn!("Payment partially failed, retry #{retries} for the unpaid quotes: {err:?}");
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::client::quote::QuoteForAddress;
+    use ant_evm::{PaymentQuote, QuotingMetrics, RewardsAddress};
+    use libp2p::PeerId;
+
+    #[test]
+    fn receipt_serialization_round_trip() {
+        let mut receipt = Receipt::new();
+        receipt.insert(
+            XorName::random(&mut rand::thread_rng()),
+            (
+                ProofOfPayment {
+                    peer_quotes: vec![],
+                },
+                AttoTokens::from_u64(42),
+            ),
+        );
+
+        let bytes = receipt_to_bytes(&receipt).unwrap();
+        let deserialized = receipt_from_bytes(&bytes).unwrap();
+        assert_eq!(deserialized.len(), 1);
+        for (name, (_, amount)) in &receipt {
+            assert_eq!(deserialized.get(name).map(|(_, a)| a), Some(amount));
+        }
+
+        assert!(receipt_from_bytes(b"not a receipt").is_err());
+    }
+
+    fn dummy_quote(content: XorName, signature: u8) -> PaymentQuote {
+        PaymentQuote {
+            content,
+            timestamp: std::time::SystemTime::now(),
+            quoting_metrics: QuotingMetrics {
+                data_size: 0,
+                data_type: 0,
+                close_records_stored: 0,
+                records_per_type: vec![],
+                max_records: 0,
+                received_payment_count: 0,
+                live_time: 0,
+                network_density: None,
+                network_size: None,
+            },
+            rewards_address: RewardsAddress::ZERO,
+            pub_key: vec![],
+            signature: vec![signature],
+        }
+    }
+
+    #[test]
+    fn partial_payment_receipt_only_covers_fully_paid_addresses() {
+        let mut rng = rand::thread_rng();
+        let mut quotes = HashMap::new();
+        let mut tx_hashes_by_quote = BTreeMap::new();
+        let (paid, unpaid) = (XorName::random(&mut rng), XorName::random(&mut rng));
+        for (content_addr, paid_quotes) in [(paid, 3), (unpaid, 2)] {
+            let mut quote_for_address = vec![];
+            for i in 0..3 {
+                let quote = dummy_quote(content_addr, i);
+                if i < paid_quotes {
+                    tx_hashes_by_quote.insert(quote.hash(), TxHash::ZERO);
+                }
+                quote_for_address.push((PeerId::random(), quote, Amount::from(1)));
+            }
+            // free quotes don't need a transaction
+            quote_for_address.push((PeerId::random(), dummy_quote(content_addr, 3), Amount::ZERO));
+            quotes.insert(content_addr, QuoteForAddress(quote_for_address));
+        }
+
+        let receipt = receipt_from_partial_payment(StoreQuote(quotes), &tx_hashes_by_quote);
+        assert_eq!(receipt.len(), 1);
+        let (proof, price) = receipt.get(&paid).expect("paid address in receipt");
+        assert_eq!(proof.peer_quotes.len(), 4);
+        assert_eq!(price.as_atto(), Amount::from(3));
+    }
 }
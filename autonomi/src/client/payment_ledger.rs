@@ -0,0 +1,364 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::collections::BTreeMap;
+use std::fs::{self, OpenOptions};
+use std::io::Write;
+use std::path::{Path, PathBuf};
+use std::sync::Mutex;
+
+use ant_evm::{AttoTokens, EvmAddress, QuoteHash, RewardsAddress, TxHash};
+use ant_networking::time::{Duration, SystemTime};
+use serde::{Deserialize, Serialize};
+use xor_name::XorName;
+
+use crate::client::quote::{DataTypes, StoreQuote};
+
+/// Errors that can occur when reading or writing the [`PaymentLedger`].
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentLedgerError {
+    #[error("Input/output failure: {0}")]
+    Io(#[from] std::io::Error),
+    #[error("Failed to serialize payment record: {0}")]
+    Serialization(#[from] rmp_serde::encode::Error),
+    #[error("Failed to deserialize payment record: {0}")]
+    Deserialization(#[from] rmp_serde::decode::Error),
+}
+
+/// The payment for the storage of a piece of data, as recorded in the [`PaymentLedger`].
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct PaymentRecord {
+    /// When the payment was made, as a UNIX timestamp
+    pub timestamp: u64,
+    /// Address of the wallet that paid
+    pub wallet: EvmAddress,
+    /// Address of the data paid for
+    pub content_addr: XorName,
+    /// Type of the data paid for
+    pub data_type: DataTypes,
+    /// Total amount paid for the data
+    pub price: AttoTokens,
+    /// Payment of each quote for the data
+    pub quote_payments: Vec<QuotePaymentRecord>,
+}
+
+/// The payment of a single node quote.
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct QuotePaymentRecord {
+    pub quote_hash: QuoteHash,
+    /// Address of the node that is paid
+    pub rewards_address: RewardsAddress,
+    pub amount: AttoTokens,
+    /// Transaction the quote was paid in, `None` for free quotes
+    pub tx_hash: Option<TxHash>,
+}
+
+/// This type essentially wraps a [`PaymentRecord`] in a version marker, so that its encoding can evolve.
+#[derive(Serialize, Deserialize)]
+enum PaymentRecordVersioned {
+    V0(PaymentRecord),
+}
+
+/// Selects the records returned by [`PaymentLedger::query`], every set criterion must match.
+#[derive(Debug, Clone, Default)]
+pub struct PaymentFilter {
+    /// Only payments made at or after this UNIX timestamp
+    pub since: Option<u64>,
+    /// Only payments made before this UNIX timestamp
+    pub until: Option<u64>,
+    /// Only payments made by this wallet
+    pub wallet: Option<EvmAddress>,
+    /// Only payments for this type of data
+    pub data_type: Option<DataTypes>,
+    /// Only payments for the data at this address
+    pub content_addr: Option<XorName>,
+}
+
+impl PaymentFilter {
+    pub fn matches(&self, record: &PaymentRecord) -> bool {
+        self.since
+            .map(|since| record.timestamp >= since)
+            .unwrap_or(true)
+            && self
+                .until
+                .map(|until| record.timestamp < until)
+                .unwrap_or(true)
+            && self
+                .wallet
+                .map(|wallet| record.wallet == wallet)
+                .unwrap_or(true)
+            && self
+                .data_type
+                .map(|data_type| record.data_type == data_type)
+                .unwrap_or(true)
+            && self
+                .content_addr
+                .map(|content_addr| record.content_addr == content_addr)
+                .unwrap_or(true)
+    }
+}
+
+/// Size of the big-endian length written before each record in the ledger file
+const RECORD_LENGTH_SIZE: usize = 4;
+
+/// Local, append-only ledger of the payments made by the client, see [`crate::ClientConfig::payment_ledger`].
+///
+/// Each payment for a piece of data is recorded with its quotes, the transactions they were paid in, and the price.
+/// The records are appended to the file as they are made, each prefixed with its length, so the ledger is never
+/// rewritten and a record that cannot be read doesn't hide the ones after it.
+pub struct PaymentLedger {
+    path: PathBuf,
+    // Length of the complete records at the start of the ledger file, once checked.
+    // Also serializes the appends to the ledger file.
+    complete_len: Mutex<Option<u64>>,
+}
+
+impl PaymentLedger {
+    pub fn new(path: PathBuf) -> Self {
+        Self {
+            path,
+            complete_len: Mutex::new(None),
+        }
+    }
+
+    /// Path of the ledger on disk.
+    pub fn path(&self) -> &Path {
+        &self.path
+    }
+
+    /// Append the records to the ledger.
+    ///
+    /// A record truncated by an interrupted write at the end of the ledger is removed first,
+    /// so that it doesn't run into the new records.
+    pub(crate) fn record(&self, records: &[PaymentRecord]) -> Result<(), PaymentLedgerError> {
+        let mut bytes = vec![];
+        for record in records {
+            let encoded = rmp_serde::to_vec_named(&PaymentRecordVersioned::V0(record.clone()))?;
+            push_record_frame(&mut bytes, &encoded)?;
+        }
+
+        let mut complete_len = self.complete_len.lock().expect("ledger lock poisoned");
+        if let Some(parent) = self.path.parent() {
+            fs::create_dir_all(parent)?;
+        }
+        let mut file = OpenOptions::new()
+            .create(true)
+            .append(true)
+            .open(&self.path)?;
+        let file_len = file.metadata()?.len();
+        let end = match *complete_len {
+            Some(len) if len == file_len => len,
+            _ => record_frames(&fs::read(&self.path)?).1 as u64,
+        };
+        if end < file_len {
+            warn!(
+                "Removing a truncated record at the end of the payment ledger {:?}",
+                self.path
+            );
+            file.set_len(end)?;
+        }
+        // a single write, so that an interruption leaves at most the last records truncated
+        file.write_all(&bytes)?;
+        *complete_len = Some(end + bytes.len() as u64);
+        Ok(())
+    }
+
+    /// The recorded payments matching the filter, from the oldest to the most recent.
+    ///
+    /// Records that cannot be read are skipped.
+    pub fn query(&self, filter: &PaymentFilter) -> Result<Vec<PaymentRecord>, PaymentLedgerError> {
+        let bytes = match fs::read(&self.path) {
+            Ok(bytes) => bytes,
+            Err(err) if err.kind() == std::io::ErrorKind::NotFound => return Ok(vec![]),
+            Err(err) => return Err(err.into()),
+        };
+
+        let (frames, end) = record_frames(&bytes);
+        if end < bytes.len() {
+            // only the end of the ledger can be truncated, by an interrupted write
+            warn!(
+                "Ignoring a truncated record at the end of the payment ledger {:?}",
+                self.path
+            );
+        }
+        let mut records = vec![];
+        for frame in frames {
+            match rmp_serde::from_slice(frame) {
+                Ok(PaymentRecordVersioned::V0(record)) => {
+                    if filter.matches(&record) {
+                        records.push(record);
+                    }
+                }
+                Err(err) => {
+                    warn!(
+                        "Skipping a corrupted record of the payment ledger {:?}: {err}",
+                        self.path
+                    );
+                }
+            }
+        }
+        Ok(records)
+    }
+}
+
+/// Append a record to the bytes to write to a file of length-prefixed records, like the ledger.
+pub(crate) fn push_record_frame(bytes: &mut Vec<u8>, record: &[u8]) -> std::io::Result<()> {
+    let len = u32::try_from(record.len()).map_err(std::io::Error::other)?;
+    bytes.extend_from_slice(&len.to_be_bytes());
+    bytes.extend_from_slice(record);
+    Ok(())
+}
+
+/// Split the content of a file of length-prefixed records, like the ledger, in records,
+/// returning them with the length of the complete records.
+pub(crate) fn record_frames(bytes: &[u8]) -> (Vec<&[u8]>, usize) {
+    let mut frames = vec![];
+    let mut offset = 0;
+    while let Some(prefix) = bytes.get(offset..offset + RECORD_LENGTH_SIZE) {
+        let mut len = [0; RECORD_LENGTH_SIZE];
+        len.copy_from_slice(prefix);
+        let start = offset + RECORD_LENGTH_SIZE;
+        let end = start + u32::from_be_bytes(len) as usize;
+        match bytes.get(start..end) {
+            Some(frame) => frames.push(frame),
+            None => break,
+        }
+        offset = end;
+    }
+    (frames, offset)
+}
+
+/// Records of the payments made for the quotes, given the transactions of the quote payments.
+/// Data is recorded if any of its quotes was paid, even if the others were not.
+pub(crate) fn payment_records(
+    quotes: &StoreQuote,
+    tx_hashes_by_quote: &BTreeMap<QuoteHash, TxHash>,
+    data_type: DataTypes,
+    wallet: EvmAddress,
+) -> Vec<PaymentRecord> {
+    let timestamp = SystemTime::now()
+        .duration_since(SystemTime::UNIX_EPOCH)
+        .unwrap_or(Duration::from_secs(0))
+        .as_secs();
+
+    quotes
+        .0
+        .iter()
+        .filter_map(|(content_addr, quote_for_address)| {
+            let quote_payments: Vec<_> = quote_for_address
+                .0
+                .iter()
+                .map(|(_, quote, amount)| {
+                    let quote_hash = quote.hash();
+                    QuotePaymentRecord {
+                        quote_hash,
+                        rewards_address: quote.rewards_address,
+                        amount: AttoTokens::from_atto(*amount),
+                        tx_hash: tx_hashes_by_quote.get(&quote_hash).copied(),
+                    }
+                })
+                .collect();
+            let paid: Vec<_> = quote_payments
+                .iter()
+                .filter(|payment| payment.tx_hash.is_some())
+                .collect();
+            if paid.is_empty() {
+                return None;
+            }
+            let price = paid.iter().map(|payment| payment.amount.as_atto()).sum();
+
+            Some(PaymentRecord {
+                timestamp,
+                wallet,
+                content_addr: *content_addr,
+                data_type,
+                price: AttoTokens::from_atto(price),
+                quote_payments,
+            })
+        })
+        .collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn record(timestamp: u64, data_type: DataTypes) -> PaymentRecord {
+        PaymentRecord {
+            timestamp,
+            wallet: EvmAddress::ZERO,
+            content_addr: XorName::random(&mut rand::thread_rng()),
+            data_type,
+            price: AttoTokens::from_u64(3),
+            quote_payments: vec![QuotePaymentRecord {
+                quote_hash: QuoteHash::ZERO,
+                rewards_address: RewardsAddress::ZERO,
+                amount: AttoTokens::from_u64(3),
+                tx_hash: Some(TxHash::ZERO),
+            }],
+        }
+    }
+
+    #[test]
+    fn ledger_appends_and_filters_records() {
+        let dir = tempfile::tempdir().unwrap();
+        let ledger = PaymentLedger::new(dir.path().join("ledger").join("payments"));
+        assert!(ledger.query(&Default::default()).unwrap().is_empty());
+
+        let first = record(100, DataTypes::Chunk);
+        let second = record(200, DataTypes::Scratchpad);
+        ledger.record(std::slice::from_ref(&first)).unwrap();
+        ledger.record(std::slice::from_ref(&second)).unwrap();
+
+        assert_eq!(
+            ledger.query(&Default::default()).unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        let since = PaymentFilter {
+            since: Some(150),
+            ..Default::default()
+        };
+        assert_eq!(ledger.query(&since).unwrap(), vec![second.clone()]);
+
+        let chunks = PaymentFilter {
+            data_type: Some(DataTypes::Chunk),
+            ..Default::default()
+        };
+        assert_eq!(ledger.query(&chunks).unwrap(), vec![first.clone()]);
+
+        // a truncated record at the end does not hide the previous ones
+        let mut file = OpenOptions::new().append(true).open(ledger.path()).unwrap();
+        file.write_all(&[0, 0, 0, 9, 0x81]).unwrap();
+        assert_eq!(
+            ledger.query(&Default::default()).unwrap(),
+            vec![first.clone(), second.clone()]
+        );
+
+        // and is removed before appending, by this ledger or a new one
+        let third = record(300, DataTypes::Pointer);
+        ledger.record(std::slice::from_ref(&third)).unwrap();
+        file.write_all(&[0, 0, 0, 9, 0x81]).unwrap();
+        let ledger = PaymentLedger::new(ledger.path().to_path_buf());
+        let fourth = record(400, DataTypes::Chunk);
+        ledger.record(std::slice::from_ref(&fourth)).unwrap();
+        assert_eq!(
+            ledger.query(&Default::default()).unwrap(),
+            vec![first.clone(), second.clone(), third.clone(), fourth.clone()]
+        );
+
+        // a complete record that cannot be read does not hide the next ones
+        file.write_all(&[0, 0, 0, 1, 0xc1]).unwrap();
+        let fifth = record(500, DataTypes::Chunk);
+        ledger.record(std::slice::from_ref(&fifth)).unwrap();
+        assert_eq!(
+            ledger.query(&Default::default()).unwrap(),
+            vec![first, second, third, fourth, fifth]
+        );
+    }
+}
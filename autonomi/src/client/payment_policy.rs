@@ -0,0 +1,204 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use std::fmt;
+use std::sync::{Arc, Mutex};
+
+use ant_evm::{Amount, AttoTokens};
+use futures::future::BoxFuture;
+use xor_name::XorName;
+
+use crate::client::quote::StoreQuote;
+
+type ApprovalFn = dyn for<'a> Fn(&'a StoreQuote) -> BoxFuture<'a, bool> + Send + Sync;
+
+/// Async callback deciding whether quoted data may be paid for.
+///
+/// It is called with the quotes of each payment before any of its transactions is sent,
+/// the payment is aborted if it returns `false`.
+#[derive(Clone)]
+pub struct PaymentApproval(Arc<ApprovalFn>);
+
+impl PaymentApproval {
+    pub fn new<F>(approve: F) -> Self
+    where
+        F: for<'a> Fn(&'a StoreQuote) -> BoxFuture<'a, bool> + Send + Sync + 'static,
+    {
+        Self(Arc::new(approve))
+    }
+
+    pub(crate) async fn approve(&self, quotes: &StoreQuote) -> bool {
+        (self.0)(quotes).await
+    }
+}
+
+impl fmt::Debug for PaymentApproval {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str("PaymentApproval")
+    }
+}
+
+/// Limits on what the [`crate::Client`] may spend, checked before any payment transaction is sent.
+///
+/// A payment is a single payment for a set of quotes, e.g. for the chunks of a piece of data.
+/// Uploads pay for their chunks in windows of [`crate::files::STREAM_UPLOAD_WINDOW_SIZE`] chunks,
+/// so a large upload consists of several payments. To limit what an upload spends as a whole,
+/// use `max_per_session` on a client dedicated to the upload.
+/// The session is the lifetime of the client, see [`crate::Client::session_spent`].
+#[derive(Debug, Clone, Default)]
+pub struct PaymentPolicy {
+    /// Maximum amount to pay in a single payment
+    pub max_per_payment: Option<AttoTokens>,
+    /// Maximum amount to pay over the lifetime of the client
+    pub max_per_session: Option<AttoTokens>,
+    /// Maximum price to pay for a single chunk or record
+    pub max_price_per_chunk: Option<AttoTokens>,
+    /// Called with the quotes of each payment before paying for them, to let the user approve or refuse it
+    pub approval: Option<PaymentApproval>,
+}
+
+/// A payment refused by the [`PaymentPolicy`].
+#[derive(Debug, thiserror::Error)]
+pub enum PaymentPolicyError {
+    #[error("Quoted price {price} for {content_addr:?} exceeds the maximum of {max} per chunk")]
+    ChunkPriceExceeded {
+        content_addr: XorName,
+        price: AttoTokens,
+        max: AttoTokens,
+    },
+    #[error("Quoted total {quoted} exceeds the maximum of {max} per payment")]
+    PaymentBudgetExceeded { quoted: AttoTokens, max: AttoTokens },
+    #[error(
+        "Quoted total {quoted} exceeds what is left of the session budget: {remaining} of {max}"
+    )]
+    SessionBudgetExceeded {
+        quoted: AttoTokens,
+        remaining: AttoTokens,
+        max: AttoTokens,
+    },
+    #[error("Payment of {0} was not approved")]
+    NotApproved(AttoTokens),
+}
+
+impl PaymentPolicy {
+    /// Check the quotes against the price limits, then ask for approval.
+    /// The session budget is checked separately, when reserving the amount to pay.
+    pub(crate) async fn check(&self, quotes: &StoreQuote) -> Result<(), PaymentPolicyError> {
+        if let Some(max) = self.max_price_per_chunk {
+            for (content_addr, quote) in quotes.0.iter() {
+                let price = AttoTokens::from_atto(quote.price());
+                if price > max {
+                    return Err(PaymentPolicyError::ChunkPriceExceeded {
+                        content_addr: *content_addr,
+                        price,
+                        max,
+                    });
+                }
+            }
+        }
+
+        let quoted = AttoTokens::from_atto(quotes.price());
+        if let Some(max) = self.max_per_payment {
+            if quoted > max {
+                return Err(PaymentPolicyError::PaymentBudgetExceeded { quoted, max });
+            }
+        }
+
+        if let Some(approval) = &self.approval {
+            if !approval.approve(quotes).await {
+                info!("Payment of {quoted} was not approved");
+                return Err(PaymentPolicyError::NotApproved(quoted));
+            }
+        }
+
+        Ok(())
+    }
+}
+
+/// What the client spent in this session, including the payments in progress.
+#[derive(Debug, Default)]
+pub(crate) struct SessionSpending(Mutex<Amount>);
+
+impl SessionSpending {
+    /// Reserve the amount about to be paid, unless it exceeds what is left of the session budget.
+    pub(crate) fn reserve(
+        &self,
+        amount: Amount,
+        max: Option<AttoTokens>,
+    ) -> Result<(), PaymentPolicyError> {
+        let mut spent = self.0.lock().expect("session spending lock poisoned");
+        if let Some(max) = max {
+            let remaining = max.as_atto().saturating_sub(*spent);
+            if amount > remaining {
+                return Err(PaymentPolicyError::SessionBudgetExceeded {
+                    quoted: AttoTokens::from_atto(amount),
+                    remaining: AttoTokens::from_atto(remaining),
+                    max,
+                });
+            }
+        }
+        *spent = spent.saturating_add(amount);
+        Ok(())
+    }
+
+    /// Give back the part of a reservation that was not paid.
+    pub(crate) fn release(&self, amount: Amount) {
+        let mut spent = self.0.lock().expect("session spending lock poisoned");
+        *spent = spent.saturating_sub(amount);
+    }
+
+    pub(crate) fn spent(&self) -> AttoTokens {
+        AttoTokens::from_atto(*self.0.lock().expect("session spending lock poisoned"))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn session_budget_is_enforced_across_reservations() {
+        let spending = SessionSpending::default();
+        let max = Some(AttoTokens::from_u64(100));
+
+        assert!(spending.reserve(Amount::from(60), max).is_ok());
+        assert!(matches!(
+            spending.reserve(Amount::from(50), max),
+            Err(PaymentPolicyError::SessionBudgetExceeded { .. })
+        ));
+        assert_eq!(spending.spent(), AttoTokens::from_u64(60));
+
+        // a failed payment gives its reservation back
+        spending.release(Amount::from(60));
+        assert!(spending.reserve(Amount::from(100), max).is_ok());
+        assert_eq!(spending.spent(), AttoTokens::from_u64(100));
+
+        // without a maximum, anything goes
+        assert!(spending.reserve(Amount::from(1000), None).is_ok());
+    }
+
+    #[tokio::test]
+    async fn empty_quotes_pass_the_policy_and_refused_approval_aborts() {
+        let quotes = StoreQuote(Default::default());
+        let policy = PaymentPolicy {
+            max_per_payment: Some(AttoTokens::zero()),
+            max_price_per_chunk: Some(AttoTokens::zero()),
+            ..Default::default()
+        };
+        assert!(policy.check(&quotes).await.is_ok());
+
+        let policy = PaymentPolicy {
+            approval: Some(PaymentApproval::new(|_| Box::pin(async { false }))),
+            ..Default::default()
+        };
+        assert!(matches!(
+            policy.check(&quotes).await,
+            Err(PaymentPolicyError::NotApproved(_))
+        ));
+    }
+}
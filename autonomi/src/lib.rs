@@ -28,7 +28,7 @@
 //!     let _data_fetched = client.data_get_public(&data_addr).await?;
 //!
 //!     // Put and fetch directory from local file system.
-//!     let (cost, dir_addr) = client.dir_upload_public("files/to/upload".into(), payment).await?;
+//!     let (cost, dir_addr, _report) = client.dir_upload_public("files/to/upload".into(), payment).await?;
 //!     client.dir_download_public(&dir_addr, "files/downloaded".into()).await?;
 //!
 //!     Ok(())
@@ -69,8 +69,11 @@ pub use client::data_types::scratchpad;
 // The high-level data types
 pub use client::data;
 pub use client::files;
+pub use client::mutable_blob;
 pub use client::register;
+pub use client::shared_scratchpad;
 pub use client::vault;
+pub use client::watch;
 
 // Re-exports of the evm types
 pub use ant_evm::utils::{get_evm_network, Error as EvmUtilError};
@@ -78,8 +81,8 @@ pub use ant_evm::EvmNetwork as Network;
 pub use ant_evm::EvmWallet as Wallet;
 pub use ant_evm::QuoteHash;
 pub use ant_evm::RewardsAddress;
-pub use ant_evm::TransactionConfig;
 pub use ant_evm::{Amount, AttoTokens};
+pub use ant_evm::{ApprovalStrategy, TransactionConfig};
 
 // Re-exports of address related types
 pub use ant_protocol::storage::AddressParseError;
@@ -100,6 +103,8 @@ pub use client::{
     config::ClientConfig,
     config::ClientOperatingStrategy,
     config::InitialPeersConfig,
+    config::PaymentApproval,
+    config::PaymentPolicy,
 
     // Native data types
     data_types::chunk::Chunk,
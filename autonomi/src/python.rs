@@ -503,7 +503,7 @@ impl PyClient {
         let client = self.inner.clone();
 
         future_into_py(py, async move {
-            let (cost, archive) = client
+            let (cost, archive, _report) = client
                 .dir_content_upload(dir_path, payment.inner)
                 .await
                 .map_err(|e| PyRuntimeError::new_err(format!("Failed to upload directory: {e}")))?;
@@ -545,7 +545,7 @@ impl PyClient {
         let client = self.inner.clone();
 
         future_into_py(py, async move {
-            let (cost, data_map) = client
+            let (cost, data_map, _report) = client
                 .dir_upload(dir_path, payment.inner)
                 .await
                 .map_err(|e| PyRuntimeError::new_err(format!("Failed to upload directory: {e}")))?;
@@ -657,7 +657,7 @@ impl PyClient {
         let payment = payment.inner.clone();
 
         future_into_py(py, async move {
-            let (cost, addr) = client
+            let (cost, addr, _report) = client
                 .dir_upload_public(dir_path, payment)
                 .await
                 .map_err(|e| PyRuntimeError::new_err(format!("Failed to upload directory: {e}")))?;
@@ -700,7 +700,7 @@ impl PyClient {
         let client = self.inner.clone();
 
         future_into_py(py, async move {
-            let (cost, archive) = client
+            let (cost, archive, _report) = client
                 .dir_content_upload_public(dir_path, payment.inner)
                 .await
                 .map_err(|e| PyRuntimeError::new_err(format!("Failed to upload directory: {e}")))?;
@@ -9,8 +9,9 @@
 use ant_protocol::storage::Chunk;
 use bytes::{BufMut, Bytes, BytesMut};
 use rayon::prelude::*;
-use self_encryption::{DataMap, MAX_CHUNK_SIZE};
+use self_encryption::{DataMap, StreamSelfEncryptor, MAX_CHUNK_SIZE, MIN_ENCRYPTABLE_BYTES};
 use serde::{Deserialize, Serialize};
+use std::path::Path;
 use tracing::debug;
 
 #[derive(Debug, thiserror::Error)]
@@ -29,9 +30,18 @@ pub(crate) enum DataMapLevel {
     // resulting from chunking up a previous level data map.
     // This happens when that previous level data map was too big to fit in a chunk itself.
     Additional(DataMap),
+    // Holds the source data itself, when it is too small to be self-encrypted.
+    Inline(Bytes),
 }
 
+/// Self-encrypt data, returning the data map chunk and the encrypted chunks.
+///
+/// Data smaller than [`MIN_ENCRYPTABLE_BYTES`] cannot be self-encrypted, it is held in the data map chunk itself.
 pub fn encrypt(data: Bytes) -> Result<(Chunk, Vec<Chunk>), Error> {
+    if data.len() < MIN_ENCRYPTABLE_BYTES {
+        return Ok((inline_data_map_chunk(data)?, vec![]));
+    }
+
     let (data_map, chunks) = self_encryption::encrypt(data)?;
     let (data_map_chunk, additional_chunks) = pack_data_map(data_map)?;
 
@@ -45,6 +55,201 @@ pub fn encrypt(data: Bytes) -> Result<(Chunk, Vec<Chunk>), Error> {
     Ok((data_map_chunk, chunks))
 }
 
+/// Self-encrypts a file chunk by chunk, without reading the whole file into memory.
+///
+/// Produces the exact same chunks and data map chunk as [`encrypt`] would for the content of the file.
+/// Self-encryption derives the key of the first chunks from the last chunks of the file,
+/// which is why this works on a file on disk rather than on a one-way stream.
+pub struct StreamingEncryptor {
+    source: StreamingSource,
+    // Hashes the content as it is encrypted, if asked for
+    hasher: Option<PlaintextHasher>,
+    chunks_produced: u64,
+}
+
+enum StreamingSource {
+    Encrypting {
+        inner: Box<StreamSelfEncryptor>,
+        data_map: Option<DataMap>,
+    },
+    // The file is too small to be self-encrypted, it is held in the data map chunk
+    Inline(Bytes),
+}
+
+impl StreamingEncryptor {
+    /// Prepare the streaming encryption of the file at the given path.
+    pub fn from_file(path: &Path) -> Result<Self, Error> {
+        let file_size = std::fs::metadata(path)
+            .map_err(self_encryption::Error::from)?
+            .len();
+        if (MIN_ENCRYPTABLE_BYTES as u64) > file_size {
+            let data = std::fs::read(path).map_err(self_encryption::Error::from)?;
+            return Ok(Self {
+                source: StreamingSource::Inline(Bytes::from(data)),
+                hasher: None,
+                chunks_produced: 0,
+            });
+        }
+
+        let inner = StreamSelfEncryptor::encrypt_from_file(path.to_path_buf(), None)?;
+        Ok(Self {
+            source: StreamingSource::Encrypting {
+                inner: Box::new(inner),
+                data_map: None,
+            },
+            hasher: None,
+            chunks_produced: 0,
+        })
+    }
+
+    /// Same as [`StreamingEncryptor::from_file`], but also computes the SHA-256 hash of the content of the file,
+    /// returned by [`StreamingEncryptor::finish_with_sha256`].
+    ///
+    /// The content is hashed as the file is encrypted, right after each chunk is read for the encryption,
+    /// so the file is only read through once.
+    pub fn from_file_with_sha256(path: &Path) -> Result<Self, Error> {
+        let mut encryptor = Self::from_file(path)?;
+        let hasher = match &encryptor.source {
+            StreamingSource::Inline(data) => PlaintextHasher::of_inline(data),
+            StreamingSource::Encrypting { .. } => PlaintextHasher::of_file(path)?,
+        };
+        encryptor.hasher = Some(hasher);
+        Ok(encryptor)
+    }
+
+    /// Encrypt and return the next chunk of the file, or `None` once all chunks have been produced.
+    pub fn next_chunk(&mut self) -> Result<Option<Chunk>, Error> {
+        let StreamingSource::Encrypting { inner, data_map } = &mut self.source else {
+            return Ok(None);
+        };
+        if data_map.is_some() {
+            return Ok(None);
+        }
+
+        match inner.next_encryption()? {
+            (Some(encrypted_chunk), _) => {
+                self.chunks_produced += 1;
+                if let Some(hasher) = &mut self.hasher {
+                    hasher.hash_up_to(self.chunks_produced * *MAX_CHUNK_SIZE as u64)?;
+                }
+                Ok(Some(Chunk::new(encrypted_chunk.content)))
+            }
+            (None, Some(map)) => {
+                *data_map = Some(map);
+                Ok(None)
+            }
+            (None, None) => Err(self_encryption::Error::Encryption.into()),
+        }
+    }
+
+    /// Encrypt and return up to `max` of the next chunks of the file.
+    ///
+    /// An empty list means all chunks have been produced.
+    pub fn next_chunks(&mut self, max: usize) -> Result<Vec<Chunk>, Error> {
+        let mut chunks = Vec::with_capacity(max);
+        while chunks.len() < max {
+            match self.next_chunk()? {
+                Some(chunk) => chunks.push(chunk),
+                None => break,
+            }
+        }
+        Ok(chunks)
+    }
+
+    /// Once all chunks have been produced, pack the data map.
+    ///
+    /// Returns the data map chunk and the additional chunks needed when the data map is too big to fit in one chunk,
+    /// just like the chunks returned by [`encrypt`].
+    pub fn finish(self) -> Result<(Chunk, Vec<Chunk>), Error> {
+        let (data_map_chunk, additional_chunks, _) = self.finish_with_sha256()?;
+        Ok((data_map_chunk, additional_chunks))
+    }
+
+    /// Same as [`StreamingEncryptor::finish`], but also returns the SHA-256 hash of the content of the file,
+    /// if the encryptor was created with [`StreamingEncryptor::from_file_with_sha256`].
+    #[allow(clippy::type_complexity)]
+    pub fn finish_with_sha256(mut self) -> Result<(Chunk, Vec<Chunk>, Option<[u8; 32]>), Error> {
+        // drain any chunk that has not been asked for yet so we get to the data map
+        while self.next_chunk()?.is_some() {}
+        let hash = match self.hasher {
+            Some(mut hasher) => {
+                hasher.hash_up_to(u64::MAX)?;
+                Some(hasher.finalize())
+            }
+            None => None,
+        };
+        let (data_map_chunk, additional_chunks) = match self.source {
+            StreamingSource::Encrypting { data_map, .. } => {
+                pack_data_map(data_map.ok_or(self_encryption::Error::Encryption)?)?
+            }
+            StreamingSource::Inline(data) => (inline_data_map_chunk(data)?, vec![]),
+        };
+        Ok((data_map_chunk, additional_chunks, hash))
+    }
+}
+
+/// Hashes the content of a file in the wake of its encryption.
+///
+/// Self-encryption reads the chunks of the file itself, this reads the content of the chunks that were just
+/// encrypted, while they are still in the page cache, rather than hashing the file in a separate pass.
+struct PlaintextHasher {
+    // `None` when the content is already in memory
+    file: Option<std::fs::File>,
+    hashed: u64,
+    hasher: sha2::Sha256,
+}
+
+impl PlaintextHasher {
+    fn of_file(path: &Path) -> Result<Self, Error> {
+        Ok(Self {
+            file: Some(std::fs::File::open(path).map_err(self_encryption::Error::from)?),
+            hashed: 0,
+            hasher: sha2::Digest::new(),
+        })
+    }
+
+    fn of_inline(data: &Bytes) -> Self {
+        let mut hasher: sha2::Sha256 = sha2::Digest::new();
+        sha2::Digest::update(&mut hasher, data);
+        Self {
+            file: None,
+            hashed: data.len() as u64,
+            hasher,
+        }
+    }
+
+    /// Hash the content of the file up to the given offset, or to its end.
+    fn hash_up_to(&mut self, end: u64) -> Result<(), Error> {
+        use std::io::Read;
+
+        let Some(file) = &mut self.file else {
+            return Ok(());
+        };
+        let mut buf = vec![0; 64 * 1024];
+        while self.hashed < end {
+            let want = (end - self.hashed).min(buf.len() as u64) as usize;
+            let read = file
+                .read(&mut buf[..want])
+                .map_err(self_encryption::Error::from)?;
+            if read == 0 {
+                break;
+            }
+            sha2::Digest::update(&mut self.hasher, &buf[..read]);
+            self.hashed += read as u64;
+        }
+        Ok(())
+    }
+
+    fn finalize(self) -> [u8; 32] {
+        sha2::Digest::finalize(self.hasher).into()
+    }
+}
+
+// Produces a data map chunk holding the data itself, for data too small to be self-encrypted.
+fn inline_data_map_chunk(data: Bytes) -> Result<Chunk, Error> {
+    Ok(Chunk::new(wrap_data_map(&DataMapLevel::Inline(data))?))
+}
+
 // Produces a chunk out of the first `DataMap`, which is validated for its size.
 // If the chunk is too big, it is self-encrypted and the resulting (additional level) `DataMap` is put into a chunk.
 // The above step is repeated as many times as required until the chunk size is valid.
@@ -92,3 +297,77 @@ fn wrap_data_map(data_map: &DataMapLevel) -> Result<Bytes, rmp_serde::encode::Er
         .inspect_err(|err| error!("Failed to serialize data map: {err:?}"))?;
     Ok(bytes.into_inner().freeze())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::io::Write;
+
+    #[test]
+    fn streaming_encryption_matches_in_memory_encryption() {
+        let data: Vec<u8> = (0..(3 * *MAX_CHUNK_SIZE + 1234))
+            .map(|i| (i % 251) as u8)
+            .collect();
+        let mut file = tempfile::NamedTempFile::new().unwrap();
+        file.write_all(&data).unwrap();
+
+        let (data_map_chunk, chunks) = encrypt(Bytes::from(data)).unwrap();
+
+        let mut encryptor = StreamingEncryptor::from_file(file.path()).unwrap();
+        let mut streamed_chunks = vec![];
+        loop {
+            let window = encryptor.next_chunks(2).unwrap();
+            if window.is_empty() {
+                break;
+            }
+            assert!(window.len() <= 2);
+            streamed_chunks.extend(window);
+        }
+        let (streamed_data_map_chunk, additional_chunks) = encryptor.finish().unwrap();
+        streamed_chunks.extend(additional_chunks);
+
+        let mut chunks = chunks;
+        chunks.sort();
+        streamed_chunks.sort();
+        assert_eq!(data_map_chunk, streamed_data_map_chunk);
+        assert_eq!(chunks, streamed_chunks);
+    }
+
+    #[test]
+    fn streaming_encryption_hashes_the_content() {
+        for len in [2, 3 * *MAX_CHUNK_SIZE + 1234] {
+            let data: Vec<u8> = (0..len).map(|i| (i % 251) as u8).collect();
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(&data).unwrap();
+
+            let mut encryptor = StreamingEncryptor::from_file_with_sha256(file.path()).unwrap();
+            assert!(encryptor.next_chunk().unwrap().is_some() || len == 2);
+            let (data_map_chunk, _, hash) = encryptor.finish_with_sha256().unwrap();
+            let expected: [u8; 32] =
+                sha2::Digest::finalize(<sha2::Sha256 as sha2::Digest>::new_with_prefix(&data))
+                    .into();
+            assert_eq!(hash, Some(expected));
+            assert_eq!(data_map_chunk, encrypt(Bytes::from(data)).unwrap().0);
+        }
+    }
+
+    #[test]
+    fn tiny_data_is_inlined_in_the_data_map_chunk() {
+        for data in [&b""[..], b"a", b"ab"] {
+            let (data_map_chunk, chunks) = encrypt(Bytes::copy_from_slice(data)).unwrap();
+            assert!(chunks.is_empty());
+            match rmp_serde::from_slice(data_map_chunk.value()).unwrap() {
+                DataMapLevel::Inline(inlined) => assert_eq!(&inlined[..], data),
+                _ => panic!("expected the data to be inlined"),
+            }
+
+            let mut file = tempfile::NamedTempFile::new().unwrap();
+            file.write_all(data).unwrap();
+            let mut encryptor = StreamingEncryptor::from_file(file.path()).unwrap();
+            assert!(encryptor.next_chunk().unwrap().is_none());
+            let (streamed_data_map_chunk, additional_chunks) = encryptor.finish().unwrap();
+            assert!(additional_chunks.is_empty());
+            assert_eq!(data_map_chunk, streamed_data_map_chunk);
+        }
+    }
+}
@@ -130,7 +130,7 @@ async fn test_data_addresses_use() -> Result<()> {
     // put private dir
     let payment_option = PaymentOption::from(&wallet);
     let path = "tests/file/test_dir/".into();
-    let (_cost, archive_datamap) = client.dir_upload(path, payment_option.clone()).await?;
+    let (_cost, archive_datamap, _report) = client.dir_upload(path, payment_option.clone()).await?;
     let archive_datamap_addr = archive_datamap.to_hex();
     println!("Private Archive (DataMap): {archive_datamap_addr}");
 
@@ -139,7 +139,7 @@ async fn test_data_addresses_use() -> Result<()> {
 
     // put public dir
     let path = "tests/file/test_dir/".into();
-    let (_cost, archive_addr) = client
+    let (_cost, archive_addr, _report) = client
         .dir_upload_public(path, payment_option.clone())
         .await?;
     let archive_addr_str = archive_addr.to_hex();
@@ -8,6 +8,7 @@
 
 use ant_logging::LogBuilder;
 use autonomi::client::payment::PaymentOption;
+use autonomi::data::DataAddress;
 use autonomi::Client;
 use eyre::Result;
 use serial_test::serial;
@@ -30,9 +31,10 @@ async fn dir_upload_download() -> Result<()> {
     let client = Client::init_local().await?;
     let wallet = get_funded_wallet();
 
-    let (_cost, addr) = client
+    let (_cost, addr, report) = client
         .dir_upload_public("tests/file/test_dir".into(), wallet.into())
         .await?;
+    assert!(report.is_complete());
 
     sleep(Duration::from_secs(10)).await;
 
@@ -48,6 +50,34 @@ async fn dir_upload_download() -> Result<()> {
     Ok(())
 }
 
+// Files too small to be self-encrypted are inlined in their data map
+#[tokio::test]
+#[serial]
+async fn tiny_files_upload_download() -> Result<()> {
+    let _log_appender_guard =
+        LogBuilder::init_single_threaded_tokio_test("tiny_files_upload_download", false);
+
+    let client = Client::init_local().await?;
+    let wallet = get_funded_wallet();
+
+    let (_cost, addr, report) = client
+        .dir_upload_public("tests/file/tiny_files".into(), wallet.into())
+        .await?;
+    assert!(report.is_complete());
+
+    sleep(Duration::from_secs(10)).await;
+
+    client
+        .dir_download_public(&addr, "tests/file/tiny_files_fetched".into())
+        .await?;
+
+    assert_eq!(
+        compute_dir_sha256("tests/file/tiny_files")?,
+        compute_dir_sha256("tests/file/tiny_files_fetched")?,
+    );
+    Ok(())
+}
+
 fn compute_sha256(path: &str) -> Result<String> {
     let mut hasher = Sha256::new();
     let mut file = BufReader::new(File::open(path)?);
@@ -86,7 +116,7 @@ async fn file_into_vault() -> Result<()> {
     let wallet = get_funded_wallet();
     let client_sk = bls::SecretKey::random();
 
-    let (_cost, addr) = client
+    let (_cost, addr, _report) = client
         .dir_upload_public("tests/file/test_dir".into(), wallet.clone().into())
         .await?;
     sleep(Duration::from_secs(2)).await;
@@ -124,7 +154,7 @@ async fn file_advanced_use() -> Result<()> {
     let payment_option = PaymentOption::Wallet(wallet);
 
     // upload a directory
-    let (cost, mut archive) = client
+    let (cost, mut archive, _report) = client
         .dir_content_upload("tests/file/test_dir/dir_a".into(), payment_option.clone())
         .await?;
     println!("cost to upload private directory: {cost:?}");
@@ -177,3 +207,28 @@ async fn file_advanced_use() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn file_stream_upload() -> Result<()> {
+    let _log_appender_guard =
+        LogBuilder::init_single_threaded_tokio_test("file_stream_upload", false);
+
+    let client = Client::init_local().await?;
+    let wallet = get_funded_wallet();
+    let data = test_utils::gen_random_data(1024 * 1024 * 5);
+
+    let (_cost, addr) = client
+        .file_stream_upload(data.as_ref(), wallet.into())
+        .await?;
+
+    sleep(Duration::from_secs(10)).await;
+
+    // the stream gets the same address as the same bytes uploaded in one go
+    let (data_map_chunk, _chunks) = autonomi::self_encryption::encrypt(data.clone())?;
+    assert_eq!(addr, DataAddress::new(*data_map_chunk.name()));
+    let fetched = client.data_get_public(&addr).await?;
+    assert_eq!(fetched, data);
+
+    Ok(())
+}
@@ -0,0 +1,40 @@
+// Copyright 2025 MaidSafe.net limited.
+//
+// This SAFE Network Software is licensed to you under The General Public License (GPL), version 3.
+// Unless required by applicable law or agreed to in writing, the SAFE Network Software distributed
+// under the GPL Licence is distributed on an "AS IS" BASIS, WITHOUT WARRANTIES OR CONDITIONS OF ANY
+// KIND, either express or implied. Please review the Licences for the specific language governing
+// permissions and limitations relating to use of the SAFE Network Software.
+
+use ant_logging::LogBuilder;
+use autonomi::{Client, Scratchpad};
+use bytes::Bytes;
+use eyre::Result;
+use serial_test::serial;
+use test_utils::{evm::get_funded_wallet, gen_random_data};
+
+#[tokio::test]
+#[serial]
+async fn mutable_blob_append() -> Result<()> {
+    let _log_appender_guard = LogBuilder::init_single_threaded_tokio_test("mutable_blob", false);
+    let client = Client::init_local().await?;
+    let wallet = get_funded_wallet();
+    let key = bls::SecretKey::random();
+
+    // fill the first scratchpad so that appending starts a new one
+    let max_content_per_scratchpad = Scratchpad::MAX_SIZE - 1024;
+    let original = gen_random_data(max_content_per_scratchpad - 10);
+    client
+        .mutable_blob_create(&key, original.clone(), wallet.clone().into())
+        .await?;
+
+    let appended = gen_random_data(1024);
+    client
+        .mutable_blob_append(&key, appended.clone(), wallet.into())
+        .await?;
+
+    let fetched = client.mutable_blob_get(&key).await?;
+    assert_eq!(fetched, Bytes::from([original, appended].concat()));
+
+    Ok(())
+}
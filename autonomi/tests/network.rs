@@ -8,7 +8,7 @@
 
 use ant_bootstrap::InitialPeersConfig;
 use ant_node::spawn::network_spawner::NetworkSpawner;
-use autonomi::{Client, ClientConfig, ClientOperatingStrategy};
+use autonomi::{Client, ClientConfig};
 use evmlib::testnet::Testnet;
 
 // Test fails in CI because of external node interference.
@@ -28,8 +28,8 @@ async fn test_get_closest_to_address() {
 
     let peer = network.bootstrap_peer().await;
 
-    let config = ClientConfig {
-        init_peers_config: InitialPeersConfig {
+    let config = ClientConfig::default()
+        .with_init_peers_config(InitialPeersConfig {
             first: false,
             addrs: vec![peer],
             network_contacts_url: vec![],
@@ -37,10 +37,8 @@ async fn test_get_closest_to_address() {
             disable_mainnet_contacts: true,
             ignore_cache: true,
             bootstrap_cache_dir: None,
-        },
-        evm_network,
-        strategy: ClientOperatingStrategy::default(),
-    };
+        })
+        .with_evm_network(evm_network);
 
     let client = Client::init_with_config(config).await.unwrap();
 
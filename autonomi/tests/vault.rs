@@ -8,7 +8,7 @@
 
 use ant_evm::AttoTokens;
 use ant_logging::LogBuilder;
-use autonomi::{vault::app_name_to_vault_content_type, Client};
+use autonomi::{vault::app_name_to_vault_content_type, Client, Scratchpad};
 use eyre::Result;
 use serial_test::serial;
 use test_utils::{evm::get_funded_wallet, gen_random_data};
@@ -57,10 +57,10 @@ async fn vault_expand() -> Result<()> {
     assert_eq!(fetched_content_type, content_type);
     assert_eq!(fetched_content, original_content);
 
-    // Update content to 2KB. Shall not incur any cost.
+    // Update content to 2KB. Shall not incur any cost without a snapshot.
     let update_content_2_kb = gen_random_data(2 * 1024);
     let cost = client
-        .write_bytes_to_vault(
+        .write_bytes_to_vault_without_snapshot(
             update_content_2_kb.clone(),
             wallet.clone().into(),
             &main_key,
@@ -75,10 +75,10 @@ async fn vault_expand() -> Result<()> {
     assert_eq!(fetched_content_type, content_type);
     assert_eq!(fetched_content, update_content_2_kb);
 
-    // Update content to 10MB. Shall only incur cost paying two extra Scratchpad.
+    // Update content to 10MB. Shall only incur cost paying two extra Scratchpad without a snapshot.
     let update_content_10_mb = gen_random_data(10 * 1024 * 1024);
     let cost = client
-        .write_bytes_to_vault(
+        .write_bytes_to_vault_without_snapshot(
             update_content_10_mb.clone(),
             wallet.into(),
             &main_key,
@@ -98,3 +98,110 @@ async fn vault_expand() -> Result<()> {
 
     Ok(())
 }
+
+#[tokio::test]
+#[serial]
+async fn vault_read_write_at_scratchpad_boundaries() -> Result<()> {
+    let _log_appender_guard = LogBuilder::init_single_threaded_tokio_test("vault", false);
+    let client = Client::init_local().await?;
+    let wallet = get_funded_wallet();
+    let main_key = bls::SecretKey::random();
+    let content_type = app_name_to_vault_content_type("TestData");
+
+    // Content exactly filling scratchpads, then shrinking to a part whose encrypted size is
+    // larger than a full part's content, then filling a single scratchpad again.
+    let max_content_per_scratchpad = Scratchpad::MAX_SIZE - 1024;
+    for len in [
+        2 * max_content_per_scratchpad,
+        max_content_per_scratchpad - 1,
+        max_content_per_scratchpad,
+    ] {
+        let content = gen_random_data(len);
+        client
+            .write_bytes_to_vault(
+                content.clone(),
+                wallet.clone().into(),
+                &main_key,
+                content_type,
+            )
+            .await?;
+
+        let (fetched_content, fetched_content_type) =
+            client.fetch_and_decrypt_vault(&main_key).await?;
+        assert_eq!(fetched_content_type, content_type);
+        assert_eq!(fetched_content, content, "vault content of {len} bytes");
+    }
+
+    Ok(())
+}
+
+#[tokio::test]
+#[serial]
+async fn vault_snapshot_and_restore() -> Result<()> {
+    let _log_appender_guard = LogBuilder::init_single_threaded_tokio_test("vault", false);
+    let client = Client::init_local().await?;
+    let wallet = get_funded_wallet();
+    let main_key = bls::SecretKey::random();
+
+    let content_type = app_name_to_vault_content_type("TestData");
+    let original_content = gen_random_data(1024);
+    client
+        .write_bytes_to_vault(
+            original_content.clone(),
+            wallet.clone().into(),
+            &main_key,
+            content_type,
+        )
+        .await?;
+    assert!(client.vault_snapshots(&main_key).await?.is_empty());
+
+    // The write itself is still free, only the snapshot is paid for.
+    let updated_content = gen_random_data(1024);
+    let cost = client
+        .write_bytes_to_vault(
+            updated_content.clone(),
+            wallet.clone().into(),
+            &main_key,
+            content_type,
+        )
+        .await?;
+    assert!(!cost.is_zero());
+
+    let snapshots = client.vault_snapshots(&main_key).await?;
+    assert_eq!(snapshots.len(), 1);
+    assert_eq!(snapshots[0].data, original_content);
+    assert_eq!(snapshots[0].content_type, content_type);
+
+    // Writing the same content again doesn't take a snapshot.
+    let cost = client
+        .write_bytes_to_vault(
+            updated_content.clone(),
+            wallet.clone().into(),
+            &main_key,
+            content_type,
+        )
+        .await?;
+    assert_eq!(cost, AttoTokens::zero());
+    assert_eq!(client.vault_snapshots(&main_key).await?.len(), 1);
+
+    // Neither does a write without snapshot.
+    let cost = client
+        .write_bytes_to_vault_without_snapshot(
+            gen_random_data(1024),
+            wallet.clone().into(),
+            &main_key,
+            content_type,
+        )
+        .await?;
+    assert_eq!(cost, AttoTokens::zero());
+    assert_eq!(client.vault_snapshots(&main_key).await?.len(), 1);
+
+    client
+        .vault_restore_snapshot(&main_key, 0, wallet.into())
+        .await?;
+    let (fetched_content, _) = client.fetch_and_decrypt_vault(&main_key).await?;
+    assert_eq!(fetched_content, original_content);
+    assert_eq!(client.vault_snapshots(&main_key).await?.len(), 2);
+
+    Ok(())
+}
@@ -1,13 +1,26 @@
+use crate::common::U256;
+use crate::wallet::Error;
+
 const DEFAULT_MAX_FEE_PER_GAS: u128 = 200_000_000; // 0.2 Gwei
 
 #[derive(Clone, Debug)]
 pub struct TransactionConfig {
     pub max_fee_per_gas: u128,
+    /// How much the data payments contract is approved to spend when paying for quotes.
+    pub approval_strategy: ApprovalStrategy,
 }
 
 impl TransactionConfig {
     pub fn new(max_fee_per_gas: u128) -> Self {
-        Self { max_fee_per_gas }
+        Self {
+            max_fee_per_gas,
+            approval_strategy: Default::default(),
+        }
+    }
+
+    pub fn with_approval_strategy(mut self, approval_strategy: ApprovalStrategy) -> Self {
+        self.approval_strategy = approval_strategy;
+        self
     }
 }
 
@@ -15,6 +28,64 @@ impl Default for TransactionConfig {
     fn default() -> Self {
         Self {
             max_fee_per_gas: DEFAULT_MAX_FEE_PER_GAS,
+            approval_strategy: Default::default(),
         }
     }
 }
+
+/// The allowance to approve for the data payments contract when the current one is too low to pay for quotes.
+#[derive(Clone, Copy, Debug, Default, PartialEq, Eq)]
+pub enum ApprovalStrategy {
+    /// Approve exactly the amount to be paid, nothing is left to spend afterwards.
+    #[default]
+    Exact,
+    /// Approve up to a fixed amount, so that several payments can be made with a single approval.
+    /// A payment above the cap is refused.
+    Cap(U256),
+    /// Approve the contract to spend all the tokens of the wallet.
+    Unlimited,
+}
+
+impl ApprovalStrategy {
+    /// The allowance to approve to pay the given amount.
+    ///
+    /// Returns [`Error::ApprovalCapExceeded`] if the amount is above the cap of [`ApprovalStrategy::Cap`].
+    pub fn allowance_for(&self, amount: U256) -> Result<U256, Error> {
+        match self {
+            ApprovalStrategy::Exact => Ok(amount),
+            ApprovalStrategy::Cap(cap) if amount > *cap => {
+                Err(Error::ApprovalCapExceeded(amount, *cap))
+            }
+            ApprovalStrategy::Cap(cap) => Ok(*cap),
+            ApprovalStrategy::Unlimited => Ok(U256::MAX),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn allowance_follows_the_approval_strategy() {
+        let amount = U256::from(100);
+        assert_eq!(
+            ApprovalStrategy::Exact.allowance_for(amount).unwrap(),
+            amount
+        );
+        assert_eq!(
+            ApprovalStrategy::Cap(U256::from(1000))
+                .allowance_for(amount)
+                .unwrap(),
+            U256::from(1000)
+        );
+        assert!(matches!(
+            ApprovalStrategy::Cap(U256::from(10)).allowance_for(amount),
+            Err(Error::ApprovalCapExceeded(..))
+        ));
+        assert_eq!(
+            ApprovalStrategy::Unlimited.allowance_for(amount).unwrap(),
+            U256::MAX
+        );
+    }
+}
@@ -11,7 +11,7 @@ use crate::contract::network_token::NetworkToken;
 use crate::contract::payment_vault::handler::PaymentVaultHandler;
 use crate::contract::payment_vault::MAX_TRANSFERS_PER_TRANSACTION;
 use crate::contract::{network_token, payment_vault};
-use crate::transaction_config::TransactionConfig;
+use crate::transaction_config::{ApprovalStrategy, TransactionConfig};
 use crate::utils::http_provider;
 use crate::{Network, TX_TIMEOUT};
 use alloy::hex::ToHexExt;
@@ -21,18 +21,29 @@ use alloy::providers::fillers::{
 };
 use alloy::providers::{Identity, Provider, ProviderBuilder, ReqwestProvider};
 use alloy::rpc::types::TransactionRequest;
-use alloy::signers::local::{LocalSigner, PrivateKeySigner};
+use alloy::signers::local::coins_bip39::{English, Mnemonic};
+use alloy::signers::local::{LocalSigner, MnemonicBuilder, PrivateKeySigner};
 use alloy::transports::http::{reqwest, Client, Http};
 use alloy::transports::{RpcError, TransportErrorKind};
 use std::collections::BTreeMap;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
+/// Number of words of the mnemonic phrases generated for new wallets.
+const MNEMONIC_WORD_COUNT: usize = 24;
+
 #[derive(thiserror::Error, Debug)]
 pub enum Error {
     #[error("Insufficient tokens to pay for quotes. Have: {0} atto, need: {1} atto")]
     InsufficientTokensForQuotes(Amount, Amount),
+    #[error("Payment of {0} atto exceeds the approval cap of {1} atto")]
+    ApprovalCapExceeded(Amount, Amount),
     #[error("Private key is invalid")]
     PrivateKeyInvalid,
+    #[error("Mnemonic is invalid: {0}")]
+    MnemonicInvalid(String),
+    #[error("Keystore error: {0}")]
+    Keystore(String),
     #[error(transparent)]
     RpcError(#[from] RpcError<TransportErrorKind>),
     #[error("Network token contract error: {0}")]
@@ -71,6 +82,17 @@ impl Wallet {
         Ok(Self::new(network, wallet))
     }
 
+    /// Creates a new Wallet from a BIP-39 mnemonic phrase, using the BIP-44 derivation path
+    /// `m/44'/60'/0'/0/{account_index}`, the default of Ethereum wallets.
+    pub fn new_from_mnemonic(
+        network: Network,
+        mnemonic: &str,
+        account_index: u32,
+    ) -> Result<Self, Error> {
+        let signer = signer_from_mnemonic(mnemonic, account_index)?;
+        Ok(Self::new(network, EthereumWallet::from(signer)))
+    }
+
     /// Returns the address of this wallet.
     pub fn address(&self) -> Address {
         wallet_address(&self.wallet)
@@ -169,10 +191,80 @@ impl Wallet {
         signer.to_bytes().encode_hex_with_prefix()
     }
 
-    /// Sets the transaction configuration for the wallet.
+    /// Returns a random 24 words BIP-39 mnemonic phrase.
+    pub fn random_mnemonic() -> String {
+        Mnemonic::<English>::new_with_count(&mut rand::thread_rng(), MNEMONIC_WORD_COUNT)
+            .expect("24 is a valid mnemonic word count")
+            .to_phrase()
+    }
+
+    /// Returns the private key string derived from a BIP-39 mnemonic phrase, see [`Wallet::new_from_mnemonic`].
+    pub fn private_key_from_mnemonic(mnemonic: &str, account_index: u32) -> Result<String, Error> {
+        let signer = signer_from_mnemonic(mnemonic, account_index)?;
+        Ok(signer.to_bytes().encode_hex_with_prefix())
+    }
+
+    /// Decrypts the private key string of an Ethereum V3 JSON keystore file.
+    pub fn private_key_from_keystore(path: &Path, password: &str) -> Result<String, Error> {
+        let signer = PrivateKeySigner::decrypt_keystore(path, password).map_err(|err| {
+            error!("Error decrypting keystore at {path:?}: {err}");
+            Error::Keystore(err.to_string())
+        })?;
+        Ok(signer.to_bytes().encode_hex_with_prefix())
+    }
+
+    /// Encrypts a private key into an Ethereum V3 JSON keystore file named `file_name` in `dir`.
+    /// Returns the path of the keystore file.
+    pub fn write_keystore(
+        private_key: &str,
+        dir: &Path,
+        file_name: &str,
+        password: &str,
+    ) -> Result<PathBuf, Error> {
+        let signer: PrivateKeySigner = private_key.parse().map_err(|err| {
+            error!("Error parsing private key: {err}");
+            Error::PrivateKeyInvalid
+        })?;
+        PrivateKeySigner::encrypt_keystore(
+            dir,
+            &mut rand::thread_rng(),
+            signer.to_bytes(),
+            password,
+            Some(file_name),
+        )
+        .map_err(|err| {
+            error!("Error writing keystore to {dir:?}: {err}");
+            Error::Keystore(err.to_string())
+        })?;
+        Ok(dir.join(file_name))
+    }
+
+    /// Sets the transaction configuration for the wallet, replacing the approval strategy as well.
     pub fn set_transaction_config(&mut self, config: TransactionConfig) {
         self.transaction_config = config;
     }
+
+    /// Sets the maximum fee per gas of the transactions, keeping the rest of the transaction configuration.
+    pub fn set_max_fee_per_gas(&mut self, max_fee_per_gas: u128) {
+        self.transaction_config.max_fee_per_gas = max_fee_per_gas;
+    }
+
+    /// Sets how much the data payments contract is approved to spend when paying for quotes.
+    pub fn set_approval_strategy(&mut self, approval_strategy: ApprovalStrategy) {
+        self.transaction_config.approval_strategy = approval_strategy;
+    }
+
+    /// How much the data payments contract may still spend from this wallet.
+    pub async fn data_payments_allowance(&self) -> Result<U256, network_token::Error> {
+        self.token_allowance(*self.network.data_payments_address())
+            .await
+    }
+
+    /// Revoke the approval of the data payments contract to spend this wallet's tokens.
+    pub async fn revoke_data_payments_allowance(&self) -> Result<TxHash, network_token::Error> {
+        self.approve_to_spend_tokens(*self.network.data_payments_address(), U256::ZERO)
+            .await
+    }
 }
 
 /// Generate an EthereumWallet with a random private key.
@@ -181,6 +273,18 @@ fn random() -> EthereumWallet {
     EthereumWallet::from(signer)
 }
 
+/// Derives a signer from a BIP-39 mnemonic phrase at `m/44'/60'/0'/0/{account_index}`.
+fn signer_from_mnemonic(mnemonic: &str, account_index: u32) -> Result<PrivateKeySigner, Error> {
+    MnemonicBuilder::<English>::default()
+        .phrase(mnemonic.trim())
+        .index(account_index)
+        .and_then(|builder| builder.build())
+        .map_err(|err| {
+            error!("Error deriving key from mnemonic: {err}");
+            Error::MnemonicInvalid(err.to_string())
+        })
+}
+
 /// Creates a wallet from a private key in HEX format.
 fn from_private_key(private_key: &str) -> Result<EthereumWallet, Error> {
     let signer: PrivateKeySigner = private_key.parse().map_err(|err| {
@@ -344,6 +448,12 @@ pub async fn pay_for_quotes<T: IntoIterator<Item = QuotePayment>>(
         ));
     }
 
+    // Refuse the payment before anything is sent if the approval strategy does not allow it
+    let approval = transaction_config
+        .approval_strategy
+        .allowance_for(total_amount_to_be_paid)
+        .map_err(|err| PayForQuotesError(err, Default::default()))?;
+
     // Get current allowance
     let allowance = token_allowance(
         network,
@@ -355,12 +465,15 @@ pub async fn pay_for_quotes<T: IntoIterator<Item = QuotePayment>>(
 
     // TODO: Get rid of approvals altogether, by using permits or whatever..
     if allowance < total_amount_to_be_paid {
-        // Approve the contract to spend all the client's tokens.
+        debug!(
+            "Allowance of {allowance} is too low to pay {total_amount_to_be_paid}, approving {approval} ({:?})",
+            transaction_config.approval_strategy
+        );
         approve_to_spend_tokens(
             wallet.clone(),
             network,
             *network.data_payments_address(),
-            U256::MAX,
+            approval,
             transaction_config,
         )
         .await
@@ -425,6 +538,52 @@ mod tests {
         );
     }
 
+    #[test]
+    fn test_from_mnemonic() {
+        // The mnemonic of the default accounts of Anvil and Hardhat
+        let mnemonic = "test test test test test test test test test test test junk";
+        let network = crate::Network::ArbitrumSepolia;
+
+        let wallet = Wallet::new_from_mnemonic(network.clone(), mnemonic, 0).unwrap();
+        assert_eq!(
+            wallet.address(),
+            address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+        );
+        let wallet = Wallet::new_from_mnemonic(network.clone(), mnemonic, 1).unwrap();
+        assert_eq!(
+            wallet.address(),
+            address!("70997970C51812dc3A010C7d01b50e0d17dc79C8")
+        );
+
+        let private_key = Wallet::private_key_from_mnemonic(mnemonic, 0).unwrap();
+        let wallet = Wallet::new_from_private_key(network.clone(), &private_key).unwrap();
+        assert_eq!(
+            wallet.address(),
+            address!("f39Fd6e51aad88F6F4ce6aB8827279cffFb92266")
+        );
+
+        let random = Wallet::random_mnemonic();
+        assert_eq!(random.split_whitespace().count(), 24);
+        assert!(Wallet::new_from_mnemonic(network.clone(), &random, 0).is_ok());
+        assert!(Wallet::new_from_mnemonic(network, "not a mnemonic", 0).is_err());
+    }
+
+    #[test]
+    fn test_keystore_round_trip() {
+        let dir = std::env::temp_dir().join(format!("evmlib-keystore-{}", rand::random::<u64>()));
+        std::fs::create_dir_all(&dir).unwrap();
+        let private_key = Wallet::random_private_key();
+
+        let path = Wallet::write_keystore(&private_key, &dir, "wallet.json", "password").unwrap();
+        assert_eq!(
+            Wallet::private_key_from_keystore(&path, "password").unwrap(),
+            private_key
+        );
+        assert!(Wallet::private_key_from_keystore(&path, "wrong password").is_err());
+
+        std::fs::remove_dir_all(dir).unwrap();
+    }
+
     #[tokio::test]
     async fn test_transfer_gas_tokens() {
         let testnet = Testnet::new().await;
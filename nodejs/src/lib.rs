@@ -598,7 +598,7 @@ impl JsClient {
     ) -> Result</* (AttoTokens, JsPrivateArchive) */ tuple_result::DirContentUpload> {
         let dir_path = PathBuf::from(dir_path);
 
-        let (cost, archive) = self
+        let (cost, archive, _report) = self
             .0
             .dir_content_upload(dir_path, payment_option.0.clone())
             .await
@@ -618,7 +618,7 @@ impl JsClient {
     ) -> Result</* (AttoTokens, JsPrivateArchiveDataMap) */ tuple_result::DirUpload> {
         let dir_path = PathBuf::from(dir_path);
 
-        let (cost, data_map) = self
+        let (cost, data_map, _report) = self
             .0
             .dir_upload(dir_path, payment_option.0.clone())
             .await
@@ -689,7 +689,7 @@ impl JsClient {
     ) -> Result</* (AttoTokens, JsPublicArchive) */ tuple_result::DirContentUploadPublic> {
         let dir_path = PathBuf::from(dir_path);
 
-        let (cost, archive) = self
+        let (cost, archive, _report) = self
             .0
             .dir_content_upload_public(dir_path, payment_option.0.clone())
             .await
@@ -709,7 +709,7 @@ impl JsClient {
     ) -> Result</* (AttoTokens, JsArchiveAddress) */ tuple_result::DirUploadPublic> {
         let dir_path = PathBuf::from(dir_path);
 
-        let (cost, addr) = self
+        let (cost, addr, _report) = self
             .0
             .dir_upload_public(dir_path, payment_option.0.clone())
             .await